@@ -0,0 +1,2082 @@
+//! Text assembler and disassembler for the 0x66 anmchr commands, so that nobody has to hand-write hex
+//!
+//! One command per line. `;` starts a comment. Operands are separated by commas and/or spaces.
+//!
+//! Operands:
+//! - `r01` is register 01 (00-7f are integers, 80-ff are floats), `b01` is boolean register 01. Wrap a register in brackets like `[r05]` for an indirect register lookup.
+//! - `v01` is vector register 01 (00-1f). `[r05]` in place of a vector register looks up which vector register to use in register 05.
+//! - immediates are decimal (`-3`, `1.7`), hex (`0x10`, which for floats is the raw bits), `true` or `false`. Where the command accepts float replacement, write `r01` for the `01FFFFFF` pattern.
+//! - operations are names from the operation lists like `add` or `sqrtwithnegative`, ignoring case, or `r18` for operation replacement, or a raw number like `0xC0`.
+//! - variables are names from the variable lists like `ConditionRegister`, ignoring case, or a raw number like `0x40`.
+//! - character relations are `me`, `point`, `assist1`, `assist2`, `assist1.fallback`, `assist2.fallback`, `char1notme`, `ancestor`, `parent`, with an `opponent.` prefix for the opponent's side (`opponent` alone is the opponent's point character).
+//! - strings are in double quotes and can be up to 64 bytes, like `"Djinn\shot\AirthrowA"`.
+//! - tests for skipping and jumping are a register (`b01`, `r01`, `[r01]`) or `cond` for the condition register. Put `!` in front to flip it, like `!cond`.
+//! - jump offsets count 66 commands, starting from the command after the jump. A label can be used instead. Labels are written as `name:` on their own line. A `.hex` line counts as one command if it starts with a 66 command, and not at all otherwise, since skips and jumps step right over the game's own commands.
+//!
+//! Commands:
+//! ```text
+//! teleport.x <float>                                   ; 66_00
+//! teleport.y <float>                                   ; 66_01
+//!                                                      ; bounds is clamp, fail or wrap, for going past the stage walls
+//! load.imm <dst>, <imm>                                ; 66_10
+//! <binop>.imm <dst>, <lhs>, <imm>                      ; 66_11, or binop.imm <op>, <dst>, <lhs>, <imm>
+//! <binop>.reg <dst>, <lhs>, <rhs>                      ; 66_12, or binop.reg <op>, ...
+//! <unop>.reg <dst>, <src>                              ; 66_13, or unop.reg <op>, ...
+//! <unop>.imm <dst>, <imm>                              ; 66_14, or unop.imm <op>, ...
+//! load.var <dst>, <var>, <relation>                    ; 66_15
+//! store.var <src>, <var>, <relation>                   ; 66_16
+//! store.var.imm <var>, <relation>, <imm>               ; 66_17
+//! <binop>.var <var>, <relation>, <rhs>                 ; 66_18, or binop.var <op>, ...
+//! <binop>.var.imm <var>, <relation>, <imm>             ; 66_19, or binop.var.imm <op>, ...
+//! <unop>.var <var>, <relation>                         ; 66_1a, or unop.var <op>, ...
+//! char.name <dst>, <relation>, "<name>"                ; 66_1b
+//! cond <compare op>, <result op>, <dst>, <lhs>, <compare imm>, <result imm>, <lhs reset imm> ; 66_1c
+//! <terop>.reg <dst>, <a>, <b>, <c>                     ; 66_1d, or terop.reg <op>, ...
+//! <terop>.imm <dst>, <a>, <imm b>, <imm c>             ; 66_1e, or terop.imm <op>, ...
+//! skip.if <test>, <count>                              ; 66_20, or skip <count> to always skip
+//! jump.if <test>, <label or offset>                    ; 66_21, or jump <label or offset> to always jump
+//! schedule <delay>[, <repeat every>[, <tag>]]          ; 66_23, runs the next command later. delay and repeat can be r01
+//! schedule.cancel <tag>                                ; 66_24
+//! proj.get <relation> [backwards]                      ; 66_30
+//! proj.next                                            ; 66_31
+//! proj.filter <binop>, <projectile var>, <imm>, <relation> [backwards] ; 66_32
+//! proj.filter.name "<name>", <relation> [backwards]    ; 66_33
+//! load.proj <dst>, <projectile var>, <relation>        ; 66_35
+//! store.proj <src>, <projectile var>, <relation>       ; 66_36
+//! load.shared <dst>, <bank>, <shared register>         ; 66_44, bank is team, opponent or match
+//! store.shared <src>, <bank>, <shared register>        ; 66_45
+//! persist <policy>, <first>[, <last>]                  ; 66_46, policy is round, match or unload
+//! snapshot.save "<slot name>"                          ; 66_47
+//! snapshot.load "<slot name>"                          ; 66_48
+//! vec.load <vector>, <relation> [proj]                 ; 66_40
+//! vec.store <vector>, <relation> [proj]                ; 66_41
+//! vec.<vector op> <dst>, <lhs>[, <rhs or scalar>]      ; 66_42, see VectorOp for which operands each one takes
+//! vec.dir <vector>, <relation>                         ; 66_43
+//! suck.x <magnitude>, <delta>                          ; 66_50
+//! force <slot>, <relation>, <x>, <y>, <falloff>, <decay>, <radius> [proj|both] [radial] ; 66_51, falloff is linear or exp
+//! force.clear <slot>                                   ; 66_52, or force.clear all
+//! motion <dst>, <relation>, <motion>[, <window>[, <charge>]] ; 66_60, window and charge are frames, 0 for the defaults
+//! .hex 66000000 31000000                               ; raw bytes, for anything else
+//! ```
+//!
+//! Immediates are encoded the same way `handle_ano_command` decodes them, so the destination register (or variable) decides if the immediate is an integer or a float. When that can't be known ahead of time (indirect registers), the way the immediate is written decides it.
+
+#![deny(unsafe_op_in_unsafe_fn)]
+
+use std::collections::HashMap;
+use std::fmt;
+use std::fmt::Write;
+
+use byteorder::{ByteOrder, LittleEndian};
+
+use crate::anmchr_commands::{AnoCmd, ANO_COMMAND_GROUP};
+use crate::command_flow::BranchTest;
+use crate::binary_operators::BinaryOp;
+use crate::unary_operators::UnaryOp;
+use crate::ternary_operators::TernaryOp;
+use crate::vector::{VectorOp, PositionSource};
+use crate::game_data::RelationWithinTeam;
+use crate::storage::{CharStore, RegisterType, RegisterFlags, SharedBankSelect, Persistence};
+use crate::character_extensions::{ForceFlags, Falloff, FORCE_SLOTS};
+use crate::motion::{Motion, INPUT_HISTORY_LEN};
+use crate::var_rw;
+
+/// maximum length of a string argument, like in 66_1b and 66_33
+pub const STRING_CAPACITY : usize = 64;
+
+const RELATION_OPPONENT_MASK : u8 = 0x80;
+const PROJECTILE_FILTER_BACKWARDS : u32 = 0x01;
+
+#[derive(Debug)]
+pub struct AsmError {
+    /// 1-based line in the source text, if there is one
+    pub line : Option<usize>,
+    pub msg : String,
+}
+
+impl std::error::Error for AsmError {}
+
+impl fmt::Display for AsmError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "line {}: {}", line, self.msg),
+            None => write!(f, "{}", self.msg),
+        }
+    }
+}
+
+/// interpret a string that looks like "12abce53" to a series of hex bytes. whitespace is ignored.
+pub fn to_bytes(input : &str) -> Result<Vec<u8>, AsmError>
+{
+    let digits : Vec<char> = input.chars().filter(|c| !c.is_whitespace()).collect();
+    
+    if !digits.len().is_multiple_of(2) {
+        return Err(AsmError {
+            line : None,
+            msg : format!("odd number of hex digits in `{}`", input.trim()),
+        });
+    }
+    
+    let mut output : Vec<u8> = Vec::with_capacity(digits.len() / 2);
+    
+    for pair in digits.chunks(2) {
+        let byte : String = pair.iter().collect();
+        
+        match u8::from_str_radix(byte.as_str(), 16) {
+            Ok(byte) => output.push(byte),
+            Err(_) => {
+                return Err(AsmError {
+                    line : None,
+                    msg : format!("`{}` is not a hex byte", byte),
+                });
+            },
+        }
+    }
+    
+    Ok(output)
+}
+
+/// the reverse of `to_bytes`, 4 bytes per line like the examples in the docs
+pub fn to_hex_string(input : &[u8]) -> String
+{
+    let mut output = String::new();
+    
+    for (index, value) in input.iter().enumerate() {
+        if index != 0 && index % 4 == 0 {
+            output.push('\n');
+        }
+        output.push_str(format!("{:02X}",value).as_str());
+    }
+    
+    output
+}
+
+/// turn command text into the bytes that `handle_ano_command` decodes
+pub fn assemble(source : &str) -> Result<Vec<u8>, AsmError>
+{
+    let mut lines = Vec::new();
+    
+    for (index, line) in source.lines().enumerate() {
+        let tokens = tokenize(line).map_err(|msg| AsmError { line : Some(index + 1), msg })?;
+        
+        lines.push((index + 1, tokens));
+    }
+    
+    let labels = find_labels(&lines)?;
+    
+    let mut output = Vec::new();
+    let mut command_index = 0;
+    
+    for (line, tokens) in &lines {
+        if label_name(tokens).is_some() {
+            continue;
+        }
+        
+        if let Some((mnemonic, operands)) = tokens.split_first() {
+            let operands = resolve_label(mnemonic, operands, &labels, command_index);
+            
+            assemble_instruction(mnemonic, &operands, &mut output)
+                .map_err(|msg| AsmError { line : Some(*line), msg })?;
+            
+            if is_counted(tokens) {
+                command_index += 1;
+            }
+        }
+    }
+    
+    Ok(output)
+}
+
+/// `name:` on a line by itself
+fn label_name(tokens : &[String]) -> Option<&str>
+{
+    match tokens {
+        [token] => token.strip_suffix(':'),
+        _ => None,
+    }
+}
+
+/// which command each label is in front of
+fn find_labels(lines : &[(usize, Vec<String>)]) -> Result<HashMap<String, usize>, AsmError>
+{
+    let mut labels = HashMap::new();
+    let mut command_index = 0;
+    
+    for (line, tokens) in lines {
+        if let Some(name) = label_name(tokens) {
+            if name.is_empty() || parse_i32(name).is_ok() {
+                return Err(AsmError { line : Some(*line), msg : format!("`{}` can't be used as a label", name) });
+            }
+            
+            if labels.insert(String::from(name), command_index).is_some() {
+                return Err(AsmError { line : Some(*line), msg : format!("label `{}` is defined twice", name) });
+            }
+        } else if is_counted(tokens) {
+            command_index += 1;
+        }
+    }
+    
+    Ok(labels)
+}
+
+/// whether a line is a command that jumps count, which is any 66 command. a `.hex` line could be anything, so it's
+/// only counted if it starts with 66
+fn is_counted(tokens : &[String]) -> bool
+{
+    match tokens.split_first() {
+        Some((mnemonic, operands)) if mnemonic.eq_ignore_ascii_case(".hex") => {
+            to_bytes(&operands.concat()).is_ok_and(|bytes| bytes.starts_with(&ANO_COMMAND_GROUP.to_le_bytes()))
+        },
+        Some(_) => true,
+        None => false,
+    }
+}
+
+/// swap a label at the end of a jump for how many commands away it is
+fn resolve_label(mnemonic : &str, operands : &[String], labels : &HashMap<String, usize>, command_index : usize) -> Vec<String>
+{
+    let mut operands = operands.to_vec();
+    let is_jump = matches!(mnemonic.to_ascii_lowercase().as_str(), "jump" | "jump.if");
+    
+    if is_jump
+        && let Some(last) = operands.last_mut()
+        && let Some(&target) = labels.get(last.as_str()) {
+        let offset = target as i64 - (command_index as i64 + 1);
+        
+        *last = offset.to_string();
+    }
+    
+    operands
+}
+
+/// turn a dump of anmchr commands back into text that `assemble` accepts.
+/// commands that aren't 0x66 commands (or that can't be written any other way) come out as `.hex` lines.
+pub fn disassemble(bytes : &[u8]) -> String
+{
+    let mut output = String::new();
+    let mut position = 0;
+    
+    while position < bytes.len() {
+        let remaining = &bytes[position..];
+        
+        match disassemble_one(remaining) {
+            Disassembled::Command(text, len) => {
+                // only trust the text if it turns back into exactly the same bytes
+                let is_exact = match tokenize(&text) {
+                    Ok(tokens) => {
+                        let mut check = Vec::with_capacity(len);
+                        
+                        match tokens.split_first() {
+                            Some((mnemonic, operands)) => {
+                                assemble_instruction(mnemonic, operands, &mut check).is_ok() && check == remaining[..len]
+                            },
+                            None => false,
+                        }
+                    },
+                    Err(_) => false,
+                };
+                
+                if is_exact {
+                    let _ = writeln!(output, "{}", text);
+                } else {
+                    let _ = writeln!(output, ".hex {} ; {}", hex_words(&remaining[..len]), text);
+                }
+                
+                position += len;
+            },
+            Disassembled::Raw(len, note) => {
+                let _ = writeln!(output, ".hex {} ; {}", hex_words(&remaining[..len]), note);
+                
+                position += len;
+            },
+            Disassembled::Stop(note) => {
+                let _ = writeln!(output, ".hex {} ; {}", hex_words(remaining), note);
+                
+                break;
+            },
+        }
+    }
+    
+    output
+}
+
+fn hex_words(bytes : &[u8]) -> String
+{
+    let words : Vec<String> = bytes.chunks(4).map(|word| {
+        word.iter().map(|b| format!("{:02X}", b)).collect::<String>()
+    }).collect();
+    
+    words.join(" ")
+}
+
+/// split a line into mnemonic and operands, dropping any comment
+fn tokenize(line : &str) -> Result<Vec<String>, String>
+{
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    
+    for c in line.chars() {
+        if in_quotes {
+            current.push(c);
+            
+            if c == '"' {
+                in_quotes = false;
+                tokens.push(std::mem::take(&mut current));
+            }
+        } else if c == ';' {
+            break;
+        } else if c == '"' {
+            if !current.is_empty() {
+                tokens.push(std::mem::take(&mut current));
+            }
+            
+            in_quotes = true;
+            current.push(c);
+        } else if c.is_whitespace() || c == ',' {
+            if !current.is_empty() {
+                tokens.push(std::mem::take(&mut current));
+            }
+        } else {
+            current.push(c);
+        }
+    }
+    
+    if in_quotes {
+        return Err(String::from("string is missing its closing quote"));
+    }
+    
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+    
+    Ok(tokens)
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Register {
+    index : u8,
+    is_bool : bool,
+    is_indirect : bool,
+}
+
+impl Register {
+    fn parse(token : &str) -> Result<Self, String>
+    {
+        let (inner, is_indirect) = match token.strip_prefix('[').and_then(|t| t.strip_suffix(']')) {
+            Some(inner) => (inner, true),
+            None => (token, false),
+        };
+        
+        let (is_bool, digits) = if let Some(digits) = inner.strip_prefix(['r', 'R']) {
+            (false, digits)
+        } else if let Some(digits) = inner.strip_prefix(['b', 'B']) {
+            (true, digits)
+        } else {
+            return Err(format!("expected a register like r01 or b01, got `{}`", token));
+        };
+        
+        if digits.len() != 2 {
+            return Err(format!("register `{}` should have two hex digits", token));
+        }
+        
+        let index = u8::from_str_radix(digits, 16).map_err(|_| format!("register `{}` should have two hex digits", token))?;
+        
+        Ok(Self {
+            index,
+            is_bool,
+            is_indirect,
+        })
+    }
+    
+    fn format(index : u8, is_bool : bool, is_indirect : bool) -> String
+    {
+        let prefix = if is_bool { 'b' } else { 'r' };
+        
+        if is_indirect {
+            format!("[{}{:02X}]", prefix, index)
+        } else {
+            format!("{}{:02X}", prefix, index)
+        }
+    }
+    
+    /// the immediate type `handle_ano_command` will pick for this register, or None if it's only known at runtime
+    fn immediate_type(&self) -> Option<RegisterType>
+    {
+        if self.is_bool {
+            Some(RegisterType::I32)
+        } else if self.is_indirect {
+            None
+        } else {
+            Some(RegisterType::identify(self.index))
+        }
+    }
+}
+
+/// `v01`, or `[r05]` to use the vector register numbered in register 05. returns the index and if it's indirect
+fn parse_vector_register(token : &str) -> Result<(u8, bool), String>
+{
+    if token.starts_with('[') {
+        let register = Register::parse(token)?;
+        
+        if register.is_bool {
+            return Err(format!("can't look up a vector register in a boolean register, got `{}`", token));
+        }
+        
+        return Ok((register.index, true));
+    }
+    
+    let digits = token.strip_prefix(['v', 'V'])
+        .ok_or_else(|| format!("expected a vector register like v01, got `{}`", token))?;
+    
+    if digits.len() != 2 {
+        return Err(format!("vector register `{}` should have two hex digits", token));
+    }
+    
+    let index = u8::from_str_radix(digits, 16).map_err(|_| format!("vector register `{}` should have two hex digits", token))?;
+    
+    Ok((index, false))
+}
+
+fn format_vector_register(index : u8, is_indirect : bool) -> String
+{
+    if is_indirect {
+        format!("[r{:02X}]", index)
+    } else {
+        format!("v{:02X}", index)
+    }
+}
+
+fn parse_position_source(token : Option<&String>) -> Result<u32, String>
+{
+    match token {
+        None => Ok(PositionSource::Character as u32),
+        Some(token) if token.eq_ignore_ascii_case("proj") => Ok(PositionSource::Projectile as u32),
+        Some(token) => Err(format!("expected `proj` or nothing, got `{}`", token)),
+    }
+}
+
+/// a register or `cond`, either with a `!` in front
+fn parse_branch_test(token : &str) -> Result<(BranchTest, Option<Register>), String>
+{
+    let (is_flipped, inner) = match token.strip_prefix('!') {
+        Some(inner) => (true, inner),
+        None => (false, token),
+    };
+    
+    if inner.eq_ignore_ascii_case("cond") {
+        let test = if is_flipped { BranchTest::IfNotCondition } else { BranchTest::IfCondition };
+        
+        Ok((test, None))
+    } else {
+        let register = Register::parse(inner).map_err(|_| format!("expected a register or cond to test, got `{}`", token))?;
+        let test = if is_flipped { BranchTest::IfFalse } else { BranchTest::IfTrue };
+        
+        Ok((test, Some(register)))
+    }
+}
+
+/// None if it's not a test `parse_branch_test` would give back
+fn format_branch_test(test : u8, register : u8, flags : RegisterFlags) -> Option<String>
+{
+    let test : BranchTest = num::FromPrimitive::from_u8(test)?;
+    let register = Register::format(register, flags.is_lhs_bool(), flags.is_lhs_indirect());
+    
+    match test {
+        BranchTest::IfTrue => Some(register),
+        BranchTest::IfFalse => Some(format!("!{}", register)),
+        BranchTest::IfCondition => Some(String::from("cond")),
+        BranchTest::IfNotCondition => Some(String::from("!cond")),
+        BranchTest::Always => None,
+    }
+}
+
+/// float replacement, see `CharStore::F32_RELOAD_MASK`
+fn parse_replacement(token : &str) -> Option<u32>
+{
+    let digits = token.strip_prefix(['r', 'R'])?;
+    
+    if digits.len() != 2 {
+        return None;
+    }
+    
+    let index = u8::from_str_radix(digits, 16).ok()?;
+    
+    Some(CharStore::F32_RELOAD_MASK | index as u32)
+}
+
+fn is_replacement(bits : u32) -> bool
+{
+    (bits & CharStore::F32_RELOAD_MASK) == CharStore::F32_RELOAD_MASK
+}
+
+fn parse_hex_u32(token : &str) -> Option<u32>
+{
+    let digits = token.strip_prefix("0x").or_else(|| token.strip_prefix("0X"))?;
+    
+    u32::from_str_radix(digits, 16).ok()
+}
+
+fn is_bool_literal(token : &str) -> bool
+{
+    token.eq_ignore_ascii_case("true") || token.eq_ignore_ascii_case("false")
+}
+
+fn parse_i32(token : &str) -> Result<i32, String>
+{
+    if token.eq_ignore_ascii_case("true") {
+        return Ok(1);
+    } else if token.eq_ignore_ascii_case("false") {
+        return Ok(0);
+    }
+    
+    let (is_negative, unsigned) = match token.strip_prefix('-') {
+        Some(unsigned) => (true, unsigned),
+        None => (false, token),
+    };
+    
+    if let Some(bits) = parse_hex_u32(unsigned) {
+        let value = bits as i32;
+        
+        return Ok(if is_negative { value.wrapping_neg() } else { value });
+    }
+    
+    if parse_replacement(token).is_some() {
+        return Err(format!("`{}`: register replacement only works for floating point values", token));
+    }
+    
+    token.parse::<i32>().map_err(|_| format!("expected an integer, got `{}`", token))
+}
+
+fn parse_f32(token : &str) -> Result<u32, String>
+{
+    if token.eq_ignore_ascii_case("true") {
+        return Ok(1.0f32.to_bits());
+    } else if token.eq_ignore_ascii_case("false") {
+        return Ok(0.0f32.to_bits());
+    }
+    
+    if let Some(bits) = parse_hex_u32(token) {
+        return Ok(bits);
+    }
+    
+    if let Some(bits) = parse_replacement(token) {
+        return Ok(bits);
+    }
+    
+    match token.parse::<f32>() {
+        Ok(value) if value.is_finite() => Ok(value.to_bits()),
+        Ok(_) => Err(format!("`{}` isn't a finite number, mag_patch would turn it into 0", token)),
+        Err(_) => Err(format!("expected a number, got `{}`", token)),
+    }
+}
+
+/// looks like a float when the type has to be guessed from how it's written
+fn is_float_literal(token : &str) -> bool
+{
+    if parse_hex_u32(token).is_some() || is_bool_literal(token) {
+        false
+    } else {
+        parse_replacement(token).is_some() || token.contains(['.', 'e', 'E'])
+    }
+}
+
+fn encode_immediate(token : &str, immediate_type : Option<RegisterType>) -> Result<u32, String>
+{
+    match immediate_type {
+        Some(RegisterType::F32) => parse_f32(token),
+        Some(RegisterType::I32 | RegisterType::Bool) => parse_i32(token).map(|value| value as u32),
+        None => {
+            if is_float_literal(token) {
+                parse_f32(token)
+            } else {
+                parse_i32(token).map(|value| value as u32)
+            }
+        },
+    }
+}
+
+fn format_f32(bits : u32) -> String
+{
+    let value = f32::from_bits(bits);
+    
+    if is_replacement(bits) {
+        format!("r{:02X}", bits & 0xFF)
+    } else if value.is_finite() {
+        format!("{:?}", value)
+    } else {
+        format!("0x{:08X}", bits)
+    }
+}
+
+fn format_immediate(bits : u32, immediate_type : Option<RegisterType>) -> String
+{
+    match immediate_type {
+        Some(RegisterType::F32) => format_f32(bits),
+        _ => format!("{}", bits as i32),
+    }
+}
+
+fn parse_binary_op(token : &str) -> Result<u32, String>
+{
+    if let Some(op) = BinaryOp::from_name(token) {
+        Ok(op as u32)
+    } else if let Some(bits) = parse_replacement(token).or_else(|| parse_hex_u32(token)) {
+        Ok(bits)
+    } else {
+        Err(format!("unknown binary operation `{}`", token))
+    }
+}
+
+fn parse_unary_op(token : &str) -> Result<u32, String>
+{
+    if let Some(op) = UnaryOp::from_name(token) {
+        Ok(op as u32)
+    } else if let Some(bits) = parse_replacement(token).or_else(|| parse_hex_u32(token)) {
+        Ok(bits)
+    } else {
+        Err(format!("unknown unary operation `{}`", token))
+    }
+}
+
+fn parse_ternary_op(token : &str) -> Result<u32, String>
+{
+    if let Some(op) = TernaryOp::from_name(token) {
+        Ok(op as u32)
+    } else if let Some(bits) = parse_replacement(token).or_else(|| parse_hex_u32(token)) {
+        Ok(bits)
+    } else {
+        Err(format!("unknown ternary operation `{}`", token))
+    }
+}
+
+fn format_binary_op(bits : u32) -> String
+{
+    let op : Option<BinaryOp> = num::FromPrimitive::from_u32(bits);
+    
+    match op {
+        Some(op) => op.name().to_ascii_lowercase(),
+        None if is_replacement(bits) => format!("r{:02X}", bits & 0xFF),
+        None => format!("0x{:X}", bits),
+    }
+}
+
+fn format_unary_op(bits : u32) -> String
+{
+    let op : Option<UnaryOp> = num::FromPrimitive::from_u32(bits);
+    
+    match op {
+        Some(op) => op.name().to_ascii_lowercase(),
+        None if is_replacement(bits) => format!("r{:02X}", bits & 0xFF),
+        None => format!("0x{:X}", bits),
+    }
+}
+
+fn format_ternary_op(bits : u32) -> String
+{
+    let op : Option<TernaryOp> = num::FromPrimitive::from_u32(bits);
+    
+    match op {
+        Some(op) => op.name().to_ascii_lowercase(),
+        None if is_replacement(bits) => format!("r{:02X}", bits & 0xFF),
+        None => format!("0x{:X}", bits),
+    }
+}
+
+fn parse_var(token : &str) -> Result<u32, String>
+{
+    var_rw::MatchState::from_name(token)
+        .or_else(|| parse_hex_u32(token))
+        .ok_or_else(|| format!("unknown variable `{}`", token))
+}
+
+fn parse_projectile_var(token : &str) -> Result<u32, String>
+{
+    var_rw::ProjectileState::from_name(token)
+        .or_else(|| parse_hex_u32(token))
+        .ok_or_else(|| format!("unknown projectile variable `{}`", token))
+}
+
+fn format_var(var : u32) -> String
+{
+    match var_rw::MatchState::name(var) {
+        Some(name) => String::from(name),
+        None => format!("0x{:X}", var),
+    }
+}
+
+fn format_projectile_var(var : u32) -> String
+{
+    match var_rw::ProjectileState::name(var) {
+        Some(name) => String::from(name),
+        None => format!("0x{:X}", var),
+    }
+}
+
+const RELATION_NAMES : [(&str, u8); 9] = [
+    ("me", RelationWithinTeam::Me as u8),
+    ("point", RelationWithinTeam::Point as u8),
+    ("assist1", RelationWithinTeam::Assist1NoFallBack as u8),
+    ("assist2", RelationWithinTeam::Assist2NoFallBack as u8),
+    ("assist1.fallback", RelationWithinTeam::Assist1WithFallback as u8),
+    ("assist2.fallback", RelationWithinTeam::Assist2WithFallback as u8),
+    ("char1notme", RelationWithinTeam::Char1NotMe as u8),
+    ("ancestor", RelationWithinTeam::TrueAncestor as u8),
+    ("parent", RelationWithinTeam::Parent as u8),
+];
+
+fn parse_relation(token : &str) -> Result<u8, String>
+{
+    if let Some(bits) = parse_hex_u32(token) {
+        return u8::try_from(bits).map_err(|_| format!("character relation `{}` doesn't fit in a byte", token));
+    }
+    
+    let lower = token.to_ascii_lowercase();
+    
+    let (opponent_mask, within_team) = if lower == "opponent" {
+        (RELATION_OPPONENT_MASK, "me")
+    } else if let Some(within_team) = lower.strip_prefix("opponent.") {
+        (RELATION_OPPONENT_MASK, within_team)
+    } else {
+        (0, lower.as_str())
+    };
+    
+    RELATION_NAMES.iter()
+        .find(|(name, _)| *name == within_team)
+        .map(|(_, relation)| opponent_mask | *relation)
+        .ok_or_else(|| format!("unknown character relation `{}`", token))
+}
+
+fn format_relation(byte : u8) -> String
+{
+    let within_team = byte & !RELATION_OPPONENT_MASK;
+    
+    let name = RELATION_NAMES.iter().find(|(_, relation)| *relation == within_team).map(|(name, _)| *name);
+    
+    match name {
+        Some(name) if (byte & RELATION_OPPONENT_MASK) == RELATION_OPPONENT_MASK => {
+            if within_team == RelationWithinTeam::Me as u8 {
+                String::from("opponent")
+            } else {
+                format!("opponent.{}", name)
+            }
+        },
+        Some(name) => String::from(name),
+        None => format!("0x{:02X}", byte),
+    }
+}
+
+fn parse_force_slot(token : &str) -> Result<u8, String>
+{
+    let slot = parse_i32(token)?;
+    
+    if (0..FORCE_SLOTS as i32).contains(&slot) {
+        Ok(slot as u8)
+    } else {
+        Err(format!("force slot `{}` should be from 0 to {}", token, FORCE_SLOTS - 1))
+    }
+}
+
+/// window and charge for 66_60
+fn parse_motion_frames(token : Option<&String>) -> Result<u8, String>
+{
+    let Some(token) = token else {
+        return Ok(0);
+    };
+    
+    let frames = parse_i32(token)?;
+    
+    if (0..=INPUT_HISTORY_LEN as i32).contains(&frames) {
+        Ok(frames as u8)
+    } else {
+        Err(format!("`{}` frames should be from 0 to {}", token, INPUT_HISTORY_LEN))
+    }
+}
+
+fn parse_string(token : &str) -> Result<[u8; STRING_CAPACITY], String>
+{
+    let inner = token.strip_prefix('"').and_then(|t| t.strip_suffix('"'))
+        .ok_or_else(|| format!("expected a string in double quotes, got `{}`", token))?;
+    
+    if inner.len() > STRING_CAPACITY {
+        return Err(format!("string `{}` is longer than {} bytes", inner, STRING_CAPACITY));
+    }
+    
+    let mut output = [0; STRING_CAPACITY];
+    output[..inner.len()].copy_from_slice(inner.as_bytes());
+    
+    Ok(output)
+}
+
+fn format_string(bytes : &[u8]) -> String
+{
+    let len = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+    
+    // anything that can't go back through parse_string gets caught by the round trip check
+    format!("\"{}\"", String::from_utf8_lossy(&bytes[..len]))
+}
+
+fn parse_projectile_order(token : Option<&String>) -> Result<u32, String>
+{
+    match token {
+        None => Ok(0),
+        Some(token) if token.eq_ignore_ascii_case("backwards") => Ok(PROJECTILE_FILTER_BACKWARDS),
+        Some(token) if token.eq_ignore_ascii_case("forwards") => Ok(0),
+        Some(token) => Err(format!("expected `backwards` or nothing, got `{}`", token)),
+    }
+}
+
+fn format_projectile_order(flags : u32) -> &'static str
+{
+    if flags == PROJECTILE_FILTER_BACKWARDS {
+        " backwards"
+    } else {
+        ""
+    }
+}
+
+struct Emitter<'a> {
+    output : &'a mut Vec<u8>,
+}
+
+impl Emitter<'_> {
+    fn command(&mut self, command : AnoCmd)
+    {
+        self.u32(ANO_COMMAND_GROUP);
+        self.u32(command as u32);
+    }
+    
+    fn u32(&mut self, value : u32)
+    {
+        self.output.extend_from_slice(&value.to_le_bytes());
+    }
+    
+    fn bytes(&mut self, bytes : &[u8])
+    {
+        self.output.extend_from_slice(bytes);
+    }
+}
+
+fn expect_operands(mnemonic : &str, operands : &[String], min : usize, max : usize) -> Result<(), String>
+{
+    if operands.len() < min || operands.len() > max {
+        if min == max {
+            Err(format!("`{}` takes {} operands, got {}", mnemonic, min, operands.len()))
+        } else {
+            Err(format!("`{}` takes {} to {} operands, got {}", mnemonic, min, max, operands.len()))
+        }
+    } else {
+        Ok(())
+    }
+}
+
+/// `add.imm r01, r01, 1` is shorthand for `binop.imm add, r01, r01, 1`
+fn expand_shorthand(mnemonic : &str, operands : &[String]) -> Result<(String, Vec<String>), String>
+{
+    let lower = mnemonic.to_ascii_lowercase();
+    
+    let (head, form) = match lower.split_once('.') {
+        Some(split) => split,
+        None => return Ok((lower.clone(), operands.to_vec())),
+    };
+    
+    match head {
+        "teleport" | "load" | "store" | "binop" | "unop" | "terop" | "vec" | "char" | "proj" | "snapshot" | "schedule" | "suck" | "force" | "skip" | "jump" | "" => {
+            Ok((lower.clone(), operands.to_vec()))
+        },
+        _ => {
+            let is_binary = BinaryOp::from_name(head).is_some();
+            let is_unary = UnaryOp::from_name(head).is_some();
+            let is_ternary = TernaryOp::from_name(head).is_some();
+            
+            let canonical = match (is_binary, is_unary, is_ternary) {
+                (true, false, false) => format!("binop.{}", form),
+                (false, true, false) => format!("unop.{}", form),
+                (false, false, true) => format!("terop.{}", form),
+                (false, false, false) => return Err(format!("unknown command `{}`", mnemonic)),
+                _ => return Err(format!("`{}` is the name of more than one kind of operation, use binop.{}, unop.{} or terop.{}", head, form, form, form)),
+            };
+            
+            let mut expanded = Vec::with_capacity(operands.len() + 1);
+            expanded.push(String::from(head));
+            expanded.extend_from_slice(operands);
+            
+            Ok((canonical, expanded))
+        },
+    }
+}
+
+fn assemble_instruction(mnemonic : &str, operands : &[String], output : &mut Vec<u8>) -> Result<(), String>
+{
+    let (mnemonic, operands) = expand_shorthand(mnemonic, operands)?;
+    let operands = operands.as_slice();
+    let mnemonic = mnemonic.as_str();
+    
+    // build into a scratch buffer so a failed line doesn't leave half a command behind
+    let mut buffer = Vec::with_capacity(32);
+    let mut out = Emitter { output : &mut buffer };
+    
+    match mnemonic {
+        ".hex" => {
+            for operand in operands {
+                let bytes = to_bytes(operand).map_err(|e| e.msg)?;
+                out.bytes(&bytes);
+            }
+        },
+        "teleport.x" | "teleport.y" => {
+            expect_operands(mnemonic, operands, 1, 1)?;
+            
+            out.command(if mnemonic == "teleport.x" { AnoCmd::RelativeTeleportX } else { AnoCmd::RelativeTeleportY });
+            
+            out.u32(parse_f32(&operands[0])?);
+        },
+        "load.imm" => {
+            expect_operands(mnemonic, operands, 2, 2)?;
+            
+            let destination = Register::parse(&operands[0])?;
+            let flags = RegisterFlags::new(0)
+                .set_destination_bool(destination.is_bool)
+                .set_destination_indirect(destination.is_indirect);
+            
+            out.command(AnoCmd::LoadImmediateIntoRegister);
+            out.bytes(&[0, 0, flags.raw(), destination.index]);
+            out.u32(encode_immediate(&operands[1], destination.immediate_type())?);
+        },
+        "binop.imm" => {
+            expect_operands(mnemonic, operands, 4, 4)?;
+            
+            let destination = Register::parse(&operands[1])?;
+            let lhs = Register::parse(&operands[2])?;
+            let flags = RegisterFlags::new(0)
+                .set_lhs_bool(lhs.is_bool)
+                .set_lhs_indirect(lhs.is_indirect)
+                .set_rhs_bool(is_bool_literal(&operands[3]))
+                .set_destination_bool(destination.is_bool)
+                .set_destination_indirect(destination.is_indirect);
+            
+            // the immediate's type comes from the destination's register number, even for boolean destinations
+            let immediate_type = if destination.is_indirect { None } else { Some(RegisterType::identify(destination.index)) };
+            
+            out.command(AnoCmd::BinaryOperationRegisterImmediate);
+            out.u32(parse_binary_op(&operands[0])?);
+            out.bytes(&[lhs.index, 0, flags.raw(), destination.index]);
+            out.u32(encode_immediate(&operands[3], immediate_type)?);
+        },
+        "binop.reg" => {
+            expect_operands(mnemonic, operands, 4, 4)?;
+            
+            let destination = Register::parse(&operands[1])?;
+            let lhs = Register::parse(&operands[2])?;
+            let rhs = Register::parse(&operands[3])?;
+            let flags = RegisterFlags::new(0)
+                .set_lhs_bool(lhs.is_bool)
+                .set_lhs_indirect(lhs.is_indirect)
+                .set_rhs_bool(rhs.is_bool)
+                .set_rhs_indirect(rhs.is_indirect)
+                .set_destination_bool(destination.is_bool)
+                .set_destination_indirect(destination.is_indirect);
+            
+            out.command(AnoCmd::BinaryOperationRegisterRegister);
+            out.u32(parse_binary_op(&operands[0])?);
+            out.bytes(&[lhs.index, rhs.index, flags.raw(), destination.index]);
+        },
+        "terop.reg" => {
+            expect_operands(mnemonic, operands, 5, 5)?;
+            
+            let destination = Register::parse(&operands[1])?;
+            let first = Register::parse(&operands[2])?;
+            let second = Register::parse(&operands[3])?;
+            let third = Register::parse(&operands[4])?;
+            let flags = RegisterFlags::new(0)
+                .set_lhs_bool(first.is_bool)
+                .set_lhs_indirect(first.is_indirect)
+                .set_rhs_bool(second.is_bool)
+                .set_rhs_indirect(second.is_indirect)
+                .set_third_bool(third.is_bool)
+                .set_third_indirect(third.is_indirect)
+                .set_destination_bool(destination.is_bool)
+                .set_destination_indirect(destination.is_indirect);
+            
+            out.command(AnoCmd::TernaryOperationRegisters);
+            out.u32(parse_ternary_op(&operands[0])?);
+            out.bytes(&[first.index, second.index, flags.raw(), destination.index]);
+            out.bytes(&[third.index, 0, 0, 0]);
+        },
+        "terop.imm" => {
+            expect_operands(mnemonic, operands, 5, 5)?;
+            
+            let destination = Register::parse(&operands[1])?;
+            let first = Register::parse(&operands[2])?;
+            let flags = RegisterFlags::new(0)
+                .set_lhs_bool(first.is_bool)
+                .set_lhs_indirect(first.is_indirect)
+                .set_destination_bool(destination.is_bool)
+                .set_destination_indirect(destination.is_indirect);
+            
+            // same as binop.imm, the destination's register number decides the immediates' type
+            let immediate_type = if destination.is_indirect { None } else { Some(RegisterType::identify(destination.index)) };
+            
+            out.command(AnoCmd::TernaryOperationRegisterImmediate);
+            out.u32(parse_ternary_op(&operands[0])?);
+            out.bytes(&[first.index, 0, flags.raw(), destination.index]);
+            out.u32(encode_immediate(&operands[3], immediate_type)?);
+            out.u32(encode_immediate(&operands[4], immediate_type)?);
+        },
+        "unop.reg" => {
+            expect_operands(mnemonic, operands, 3, 3)?;
+            
+            let destination = Register::parse(&operands[1])?;
+            let source = Register::parse(&operands[2])?;
+            let flags = RegisterFlags::new(0)
+                .set_lhs_bool(source.is_bool)
+                .set_lhs_indirect(source.is_indirect)
+                .set_destination_bool(destination.is_bool)
+                .set_destination_indirect(destination.is_indirect);
+            
+            out.command(AnoCmd::UnaryOperationRegister);
+            out.u32(parse_unary_op(&operands[0])?);
+            out.bytes(&[source.index, 0, flags.raw(), destination.index]);
+        },
+        "unop.imm" => {
+            expect_operands(mnemonic, operands, 3, 3)?;
+            
+            let destination = Register::parse(&operands[1])?;
+            let is_bool_immediate = is_bool_literal(&operands[2]);
+            let flags = RegisterFlags::new(0)
+                .set_lhs_bool(is_bool_immediate)
+                .set_destination_bool(destination.is_bool)
+                .set_destination_indirect(destination.is_indirect);
+            
+            let immediate_type = if is_bool_immediate { Some(RegisterType::Bool) } else { destination.immediate_type() };
+            
+            out.command(AnoCmd::UnaryOperationImmediate);
+            out.u32(parse_unary_op(&operands[0])?);
+            out.bytes(&[0, 0, flags.raw(), destination.index]);
+            out.u32(encode_immediate(&operands[2], immediate_type)?);
+        },
+        "load.var" | "load.proj" | "store.var" | "store.proj" => {
+            expect_operands(mnemonic, operands, 3, 3)?;
+            
+            let register = Register::parse(&operands[0])?;
+            let (command, var, flags) = match mnemonic {
+                "load.var" => (AnoCmd::LoadVarIntoRegister, parse_var(&operands[1])?, RegisterFlags::new(0)
+                    .set_destination_bool(register.is_bool)
+                    .set_destination_indirect(register.is_indirect)),
+                "load.proj" => (AnoCmd::LoadProjectileVarIntoRegister, parse_projectile_var(&operands[1])?, RegisterFlags::new(0)
+                    .set_destination_bool(register.is_bool)
+                    .set_destination_indirect(register.is_indirect)),
+                "store.var" => (AnoCmd::StoreVarFromRegister, parse_var(&operands[1])?, RegisterFlags::new(0)
+                    .set_lhs_bool(register.is_bool)
+                    .set_lhs_indirect(register.is_indirect)),
+                _ => (AnoCmd::StoreProjectileVarFromRegister, parse_projectile_var(&operands[1])?, RegisterFlags::new(0)
+                    .set_lhs_bool(register.is_bool)
+                    .set_lhs_indirect(register.is_indirect)),
+            };
+            let relation = parse_relation(&operands[2])?;
+            
+            out.command(command);
+            out.bytes(&[0, relation, flags.raw(), register.index]);
+            out.u32(var);
+        },
+        "load.shared" | "store.shared" => {
+            expect_operands(mnemonic, operands, 3, 3)?;
+            
+            let register = Register::parse(&operands[0])?;
+            let bank = SharedBankSelect::from_name(&operands[1])
+                .ok_or_else(|| format!("unknown shared bank `{}`, expected team, opponent or match", operands[1]))?;
+            let shared = Register::parse(&operands[2])?;
+            
+            let (command, flags) = if mnemonic == "load.shared" {
+                (AnoCmd::LoadSharedRegister, RegisterFlags::new(0)
+                    .set_lhs_bool(shared.is_bool)
+                    .set_lhs_indirect(shared.is_indirect)
+                    .set_destination_bool(register.is_bool)
+                    .set_destination_indirect(register.is_indirect))
+            } else {
+                (AnoCmd::StoreSharedRegister, RegisterFlags::new(0)
+                    .set_lhs_bool(register.is_bool)
+                    .set_lhs_indirect(register.is_indirect)
+                    .set_destination_bool(shared.is_bool)
+                    .set_destination_indirect(shared.is_indirect))
+            };
+            
+            out.command(command);
+            out.bytes(&[bank as u8, shared.index, flags.raw(), register.index]);
+        },
+        "persist" => {
+            expect_operands(mnemonic, operands, 2, 3)?;
+            
+            let persistence = Persistence::from_name(&operands[0])
+                .ok_or_else(|| format!("unknown persistence `{}`, expected round, match or unload", operands[0]))?;
+            let first = Register::parse(&operands[1])?;
+            let last = match operands.get(2) {
+                Some(last) => Register::parse(last)?,
+                None => first,
+            };
+            
+            if first.is_indirect || last.is_indirect {
+                return Err(String::from("persist can't use indirect registers"));
+            }
+            if first.is_bool != last.is_bool {
+                return Err(String::from("persist needs both ends of the range to be boolean registers, or neither"));
+            }
+            
+            let flags = RegisterFlags::new(0).set_lhs_bool(first.is_bool);
+            
+            out.command(AnoCmd::SetRegisterPersistence);
+            out.bytes(&[first.index, last.index, flags.raw(), persistence as u8]);
+        },
+        "store.var.imm" => {
+            expect_operands(mnemonic, operands, 3, 3)?;
+            
+            let var = parse_var(&operands[0])?;
+            let relation = parse_relation(&operands[1])?;
+            
+            out.command(AnoCmd::StoreVarFromImmediate);
+            out.bytes(&[0, relation, 0, 0]);
+            out.u32(var);
+            out.u32(encode_immediate(&operands[2], var_rw::MatchState::get_number_type(var))?);
+        },
+        "binop.var" => {
+            expect_operands(mnemonic, operands, 4, 4)?;
+            
+            let var = parse_var(&operands[1])?;
+            let relation = parse_relation(&operands[2])?;
+            let rhs = Register::parse(&operands[3])?;
+            let flags = RegisterFlags::new(0)
+                .set_rhs_bool(rhs.is_bool)
+                .set_rhs_indirect(rhs.is_indirect);
+            
+            out.command(AnoCmd::BinaryOperationVarRegister);
+            out.u32(parse_binary_op(&operands[0])?);
+            out.bytes(&[rhs.index, relation, flags.raw(), 0]);
+            out.u32(var);
+        },
+        "binop.var.imm" => {
+            expect_operands(mnemonic, operands, 4, 4)?;
+            
+            let var = parse_var(&operands[1])?;
+            let relation = parse_relation(&operands[2])?;
+            
+            out.command(AnoCmd::BinaryOperationVarImmediate);
+            out.u32(parse_binary_op(&operands[0])?);
+            out.bytes(&[0, relation, 0, 0]);
+            out.u32(var);
+            out.u32(encode_immediate(&operands[3], var_rw::MatchState::get_number_type(var))?);
+        },
+        "unop.var" => {
+            expect_operands(mnemonic, operands, 3, 3)?;
+            
+            let var = parse_var(&operands[1])?;
+            let relation = parse_relation(&operands[2])?;
+            
+            out.command(AnoCmd::UnaryOperationVar);
+            out.u32(parse_unary_op(&operands[0])?);
+            out.bytes(&[0, relation, 0, 0]);
+            out.u32(var);
+        },
+        "char.name" => {
+            expect_operands(mnemonic, operands, 3, 3)?;
+            
+            let destination = Register::parse(&operands[0])?;
+            let relation = parse_relation(&operands[1])?;
+            let flags = RegisterFlags::new(0)
+                .set_destination_bool(destination.is_bool)
+                .set_destination_indirect(destination.is_indirect);
+            
+            out.command(AnoCmd::CheckCharacterName);
+            out.bytes(&[0, relation, flags.raw(), destination.index]);
+            out.bytes(&parse_string(&operands[2])?);
+        },
+        "motion" => {
+            expect_operands(mnemonic, operands, 3, 5)?;
+            
+            let destination = Register::parse(&operands[0])?;
+            let relation = parse_relation(&operands[1])?;
+            let motion = Motion::from_name(&operands[2])
+                .ok_or_else(|| format!("unknown motion `{}`, it should be qcf, qcb, dp, 360, charge.bf, charge.du, tap.f or tap.b", operands[2]))?;
+            let flags = RegisterFlags::new(0)
+                .set_destination_bool(destination.is_bool)
+                .set_destination_indirect(destination.is_indirect);
+            
+            out.command(AnoCmd::CheckMotion);
+            out.bytes(&[motion as u8, relation, flags.raw(), destination.index]);
+            out.bytes(&[parse_motion_frames(operands.get(3))?, parse_motion_frames(operands.get(4))?, 0, 0]);
+        },
+        "cond" => {
+            expect_operands(mnemonic, operands, 7, 7)?;
+            
+            let destination = Register::parse(&operands[2])?;
+            let lhs = Register::parse(&operands[3])?;
+            let flags = RegisterFlags::new(0)
+                .set_lhs_bool(lhs.is_bool)
+                .set_lhs_indirect(lhs.is_indirect)
+                .set_destination_bool(destination.is_bool)
+                .set_destination_indirect(destination.is_indirect);
+            
+            out.command(AnoCmd::ConditionalBinaryOperation);
+            out.u32(parse_binary_op(&operands[0])?);
+            out.u32(parse_binary_op(&operands[1])?);
+            out.bytes(&[lhs.index, 0, flags.raw(), destination.index]);
+            out.u32(encode_immediate(&operands[4], lhs.immediate_type())?);
+            out.u32(encode_immediate(&operands[5], destination.immediate_type())?);
+            out.u32(encode_immediate(&operands[6], lhs.immediate_type())?);
+        },
+        "vec.load" | "vec.store" => {
+            expect_operands(mnemonic, operands, 2, 3)?;
+            
+            let (vector, is_indirect) = parse_vector_register(&operands[0])?;
+            let relation = parse_relation(&operands[1])?;
+            let (command, flags) = if mnemonic == "vec.load" {
+                (AnoCmd::LoadVectorFromPosition, RegisterFlags::new(0).set_destination_indirect(is_indirect))
+            } else {
+                (AnoCmd::StoreVectorToPosition, RegisterFlags::new(0).set_lhs_indirect(is_indirect))
+            };
+            
+            out.command(command);
+            out.bytes(&[0, relation, flags.raw(), vector]);
+            out.u32(parse_position_source(operands.get(2))?);
+        },
+        "vec.dir" => {
+            expect_operands(mnemonic, operands, 2, 2)?;
+            
+            let (vector, is_indirect) = parse_vector_register(&operands[0])?;
+            let relation = parse_relation(&operands[1])?;
+            let flags = RegisterFlags::new(0).set_destination_indirect(is_indirect);
+            
+            out.command(AnoCmd::DirectionToCharacter);
+            out.bytes(&[0, relation, flags.raw(), vector]);
+        },
+        _ if mnemonic.starts_with("vec.") => {
+            let op = VectorOp::from_name(&mnemonic["vec.".len()..])
+                .ok_or_else(|| format!("unknown vector operation `{}`", mnemonic))?;
+            
+            let operand_count = if op.uses_rhs() || op.uses_scalar() { 3 } else { 2 };
+            expect_operands(mnemonic, operands, operand_count, operand_count)?;
+            
+            let mut flags = RegisterFlags::new(0);
+            
+            let destination = if op.has_number_result() {
+                let destination = Register::parse(&operands[0])?;
+                flags = flags.set_destination_bool(destination.is_bool).set_destination_indirect(destination.is_indirect);
+                
+                destination.index
+            } else {
+                let (destination, is_indirect) = parse_vector_register(&operands[0])?;
+                flags = flags.set_destination_indirect(is_indirect);
+                
+                destination
+            };
+            
+            let (lhs, rhs) = if op.has_number_operands() {
+                let lhs = Register::parse(&operands[1])?;
+                let rhs = Register::parse(&operands[2])?;
+                flags = flags.set_lhs_bool(lhs.is_bool).set_lhs_indirect(lhs.is_indirect)
+                    .set_rhs_bool(rhs.is_bool).set_rhs_indirect(rhs.is_indirect);
+                
+                (lhs.index, rhs.index)
+            } else {
+                let (lhs, is_lhs_indirect) = parse_vector_register(&operands[1])?;
+                flags = flags.set_lhs_indirect(is_lhs_indirect);
+                
+                let rhs = if op.uses_rhs() {
+                    let (rhs, is_rhs_indirect) = parse_vector_register(&operands[2])?;
+                    flags = flags.set_rhs_indirect(is_rhs_indirect);
+                    
+                    rhs
+                } else {
+                    0
+                };
+                
+                (lhs, rhs)
+            };
+            
+            let scalar = if op.uses_scalar() { parse_f32(&operands[2])? } else { 0 };
+            
+            out.command(AnoCmd::VectorOperation);
+            out.u32(op as u32);
+            out.bytes(&[lhs, rhs, flags.raw(), destination]);
+            out.u32(scalar);
+        },
+        "proj.get" => {
+            expect_operands(mnemonic, operands, 1, 2)?;
+            
+            let relation = parse_relation(&operands[0])?;
+            
+            out.command(AnoCmd::GetProjectile);
+            out.bytes(&[0, relation, 0, 0]);
+            out.u32(parse_projectile_order(operands.get(1))?);
+        },
+        "proj.next" => {
+            expect_operands(mnemonic, operands, 0, 0)?;
+            
+            out.command(AnoCmd::NextProjectile);
+        },
+        "proj.filter" => {
+            expect_operands(mnemonic, operands, 4, 5)?;
+            
+            let var = parse_projectile_var(&operands[1])?;
+            let relation = parse_relation(&operands[3])?;
+            
+            // the decoder types this immediate with the character variable table, so we do too
+            let immediate_type = var_rw::MatchState::get_number_type(var)
+                .ok_or_else(|| format!("projectile variable `{}` can't be used as a filter", operands[1]))?;
+            
+            out.command(AnoCmd::GetProjectileFilteredByOperation);
+            out.u32(parse_binary_op(&operands[0])?);
+            out.bytes(&[0, relation, 0, 0]);
+            out.u32(parse_projectile_order(operands.get(4))?);
+            out.u32(var);
+            out.u32(encode_immediate(&operands[2], Some(immediate_type))?);
+        },
+        "proj.filter.name" => {
+            expect_operands(mnemonic, operands, 2, 3)?;
+            
+            let name = parse_string(&operands[0])?;
+            let relation = parse_relation(&operands[1])?;
+            
+            out.command(AnoCmd::GetProjectileFilteredByFilename);
+            out.bytes(&[0, relation, 0, 0]);
+            out.u32(parse_projectile_order(operands.get(2))?);
+            out.bytes(&name);
+        },
+        "snapshot.save" | "snapshot.load" => {
+            expect_operands(mnemonic, operands, 1, 1)?;
+            
+            let name = parse_string(&operands[0])?;
+            
+            out.command(if mnemonic == "snapshot.save" { AnoCmd::SaveSnapshot } else { AnoCmd::LoadSnapshot });
+            out.bytes(&name);
+        },
+        "skip" | "skip.if" | "jump" | "jump.if" => {
+            let is_conditional = mnemonic.ends_with(".if");
+            let count = if is_conditional { 2 } else { 1 };
+            
+            expect_operands(mnemonic, operands, count, count)?;
+            
+            let (test, register) = if is_conditional {
+                parse_branch_test(&operands[0])?
+            } else {
+                (BranchTest::Always, None)
+            };
+            
+            let (command, amount) = if mnemonic.starts_with("skip") {
+                let amount = parse_i32(&operands[count - 1])?;
+                
+                if amount < 0 {
+                    return Err(String::from("can't skip backwards, use jump instead"));
+                }
+                
+                (AnoCmd::SkipIf, amount)
+            } else {
+                let amount = parse_i32(&operands[count - 1])
+                    .map_err(|_| format!("`{}` isn't a label or a number of commands", operands[count - 1]))?;
+                
+                (AnoCmd::JumpIf, amount)
+            };
+            
+            let register = register.unwrap_or(Register { index : 0, is_bool : false, is_indirect : false });
+            let flags = RegisterFlags::new(0)
+                .set_lhs_bool(register.is_bool)
+                .set_lhs_indirect(register.is_indirect);
+            
+            out.command(command);
+            out.bytes(&[register.index, test as u8, flags.raw(), 0]);
+            out.u32(amount as u32);
+        },
+        "schedule" => {
+            expect_operands(mnemonic, operands, 1, 3)?;
+            
+            let frames = |token : Option<&String>| -> Result<u32, String> {
+                match token {
+                    Some(token) => match parse_replacement(token) {
+                        Some(bits) => Ok(bits),
+                        None => Ok(parse_i32(token)? as u32),
+                    },
+                    None => Ok(0),
+                }
+            };
+            
+            let tag = match operands.get(2) {
+                Some(tag) => parse_i32(tag)? as u32,
+                None => 0,
+            };
+            
+            out.command(AnoCmd::ScheduleNextCommand);
+            out.u32(frames(operands.first())?);
+            out.u32(frames(operands.get(1))?);
+            out.u32(tag);
+        },
+        "schedule.cancel" => {
+            expect_operands(mnemonic, operands, 1, 1)?;
+            
+            out.command(AnoCmd::CancelScheduled);
+            out.u32(parse_i32(&operands[0])? as u32);
+        },
+        "suck.x" => {
+            expect_operands(mnemonic, operands, 2, 2)?;
+            
+            out.command(AnoCmd::SuckX);
+            out.u32(parse_f32(&operands[0])?);
+            out.u32(parse_f32(&operands[1])?);
+        },
+        "force" => {
+            expect_operands(mnemonic, operands, 7, 9)?;
+            
+            let slot = parse_force_slot(&operands[0])?;
+            let relation = parse_relation(&operands[1])?;
+            let falloff = Falloff::from_name(&operands[4])
+                .ok_or_else(|| format!("unknown falloff `{}`, it should be linear or exp", operands[4]))?;
+            
+            let mut flags = ForceFlags::default().set_character_affected(true);
+            
+            for option in &operands[7..] {
+                flags = match option.to_ascii_lowercase().as_str() {
+                    "proj" => flags.set_character_affected(false).set_projectiles_affected(true),
+                    "both" => flags.set_character_affected(true).set_projectiles_affected(true),
+                    "radial" => flags.set_radial(true),
+                    _ => return Err(format!("unknown force option `{}`, it should be proj, both or radial", option)),
+                };
+            }
+            
+            out.command(AnoCmd::SetForce);
+            out.bytes(&[slot, relation, flags.raw(), falloff as u8]);
+            out.u32(parse_f32(&operands[2])?);
+            out.u32(parse_f32(&operands[3])?);
+            out.u32(parse_f32(&operands[5])?);
+            out.u32(parse_f32(&operands[6])?);
+        },
+        "force.clear" => {
+            expect_operands(mnemonic, operands, 1, 1)?;
+            
+            let slot = if operands[0].eq_ignore_ascii_case("all") {
+                u8::MAX
+            } else {
+                parse_force_slot(&operands[0])?
+            };
+            
+            out.command(AnoCmd::ClearForce);
+            out.bytes(&[slot, 0, 0, 0]);
+        },
+        _ => return Err(format!("unknown command `{}`", mnemonic)),
+    }
+    
+    output.append(&mut buffer);
+    
+    Ok(())
+}
+
+enum Disassembled {
+    /// text for a command and how many bytes it took up
+    Command(String, usize),
+    /// bytes we know the length of, but can't write as anything other than .hex
+    Raw(usize, String),
+    /// we can't tell where this command ends, so everything from here on is .hex
+    Stop(String),
+}
+
+pub(crate) struct Reader<'a> {
+    bytes : &'a [u8],
+    pub(crate) position : usize,
+}
+
+impl<'a> Reader<'a> {
+    pub(crate) fn new(bytes : &'a [u8]) -> Self
+    {
+        Self { bytes, position : 0 }
+    }
+    
+    pub(crate) fn u32(&mut self) -> Option<u32>
+    {
+        let bytes = self.bytes.get(self.position..self.position + 4)?;
+        self.position += 4;
+        
+        Some(LittleEndian::read_u32(bytes))
+    }
+    
+    pub(crate) fn word(&mut self) -> Option<[u8; 4]>
+    {
+        let bytes = self.bytes.get(self.position..self.position + 4)?;
+        self.position += 4;
+        
+        Some([bytes[0], bytes[1], bytes[2], bytes[3]])
+    }
+    
+    /// everything that hasn't been read yet
+    pub(crate) fn rest(&self) -> &'a [u8]
+    {
+        self.bytes.get(self.position..).unwrap_or_default()
+    }
+    
+    pub(crate) fn string(&mut self) -> Option<&[u8]>
+    {
+        let bytes = self.bytes.get(self.position..self.position + STRING_CAPACITY)?;
+        self.position += STRING_CAPACITY;
+        
+        Some(bytes)
+    }
+}
+
+fn disassemble_one(bytes : &[u8]) -> Disassembled
+{
+    let mut reader = Reader::new(bytes);
+    
+    let (group, command) = match (reader.u32(), reader.u32()) {
+        (Some(group), Some(command)) => (group, command),
+        _ => return Disassembled::Stop(String::from("trailing bytes")),
+    };
+    
+    if group == ANO_COMMAND_GROUP {
+        let command_name = format!("66_{:02X}", command);
+        
+        match num::FromPrimitive::from_u32(command) {
+            Some(command) => {
+                match disassemble_ano_command(command, &mut reader) {
+                    Some(text) => Disassembled::Command(text, reader.position),
+                    None => Disassembled::Stop(format!("truncated {}", command_name)),
+                }
+            },
+            None => Disassembled::Stop(format!("unknown command {}, can't tell where it ends", command_name)),
+        }
+    } else {
+        let command_name = format!("{:X}_{:02X}", group, command);
+        
+        match game_command_len(bytes) {
+            Some(len) if len <= bytes.len() => Disassembled::Raw(len, command_name),
+            Some(_) => Disassembled::Stop(format!("truncated {}", command_name)),
+            None => Disassembled::Stop(format!("{}, can't tell where it ends", command_name)),
+        }
+    }
+}
+
+/// how many bytes the command at the start of `bytes` takes up, if we can tell. None if it's cut off
+#[cfg(test)]
+pub(crate) fn command_len(bytes : &[u8]) -> Option<usize>
+{
+    let group = LittleEndian::read_u32(bytes.get(0..4)?);
+    
+    let len = if group == ANO_COMMAND_GROUP {
+        let command : AnoCmd = num::FromPrimitive::from_u32(LittleEndian::read_u32(bytes.get(4..8)?))?;
+        
+        size_of::<u32>() * 2 + command.argument_size()
+    } else {
+        game_command_len(bytes)?
+    };
+    
+    if len <= bytes.len() { Some(len) } else { None }
+}
+
+/// size of one of the game's own commands, using the same argument type table that `reload::save_anmchr_command` reads
+pub(crate) fn game_command_len(bytes : &[u8]) -> Option<usize>
+{
+    const SIZE_U32 : usize = size_of::<u32>();
+    
+    let group = LittleEndian::read_u32(bytes.get(0..4)?);
+    
+    match group {
+        0 | 1 | 3 => {
+            let len = LittleEndian::read_u32(bytes.get(8..12)?) as usize;
+            
+            let mut size = SIZE_U32 * 2 + SIZE_U32 * (len.checked_add(2)?);
+            
+            for table_index in 0..len {
+                let start = SIZE_U32 * (4 + table_index);
+                let value_type = LittleEndian::read_u32(bytes.get(start..start + SIZE_U32)?);
+                
+                size += match value_type {
+                    1 => 1,
+                    3 | 5 | 0xE | 0xF | 6 => SIZE_U32,
+                    0xC => SIZE_U32 * 3,
+                    0xD => SIZE_U32 * 4,
+                    0x10 | 0x07 => 64,
+                    _ => return None,
+                };
+            }
+            
+            Some(size)
+        },
+        _ => None,
+    }
+}
+
+fn disassemble_ano_command(command : AnoCmd, reader : &mut Reader) -> Option<String>
+{
+    let text = match command {
+        AnoCmd::RelativeTeleportX => format!("teleport.x {}", format_f32(reader.u32()?)),
+        AnoCmd::RelativeTeleportY => format!("teleport.y {}", format_f32(reader.u32()?)),
+        AnoCmd::LoadImmediateIntoRegister => {
+            let [_, _, flags, destination] = reader.word()?;
+            let flags = RegisterFlags::new(flags);
+            let destination = Register { index : destination, is_bool : flags.is_destination_bool(), is_indirect : flags.is_destination_indirect() };
+            
+            format!("load.imm {}, {}",
+                Register::format(destination.index, destination.is_bool, destination.is_indirect),
+                format_immediate(reader.u32()?, destination.immediate_type()))
+        },
+        AnoCmd::BinaryOperationRegisterImmediate => {
+            let op = reader.u32()?;
+            let [lhs, _, flags, destination] = reader.word()?;
+            let flags = RegisterFlags::new(flags);
+            
+            let immediate_type = if flags.is_destination_indirect() { None } else { Some(RegisterType::identify(destination)) };
+            let immediate = reader.u32()?;
+            let immediate = if flags.is_rhs_bool() {
+                match format_immediate(immediate, immediate_type).as_str() {
+                    "0" | "0.0" => String::from("false"),
+                    "1" | "1.0" => String::from("true"),
+                    other => String::from(other),
+                }
+            } else {
+                format_immediate(immediate, immediate_type)
+            };
+            
+            format!("{} {}, {}, {}",
+                binary_mnemonic(op, "imm"),
+                Register::format(destination, flags.is_destination_bool(), flags.is_destination_indirect()),
+                Register::format(lhs, flags.is_lhs_bool(), flags.is_lhs_indirect()),
+                immediate)
+        },
+        AnoCmd::BinaryOperationRegisterRegister => {
+            let op = reader.u32()?;
+            let [lhs, rhs, flags, destination] = reader.word()?;
+            let flags = RegisterFlags::new(flags);
+            
+            format!("{} {}, {}, {}",
+                binary_mnemonic(op, "reg"),
+                Register::format(destination, flags.is_destination_bool(), flags.is_destination_indirect()),
+                Register::format(lhs, flags.is_lhs_bool(), flags.is_lhs_indirect()),
+                Register::format(rhs, flags.is_rhs_bool(), flags.is_rhs_indirect()))
+        },
+        AnoCmd::UnaryOperationRegister => {
+            let op = reader.u32()?;
+            let [source, _, flags, destination] = reader.word()?;
+            let flags = RegisterFlags::new(flags);
+            
+            format!("{} {}, {}",
+                unary_mnemonic(op, "reg"),
+                Register::format(destination, flags.is_destination_bool(), flags.is_destination_indirect()),
+                Register::format(source, flags.is_lhs_bool(), flags.is_lhs_indirect()))
+        },
+        AnoCmd::UnaryOperationImmediate => {
+            let op = reader.u32()?;
+            let [_, _, flags, destination] = reader.word()?;
+            let flags = RegisterFlags::new(flags);
+            let immediate = reader.u32()?;
+            
+            let immediate = if flags.is_lhs_bool() {
+                match immediate {
+                    0 => String::from("false"),
+                    1 => String::from("true"),
+                    other => format!("{}", other as i32),
+                }
+            } else {
+                let destination = Register { index : destination, is_bool : flags.is_destination_bool(), is_indirect : flags.is_destination_indirect() };
+                
+                format_immediate(immediate, destination.immediate_type())
+            };
+            
+            format!("{} {}, {}",
+                unary_mnemonic(op, "imm"),
+                Register::format(destination, flags.is_destination_bool(), flags.is_destination_indirect()),
+                immediate)
+        },
+        AnoCmd::LoadVarIntoRegister | AnoCmd::LoadProjectileVarIntoRegister => {
+            let [_, relation, flags, destination] = reader.word()?;
+            let flags = RegisterFlags::new(flags);
+            let var = reader.u32()?;
+            
+            let (mnemonic, var) = match command {
+                AnoCmd::LoadVarIntoRegister => ("load.var", format_var(var)),
+                _ => ("load.proj", format_projectile_var(var)),
+            };
+            
+            format!("{} {}, {}, {}",
+                mnemonic,
+                Register::format(destination, flags.is_destination_bool(), flags.is_destination_indirect()),
+                var,
+                format_relation(relation))
+        },
+        AnoCmd::StoreVarFromRegister | AnoCmd::StoreProjectileVarFromRegister => {
+            let [_, relation, flags, source] = reader.word()?;
+            let flags = RegisterFlags::new(flags);
+            let var = reader.u32()?;
+            
+            let (mnemonic, var) = match command {
+                AnoCmd::StoreVarFromRegister => ("store.var", format_var(var)),
+                _ => ("store.proj", format_projectile_var(var)),
+            };
+            
+            format!("{} {}, {}, {}",
+                mnemonic,
+                Register::format(source, flags.is_lhs_bool(), flags.is_lhs_indirect()),
+                var,
+                format_relation(relation))
+        },
+        AnoCmd::LoadSharedRegister | AnoCmd::StoreSharedRegister => {
+            let [bank, shared, flags, register] = reader.word()?;
+            let flags = RegisterFlags::new(flags);
+            
+            // an unknown bank won't assemble back, so it ends up as .hex
+            let bank = match num::FromPrimitive::from_u8(bank) {
+                Some(bank) => SharedBankSelect::name(bank).to_string(),
+                None => format!("?{}", bank),
+            };
+            
+            match command {
+                AnoCmd::LoadSharedRegister => format!("load.shared {}, {}, {}",
+                    Register::format(register, flags.is_destination_bool(), flags.is_destination_indirect()),
+                    bank,
+                    Register::format(shared, flags.is_lhs_bool(), flags.is_lhs_indirect())),
+                _ => format!("store.shared {}, {}, {}",
+                    Register::format(register, flags.is_lhs_bool(), flags.is_lhs_indirect()),
+                    bank,
+                    Register::format(shared, flags.is_destination_bool(), flags.is_destination_indirect())),
+            }
+        },
+        AnoCmd::SaveSnapshot | AnoCmd::LoadSnapshot => {
+            let name = format_string(reader.string()?);
+            
+            match command {
+                AnoCmd::SaveSnapshot => format!("snapshot.save {}", name),
+                _ => format!("snapshot.load {}", name),
+            }
+        },
+        AnoCmd::SetRegisterPersistence => {
+            let [first, last, flags, persistence] = reader.word()?;
+            let flags = RegisterFlags::new(flags);
+            
+            // an unknown policy won't assemble back, so it ends up as .hex
+            let persistence = match num::FromPrimitive::from_u8(persistence) {
+                Some(persistence) => Persistence::name(persistence).to_string(),
+                None => format!("?{}", persistence),
+            };
+            
+            if first == last {
+                format!("persist {}, {}", persistence, Register::format(first, flags.is_lhs_bool(), false))
+            } else {
+                format!("persist {}, {}, {}",
+                    persistence,
+                    Register::format(first, flags.is_lhs_bool(), false),
+                    Register::format(last, flags.is_lhs_bool(), false))
+            }
+        },
+        AnoCmd::StoreVarFromImmediate => {
+            let [_, relation, _, _] = reader.word()?;
+            let var = reader.u32()?;
+            
+            format!("store.var.imm {}, {}, {}",
+                format_var(var),
+                format_relation(relation),
+                format_immediate(reader.u32()?, var_rw::MatchState::get_number_type(var)))
+        },
+        AnoCmd::BinaryOperationVarRegister => {
+            let op = reader.u32()?;
+            let [rhs, relation, flags, _] = reader.word()?;
+            let flags = RegisterFlags::new(flags);
+            let var = reader.u32()?;
+            
+            format!("{} {}, {}, {}",
+                binary_mnemonic(op, "var"),
+                format_var(var),
+                format_relation(relation),
+                Register::format(rhs, flags.is_rhs_bool(), flags.is_rhs_indirect()))
+        },
+        AnoCmd::BinaryOperationVarImmediate => {
+            let op = reader.u32()?;
+            let [_, relation, _, _] = reader.word()?;
+            let var = reader.u32()?;
+            
+            format!("{} {}, {}, {}",
+                binary_mnemonic(op, "var.imm"),
+                format_var(var),
+                format_relation(relation),
+                format_immediate(reader.u32()?, var_rw::MatchState::get_number_type(var)))
+        },
+        AnoCmd::UnaryOperationVar => {
+            let op = reader.u32()?;
+            let [_, relation, _, _] = reader.word()?;
+            let var = reader.u32()?;
+            
+            format!("{} {}, {}",
+                unary_mnemonic(op, "var"),
+                format_var(var),
+                format_relation(relation))
+        },
+        AnoCmd::CheckCharacterName => {
+            let [_, relation, flags, destination] = reader.word()?;
+            let flags = RegisterFlags::new(flags);
+            
+            format!("char.name {}, {}, {}",
+                Register::format(destination, flags.is_destination_bool(), flags.is_destination_indirect()),
+                format_relation(relation),
+                format_string(reader.string()?))
+        },
+        AnoCmd::CheckMotion => {
+            let [motion, relation, flags, destination] = reader.word()?;
+            let [window, charge, _, _] = reader.word()?;
+            let flags = RegisterFlags::new(flags);
+            
+            let motion = match num::FromPrimitive::from_u8(motion) {
+                Some(motion) => Motion::name(motion).to_string(),
+                None => format!("?{}", motion),
+            };
+            
+            let frames = match (window, charge) {
+                (0, 0) => String::new(),
+                (window, 0) => format!(", {}", window),
+                (window, charge) => format!(", {}, {}", window, charge),
+            };
+            
+            format!("motion {}, {}, {}{}",
+                Register::format(destination, flags.is_destination_bool(), flags.is_destination_indirect()),
+                format_relation(relation),
+                motion,
+                frames)
+        },
+        AnoCmd::ConditionalBinaryOperation => {
+            let comparator = reader.u32()?;
+            let result = reader.u32()?;
+            let [lhs, _, flags, destination] = reader.word()?;
+            let flags = RegisterFlags::new(flags);
+            let lhs = Register { index : lhs, is_bool : flags.is_lhs_bool(), is_indirect : flags.is_lhs_indirect() };
+            let destination = Register { index : destination, is_bool : flags.is_destination_bool(), is_indirect : flags.is_destination_indirect() };
+            
+            format!("cond {}, {}, {}, {}, {}, {}, {}",
+                format_binary_op(comparator),
+                format_binary_op(result),
+                Register::format(destination.index, destination.is_bool, destination.is_indirect),
+                Register::format(lhs.index, lhs.is_bool, lhs.is_indirect),
+                format_immediate(reader.u32()?, lhs.immediate_type()),
+                format_immediate(reader.u32()?, destination.immediate_type()),
+                format_immediate(reader.u32()?, lhs.immediate_type()))
+        },
+        AnoCmd::TernaryOperationRegisters => {
+            let op = reader.u32()?;
+            let [first, second, flags, destination] = reader.word()?;
+            let [third, _, _, _] = reader.word()?;
+            let flags = RegisterFlags::new(flags);
+            
+            format!("{} {}, {}, {}, {}",
+                ternary_mnemonic(op, "reg"),
+                Register::format(destination, flags.is_destination_bool(), flags.is_destination_indirect()),
+                Register::format(first, flags.is_lhs_bool(), flags.is_lhs_indirect()),
+                Register::format(second, flags.is_rhs_bool(), flags.is_rhs_indirect()),
+                Register::format(third, flags.is_third_bool(), flags.is_third_indirect()))
+        },
+        AnoCmd::TernaryOperationRegisterImmediate => {
+            let op = reader.u32()?;
+            let [first, _, flags, destination] = reader.word()?;
+            let flags = RegisterFlags::new(flags);
+            
+            let immediate_type = if flags.is_destination_indirect() { None } else { Some(RegisterType::identify(destination)) };
+            
+            format!("{} {}, {}, {}, {}",
+                ternary_mnemonic(op, "imm"),
+                Register::format(destination, flags.is_destination_bool(), flags.is_destination_indirect()),
+                Register::format(first, flags.is_lhs_bool(), flags.is_lhs_indirect()),
+                format_immediate(reader.u32()?, immediate_type),
+                format_immediate(reader.u32()?, immediate_type))
+        },
+        AnoCmd::GetProjectile => {
+            let [_, relation, _, _] = reader.word()?;
+            
+            format!("proj.get {}{}", format_relation(relation), format_projectile_order(reader.u32()?))
+        },
+        AnoCmd::NextProjectile => String::from("proj.next"),
+        AnoCmd::GetProjectileFilteredByOperation => {
+            let op = reader.u32()?;
+            let [_, relation, _, _] = reader.word()?;
+            let order = reader.u32()?;
+            let var = reader.u32()?;
+            
+            let immediate_type = var_rw::MatchState::get_number_type(var)?;
+            
+            format!("proj.filter {}, {}, {}, {}{}",
+                format_binary_op(op),
+                format_projectile_var(var),
+                format_immediate(reader.u32()?, Some(immediate_type)),
+                format_relation(relation),
+                format_projectile_order(order))
+        },
+        AnoCmd::GetProjectileFilteredByFilename => {
+            let [_, relation, _, _] = reader.word()?;
+            let order = reader.u32()?;
+            
+            format!("proj.filter.name {}, {}{}",
+                format_string(reader.string()?),
+                format_relation(relation),
+                format_projectile_order(order))
+        },
+        AnoCmd::SkipIf | AnoCmd::JumpIf => {
+            let [register, test, flags, _] = reader.word()?;
+            let amount = reader.u32()? as i32;
+            let flags = RegisterFlags::new(flags);
+            
+            let name = match command {
+                AnoCmd::SkipIf => "skip",
+                _ => "jump",
+            };
+            
+            if test == BranchTest::Always as u8 {
+                format!("{} {}", name, amount)
+            } else {
+                // an unknown test won't assemble back to the same thing, so it ends up as .hex
+                let test = format_branch_test(test, register, flags).unwrap_or_else(|| format!("?{}", test));
+                
+                format!("{}.if {}, {}", name, test, amount)
+            }
+        },
+        AnoCmd::ScheduleNextCommand => {
+            let format_frames = |frames : u32| {
+                if is_replacement(frames) {
+                    format!("r{:02X}", frames & 0xFF)
+                } else {
+                    format!("{}", frames as i32)
+                }
+            };
+            
+            let delay = format_frames(reader.u32()?);
+            let interval = format_frames(reader.u32()?);
+            let tag = reader.u32()?;
+            
+            format!("schedule {}, {}, {}", delay, interval, tag as i32)
+        },
+        AnoCmd::CancelScheduled => {
+            format!("schedule.cancel {}", reader.u32()? as i32)
+        },
+        AnoCmd::LoadVectorFromPosition | AnoCmd::StoreVectorToPosition => {
+            let [_, relation, flags, vector] = reader.word()?;
+            let flags = RegisterFlags::new(flags);
+            let source = reader.u32()?;
+            
+            let (name, is_indirect) = match command {
+                AnoCmd::LoadVectorFromPosition => ("vec.load", flags.is_destination_indirect()),
+                _ => ("vec.store", flags.is_lhs_indirect()),
+            };
+            
+            let source = match num::FromPrimitive::from_u32(source) {
+                Some(PositionSource::Character) => String::new(),
+                Some(PositionSource::Projectile) => String::from(" proj"),
+                None => format!(" ?{}", source),
+            };
+            
+            format!("{} {}, {}{}", name, format_vector_register(vector, is_indirect), format_relation(relation), source)
+        },
+        AnoCmd::DirectionToCharacter => {
+            let [_, relation, flags, vector] = reader.word()?;
+            let flags = RegisterFlags::new(flags);
+            
+            format!("vec.dir {}, {}", format_vector_register(vector, flags.is_destination_indirect()), format_relation(relation))
+        },
+        AnoCmd::VectorOperation => {
+            let op = reader.u32()?;
+            let [lhs, rhs, flags, destination] = reader.word()?;
+            let flags = RegisterFlags::new(flags);
+            let scalar = reader.u32()?;
+            
+            let op : Option<VectorOp> = num::FromPrimitive::from_u32(op);
+            
+            // unknown operations won't assemble back, so they end up as .hex
+            let Some(op) = op else {
+                return Some(String::from("vec.?"));
+            };
+            
+            let destination = if op.has_number_result() {
+                Register::format(destination, flags.is_destination_bool(), flags.is_destination_indirect())
+            } else {
+                format_vector_register(destination, flags.is_destination_indirect())
+            };
+            
+            let operands = if op.has_number_operands() {
+                format!("{}, {}",
+                    Register::format(lhs, flags.is_lhs_bool(), flags.is_lhs_indirect()),
+                    Register::format(rhs, flags.is_rhs_bool(), flags.is_rhs_indirect()))
+            } else if op.uses_rhs() {
+                format!("{}, {}", format_vector_register(lhs, flags.is_lhs_indirect()), format_vector_register(rhs, flags.is_rhs_indirect()))
+            } else if op.uses_scalar() {
+                format!("{}, {}", format_vector_register(lhs, flags.is_lhs_indirect()), format_f32(scalar))
+            } else {
+                format_vector_register(lhs, flags.is_lhs_indirect())
+            };
+            
+            format!("vec.{} {}, {}", op.name(), destination, operands)
+        },
+        AnoCmd::SetForce => {
+            let [slot, relation, flags, falloff_byte] = reader.word()?;
+            let [x, y, decay, radius] = [reader.u32()?, reader.u32()?, reader.u32()?, reader.u32()?];
+            let flags = ForceFlags::new(flags);
+            
+            let falloff : Option<Falloff> = num::FromPrimitive::from_u8(falloff_byte);
+            let falloff = match falloff {
+                Some(falloff) => String::from(falloff.name()),
+                None => format!("?{}", falloff_byte),
+            };
+            
+            // anything that won't assemble back to the same thing ends up as .hex
+            let targets = match (flags.is_character_affected(), flags.is_projectiles_affected(), flags.raw() & !ForceFlags::KNOWN) {
+                (true, false, 0) => "",
+                (false, true, 0) => " proj",
+                (true, true, 0) => " both",
+                _ => " ?",
+            };
+            let radial = if flags.is_radial() { " radial" } else { "" };
+            
+            format!("force {}, {}, {}, {}, {}, {}, {}{}{}",
+                slot,
+                format_relation(relation),
+                format_f32(x),
+                format_f32(y),
+                falloff,
+                format_f32(decay),
+                format_f32(radius),
+                targets,
+                radial)
+        },
+        AnoCmd::ClearForce => {
+            let [slot, _, _, _] = reader.word()?;
+            
+            if slot == u8::MAX {
+                String::from("force.clear all")
+            } else {
+                format!("force.clear {}", slot)
+            }
+        },
+        AnoCmd::SuckX => {
+            format!("suck.x {}, {}", format_f32(reader.u32()?), format_f32(reader.u32()?))
+        },
+    };
+    
+    Some(text)
+}
+
+/// use the `add.imm` shorthand when we can, otherwise `binop.imm r18, ...`
+fn binary_mnemonic(op : u32, form : &str) -> String
+{
+    let is_known : Option<BinaryOp> = num::FromPrimitive::from_u32(op);
+    
+    match is_known {
+        Some(op) if UnaryOp::from_name(op.name()).is_none() => format!("{}.{}", op.name().to_ascii_lowercase(), form),
+        _ => format!("binop.{} {},", form, format_binary_op(op)),
+    }
+}
+
+fn unary_mnemonic(op : u32, form : &str) -> String
+{
+    let is_known : Option<UnaryOp> = num::FromPrimitive::from_u32(op);
+    
+    match is_known {
+        Some(op) if BinaryOp::from_name(op.name()).is_none() => format!("{}.{}", op.name().to_ascii_lowercase(), form),
+        _ => format!("unop.{} {},", form, format_unary_op(op)),
+    }
+}
+
+/// ternary operation names don't overlap with the others, so the shorthand always works for known operations
+fn ternary_mnemonic(op : u32, form : &str) -> String
+{
+    let is_known : Option<TernaryOp> = num::FromPrimitive::from_u32(op);
+    
+    match is_known {
+        Some(op) => format!("{}.{}", op.name().to_ascii_lowercase(), form),
+        None => format!("terop.{} {},", form, format_ternary_op(op)),
+    }
+}
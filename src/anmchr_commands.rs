@@ -3,6 +3,7 @@
 
 use std::io::{Seek, SeekFrom};
 use std::mem::size_of;
+use std::sync::Mutex;
 
 use num_derive::FromPrimitive;
 use byteorder::{LittleEndian, ReadBytesExt};
@@ -10,14 +11,37 @@ use byteorder::{LittleEndian, ReadBytesExt};
 use crate::hook_helpers::*;
 use crate::game_data::*;
 use crate::storage;
-use crate::storage::{RegisterType, RegisterFlags};
+use crate::storage::{RegisterType, RegisterFlags, SharedBankSelect, Persistence};
 use crate::character_extensions;
+use crate::motion::Motion;
+use crate::config::LogLevel;
+use crate::command_error::{self, CommandError, CommandResult};
 use crate::var_rw;
 use crate::binary_operators::{BinaryOp,BinaryOpHandler};
 use crate::unary_operators::{UnaryOp,UnaryOpHandler};
+use crate::ternary_operators::TernaryOp;
+use crate::vector::{Vec2, VectorOp, VectorResult, PositionSource};
 use crate::math::*;
 use crate::strings::*;
 
+/// the command group for all of the commands added by mag_patch
+pub const ANO_COMMAND_GROUP : u32 = 0x66;
+
+/// every group set aside for mag_patch, from mag_patch.ini, see config.rs
+static COMMAND_GROUPS : Mutex<Vec<u32>> = Mutex::new(Vec::new());
+
+/// which groups besides 66 are mag_patch's, so anything in them gets run (or reported) by mag_patch
+pub fn set_command_groups(groups : &[u32])
+{
+    *lock(&COMMAND_GROUPS) = groups.to_vec();
+}
+
+/// 66 always is, even before mag_patch.ini is read
+pub fn is_command_group(group : u32) -> bool
+{
+    group == ANO_COMMAND_GROUP || lock(&COMMAND_GROUPS).contains(&group)
+}
+
 /// This is the number after the 0x66
 /// All commands that start with 0x66 should be ones added by me (anotak). if you want to add commands you should reserve another starting value to prevent conflicts. (game uses commands 0 through 7 inclusive)
 #[derive(FromPrimitive)]
@@ -40,6 +64,13 @@ pub enum AnoCmd
     UnaryOperationVar = 0x1a,
     CheckCharacterName = 0x1b,
     ConditionalBinaryOperation = 0x1c,
+    TernaryOperationRegisters = 0x1d,
+    TernaryOperationRegisterImmediate = 0x1e,
+    
+    SkipIf = 0x20,
+    JumpIf = 0x21,
+    ScheduleNextCommand = 0x23,
+    CancelScheduled = 0x24,
     
     GetProjectile = 0x30,
     NextProjectile = 0x31,
@@ -49,23 +80,103 @@ pub enum AnoCmd
     LoadProjectileVarIntoRegister = 0x35,
     StoreProjectileVarFromRegister = 0x36,
     
+    LoadVectorFromPosition = 0x40,
+    StoreVectorToPosition = 0x41,
+    VectorOperation = 0x42,
+    DirectionToCharacter = 0x43,
+    LoadSharedRegister = 0x44,
+    StoreSharedRegister = 0x45,
+    SetRegisterPersistence = 0x46,
+    SaveSnapshot = 0x47,
+    LoadSnapshot = 0x48,
+    
     
     SuckX = 0x50,
+    SetForce = 0x51,
+    ClearForce = 0x52,
+    
+    CheckMotion = 0x60,
     
     
 }
 
+impl AnoCmd
+{
+    /// how many bytes come after the 8 byte header. every command is a fixed size
+    pub fn argument_size(&self) -> usize
+    {
+        const SIZE_U32 : usize = size_of::<u32>();
+        
+        match self {
+            AnoCmd::RelativeTeleportX | AnoCmd::RelativeTeleportY => SIZE_U32,
+            AnoCmd::LoadImmediateIntoRegister => SIZE_U32 * 2,
+            AnoCmd::BinaryOperationRegisterImmediate => SIZE_U32 * 3,
+            AnoCmd::BinaryOperationRegisterRegister => SIZE_U32 * 2,
+            AnoCmd::UnaryOperationRegister => SIZE_U32 * 2,
+            AnoCmd::UnaryOperationImmediate => SIZE_U32 * 3,
+            AnoCmd::LoadVarIntoRegister | AnoCmd::StoreVarFromRegister => SIZE_U32 * 2,
+            AnoCmd::StoreVarFromImmediate => SIZE_U32 * 3,
+            AnoCmd::BinaryOperationVarRegister => SIZE_U32 * 3,
+            AnoCmd::BinaryOperationVarImmediate => SIZE_U32 * 4,
+            AnoCmd::UnaryOperationVar => SIZE_U32 * 3,
+            AnoCmd::CheckCharacterName => SIZE_U32 + size_of::<u8>() * 64,
+            AnoCmd::ConditionalBinaryOperation => SIZE_U32 * 6,
+            AnoCmd::TernaryOperationRegisters => SIZE_U32 * 3,
+            AnoCmd::TernaryOperationRegisterImmediate => SIZE_U32 * 4,
+            AnoCmd::SkipIf | AnoCmd::JumpIf => SIZE_U32 * 2,
+            AnoCmd::ScheduleNextCommand => SIZE_U32 * 3,
+            AnoCmd::CancelScheduled => SIZE_U32,
+            AnoCmd::GetProjectile => SIZE_U32 * 2,
+            AnoCmd::NextProjectile => 0,
+            AnoCmd::GetProjectileFilteredByOperation => SIZE_U32 * 5,
+            AnoCmd::GetProjectileFilteredByFilename => SIZE_U32 * 2 + size_of::<u8>() * 64,
+            AnoCmd::LoadProjectileVarIntoRegister | AnoCmd::StoreProjectileVarFromRegister => SIZE_U32 * 2,
+            AnoCmd::LoadVectorFromPosition | AnoCmd::StoreVectorToPosition => SIZE_U32 * 2,
+            AnoCmd::VectorOperation => SIZE_U32 * 3,
+            AnoCmd::DirectionToCharacter => SIZE_U32,
+            AnoCmd::LoadSharedRegister | AnoCmd::StoreSharedRegister => SIZE_U32,
+            AnoCmd::SetRegisterPersistence => SIZE_U32,
+            AnoCmd::SaveSnapshot | AnoCmd::LoadSnapshot => size_of::<u8>() * 64,
+            AnoCmd::SuckX => SIZE_U32 * 2,
+            AnoCmd::SetForce => SIZE_U32 * 5,
+            AnoCmd::ClearForce => SIZE_U32,
+            AnoCmd::CheckMotion => SIZE_U32 * 2,
+        }
+    }
+}
+
+/// runs `command` from one of mag_patch's command groups. if it can't be run, it's reported (see command_error.rs)
+/// instead of taking the game down with it
+pub fn run_ano_command(exe_char : Char, group : u32, command : u32, command_ptr : usize)
+{
+    let result = command_error::catch(|| {
+        // the other groups are set aside, but nothing's been put in them yet
+        if group != ANO_COMMAND_GROUP {
+            return Err(CommandError::UnknownCommand(command));
+        }
+        
+        let ano_command : AnoCmd = num::FromPrimitive::from_u32(command)
+            .ok_or(CommandError::UnknownCommand(command))?;
+        
+        handle_ano_command(ano_command, exe_char.clone(), command_ptr)
+    });
+    
+    if let Err(error) = result {
+        command_error::report(&exe_char, group, command, &error);
+    }
+}
+
 /// handle commands starting in 66
-pub fn handle_ano_command(command : AnoCmd, exe_char : Char, command_ptr : usize)
+pub fn handle_ano_command(command : AnoCmd, exe_char : Char, command_ptr : usize) -> CommandResult
 {
     match command {
         AnoCmd::RelativeTeleportX => {
-            //debug_msg(format!("exe_char = {}\np1c1 = {:#X}\np1c2 = {:#X}\np1c3 = {:#X}\np2c1 = {:#X}\np2c2 = {:#X}\np2c3 = {:#X}", exe_char, get_p1_char1_ptr(), get_p1_char2_ptr(), get_p1_char3_ptr(), get_p2_char1_ptr(), get_p2_char2_ptr(), get_p2_char3_ptr()));
+            crate::log!(LogLevel::Debug, Some(&exe_char), "exe_char = {} p1 point = {} p2 point = {}", exe_char, Char::get_p1_point(), Char::get_p2_point());
             
             let my_team = exe_char.identify_team();
-            //debug_msg(format!("my_team = {:?}", my_team));
+            crate::log!(LogLevel::Debug, Some(&exe_char), "my_team = {:?}", my_team);
             let op_team = my_team.opposite();
-            //debug_msg(format!("op_team = {:?}", op_team));
+            crate::log!(LogLevel::Debug, Some(&exe_char), "op_team = {:?}", op_team);
             
             let offset : f32 = {
                 let offset = storage::with(
@@ -169,9 +280,9 @@ pub fn handle_ano_command(command : AnoCmd, exe_char : Char, command_ptr : usize
         AnoCmd::LoadImmediateIntoRegister => {
             let mut cursor = unsafe { get_cursor(command_ptr, const { size_of::<u32>() * 2 }) };
             
-            cursor.seek(SeekFrom::Current(2)).unwrap();
-            let register_flags = RegisterFlags::read(&mut cursor);
-            let destination = cursor.read_u8().unwrap();
+            cursor.seek(SeekFrom::Current(2))?;
+            let register_flags = RegisterFlags::read(&mut cursor)?;
+            let destination = cursor.read_u8()?;
             
             storage::with(
                 exe_char.get_ptr(),
@@ -180,7 +291,7 @@ pub fn handle_ano_command(command : AnoCmd, exe_char : Char, command_ptr : usize
                     
                     store.read_into_register(destination, &mut cursor, register_flags)
                 }
-            );
+            )?;
         },
         AnoCmd::BinaryOperationRegisterImmediate => {
             let mut cursor = unsafe { get_cursor(command_ptr, const { size_of::<u32>() * 3 }) };
@@ -190,19 +301,19 @@ pub fn handle_ano_command(command : AnoCmd, exe_char : Char, command_ptr : usize
                     |store| {
                         store.cursor_read_u32_with_replacement(&mut cursor)
                     }
-                );
+                )?;
             let operation = num::FromPrimitive::from_u32(operation);
             
-            let lhs = cursor.read_u8().unwrap();
-            cursor.seek(SeekFrom::Current(1)).unwrap();
-            let register_flags = RegisterFlags::read(&mut cursor);
-            let destination = cursor.read_u8().unwrap();
+            let lhs = cursor.read_u8()?;
+            cursor.seek(SeekFrom::Current(1))?;
+            let register_flags = RegisterFlags::read(&mut cursor)?;
+            let destination = cursor.read_u8()?;
             
             
             if let Some(operation) = operation {
                 storage::with(
                     exe_char.get_ptr(),
-                    |store| {
+                    |store| -> CommandResult<_> {
                         let lhs = store.resolve_indirect_register(lhs, register_flags.is_lhs_indirect());
                         let destination = store.resolve_indirect_register(destination, register_flags.is_destination_indirect());
                         
@@ -210,18 +321,20 @@ pub fn handle_ano_command(command : AnoCmd, exe_char : Char, command_ptr : usize
                         
                         match op_type {
                             RegisterType::F32 => {
-                                let rhs = store.cursor_read_f32_with_replacement(&mut cursor);
+                                let rhs = store.cursor_read_f32_with_replacement(&mut cursor)?;
                                 
                                 store.register_imm_operation_f32(lhs, rhs, destination, operation, register_flags);
                             },
                             RegisterType::I32 | RegisterType::Bool => {
-                                let rhs = cursor.read_i32::<LittleEndian>().unwrap();
+                                let rhs = cursor.read_i32::<LittleEndian>()?;
                                 
                                 store.register_imm_operation_i32(lhs, rhs, destination, operation, register_flags);
                             },
                         };
+                        
+                        Ok(())
                     }
-                );
+                )?;
             }
         },
         AnoCmd::BinaryOperationRegisterRegister => {
@@ -232,13 +345,13 @@ pub fn handle_ano_command(command : AnoCmd, exe_char : Char, command_ptr : usize
                     |store| {
                         store.cursor_read_u32_with_replacement(&mut cursor)
                     }
-                );
+                )?;
             let operation = num::FromPrimitive::from_u32(operation);
             
-            let lhs = cursor.read_u8().unwrap();
-            let rhs = cursor.read_u8().unwrap();
-            let register_flags = RegisterFlags::read(&mut cursor);
-            let destination = cursor.read_u8().unwrap();
+            let lhs = cursor.read_u8()?;
+            let rhs = cursor.read_u8()?;
+            let register_flags = RegisterFlags::read(&mut cursor)?;
+            let destination = cursor.read_u8()?;
             
             if let Some(operation) = operation {
                 storage::with(
@@ -262,13 +375,13 @@ pub fn handle_ano_command(command : AnoCmd, exe_char : Char, command_ptr : usize
                     |store| {
                         store.cursor_read_u32_with_replacement(&mut cursor)
                     }
-                );
+                )?;
             let operation = num::FromPrimitive::from_u32(operation);
             
-            let reg = cursor.read_u8().unwrap();
-            cursor.seek(SeekFrom::Current(1)).unwrap();
-            let register_flags = RegisterFlags::read(&mut cursor);
-            let destination = cursor.read_u8().unwrap();
+            let reg = cursor.read_u8()?;
+            cursor.seek(SeekFrom::Current(1))?;
+            let register_flags = RegisterFlags::read(&mut cursor)?;
+            let destination = cursor.read_u8()?;
             
             if let Some(operation) = operation {
                 storage::with(
@@ -290,18 +403,18 @@ pub fn handle_ano_command(command : AnoCmd, exe_char : Char, command_ptr : usize
                     |store| {
                         store.cursor_read_u32_with_replacement(&mut cursor)
                     }
-                );
+                )?;
             let operation = num::FromPrimitive::from_u32(operation);
             
-            cursor.seek(SeekFrom::Current(2)).unwrap();
-            let register_flags = RegisterFlags::read(&mut cursor);
-            let destination = cursor.read_u8().unwrap();
+            cursor.seek(SeekFrom::Current(2))?;
+            let register_flags = RegisterFlags::read(&mut cursor)?;
+            let destination = cursor.read_u8()?;
             
             
             if let Some(operation) = operation {
                 storage::with(
                     exe_char.get_ptr(),
-                    |store| {
+                    |store| -> CommandResult<_> {
                         let destination = store.resolve_indirect_register(destination, register_flags.is_destination_indirect());
                         
                         let op_type = if register_flags.is_lhs_bool() | register_flags.is_destination_bool()
@@ -313,23 +426,25 @@ pub fn handle_ano_command(command : AnoCmd, exe_char : Char, command_ptr : usize
                         
                         match op_type {
                             RegisterType::F32 => {
-                                let immediate = store.cursor_read_f32_with_replacement(&mut cursor);
+                                let immediate = store.cursor_read_f32_with_replacement(&mut cursor)?;
                                 
                                 store.immediate_unary_operation_f32(immediate, destination, operation, register_flags);
                             },
                             RegisterType::I32 => {
-                                let immediate = cursor.read_i32::<LittleEndian>().unwrap();
+                                let immediate = cursor.read_i32::<LittleEndian>()?;
                                 
                                 store.immediate_unary_operation_i32(immediate, destination, operation, register_flags);
                             },
                             RegisterType::Bool => {
-                                let immediate = cursor.read_i32::<LittleEndian>().unwrap();
+                                let immediate = cursor.read_i32::<LittleEndian>()?;
                                 
                                 store.immediate_unary_operation_bool(immediate, destination, operation, register_flags);
                             },
                         };
+                        
+                        Ok(())
                     }
-                );
+                )?;
             }
         },
         AnoCmd::LoadVarIntoRegister => {
@@ -347,7 +462,7 @@ pub fn handle_ano_command(command : AnoCmd, exe_char : Char, command_ptr : usize
                         },
                     }
                 }
-            )
+            )?
         }, 
         AnoCmd::StoreVarFromRegister => {
             store_var_from_register(exe_char, command_ptr, |variable_character, var, source_type, source_value| {
@@ -362,57 +477,111 @@ pub fn handle_ano_command(command : AnoCmd, exe_char : Char, command_ptr : usize
                         var_rw::MatchState::store_i32(variable_character.get_ptr(), var, source_value.into_int());
                     },
                 }
-            })
+            })?
         },
         AnoCmd::StoreVarFromImmediate => {
-            store_var_from_immediate(exe_char, command_ptr)
+            store_var_from_immediate(exe_char, command_ptr)?
         },
         AnoCmd::BinaryOperationVarRegister => {
-            binary_operation_var_register(exe_char, command_ptr)
+            binary_operation_var_register(exe_char, command_ptr)?
         },
         AnoCmd::BinaryOperationVarImmediate => {
-            binary_operation_var_immediate(exe_char, command_ptr)
+            binary_operation_var_immediate(exe_char, command_ptr)?
         },
         AnoCmd::UnaryOperationVar => {
-            unary_operation_var(exe_char, command_ptr)
+            unary_operation_var(exe_char, command_ptr)?
         },
         AnoCmd::CheckCharacterName => {
-            check_character_name(exe_char, command_ptr)
+            check_character_name(exe_char, command_ptr)?
+        },
+        AnoCmd::CheckMotion => {
+            check_motion(exe_char, command_ptr)?
         },
         AnoCmd::SuckX => {
-            use character_extensions::SuckOpponent;
+            use character_extensions::Forces;
             
             let mut cursor = unsafe { get_cursor(command_ptr, const { size_of::<f32>() * 2 }) };
             let (magnitude, delta) = storage::with(
                 exe_char.get_ptr(),
-                |store| {
-                    let magnitude = store.cursor_read_f32_with_replacement(&mut cursor);
-                    let delta = store.cursor_read_f32_with_replacement(&mut cursor);
+                |store| -> CommandResult<_> {
+                    let magnitude = store.cursor_read_f32_with_replacement(&mut cursor)?;
+                    let delta = store.cursor_read_f32_with_replacement(&mut cursor)?;
                     
-                    (magnitude, delta)
+                    Ok((magnitude, delta))
                 }
-            );
-            SuckOpponent::apply_suck(exe_char, magnitude, delta);
+            )?;
+            Forces::apply_suck(exe_char, magnitude, delta);
+        },
+        AnoCmd::SetForce => {
+            set_force(exe_char, command_ptr)?
+        },
+        AnoCmd::ClearForce => {
+            clear_force(exe_char, command_ptr)?
         },
         AnoCmd::ConditionalBinaryOperation => {
-            conditional_binary_operation(exe_char, command_ptr)
+            conditional_binary_operation(exe_char, command_ptr)?
+        },
+        AnoCmd::TernaryOperationRegisters => {
+            ternary_operation_registers(exe_char, command_ptr)?
+        },
+        AnoCmd::TernaryOperationRegisterImmediate => {
+            ternary_operation_register_immediate(exe_char, command_ptr)?
+        },
+        AnoCmd::SkipIf => {
+            branch_if(exe_char, command_ptr, false)?
+        },
+        AnoCmd::JumpIf => {
+            branch_if(exe_char, command_ptr, true)?
+        },
+        AnoCmd::ScheduleNextCommand => {
+            schedule_next_command(exe_char, command_ptr)?
+        },
+        AnoCmd::CancelScheduled => {
+            cancel_scheduled(exe_char, command_ptr)?
         },
         AnoCmd::GetProjectile => {
-            get_projectile(exe_char, command_ptr)
+            get_projectile(exe_char, command_ptr)?
+        },
+        AnoCmd::LoadVectorFromPosition => {
+            load_vector_from_position(exe_char, command_ptr)?
+        },
+        AnoCmd::StoreVectorToPosition => {
+            store_vector_to_position(exe_char, command_ptr)?
+        },
+        AnoCmd::VectorOperation => {
+            vector_operation(exe_char, command_ptr)?
+        },
+        AnoCmd::DirectionToCharacter => {
+            direction_to_character(exe_char, command_ptr)?
+        },
+        AnoCmd::LoadSharedRegister => {
+            load_shared_register(exe_char, command_ptr)?
+        },
+        AnoCmd::StoreSharedRegister => {
+            store_shared_register(exe_char, command_ptr)?
+        },
+        AnoCmd::SetRegisterPersistence => {
+            set_register_persistence(exe_char, command_ptr)?
+        },
+        AnoCmd::SaveSnapshot => {
+            save_snapshot(exe_char, command_ptr)?
+        },
+        AnoCmd::LoadSnapshot => {
+            load_snapshot(exe_char, command_ptr)?
         },
         AnoCmd::GetProjectileFilteredByOperation => {
-            get_projectile_filtered_by_operation(exe_char, command_ptr)
+            get_projectile_filtered_by_operation(exe_char, command_ptr)?
         },
         AnoCmd::GetProjectileFilteredByFilename => {
-            get_projectile_filtered_by_filename(exe_char, command_ptr)
+            get_projectile_filtered_by_filename(exe_char, command_ptr)?
         },
         AnoCmd::NextProjectile => {
-            next_projectile(exe_char, command_ptr)
+            next_projectile(exe_char, command_ptr)?
         },
         AnoCmd::LoadProjectileVarIntoRegister => {
             load_var_into_register(exe_char, command_ptr,
                 |variable_character, destination_type, var| {
-                    crate::debug_msg(format!("getting proj variable?"));
+                    crate::debug_msg("getting proj variable?");
                     
                     storage::with_stored_projectile(variable_character.get_ptr(), 0.into_number(), |projectile| {
                         match destination_type {
@@ -428,7 +597,7 @@ pub fn handle_ano_command(command : AnoCmd, exe_char : Char, command_ptr : usize
                         }
                     })
                 }
-            )
+            )?
         },
         
         AnoCmd::StoreProjectileVarFromRegister => {
@@ -446,23 +615,25 @@ pub fn handle_ano_command(command : AnoCmd, exe_char : Char, command_ptr : usize
                         },
                     }
                 });
-            })
+            })?
         },
-    }
+    };
+    
+    Ok(())
 }
 
 
-fn load_var_into_register<F>(storage_character : Char, command_ptr : usize, loading_fn : F)
+fn load_var_into_register<F>(storage_character : Char, command_ptr : usize, loading_fn : F) -> CommandResult
     where F : FnOnce(&Char, RegisterType, u32) -> Number
 {
     let mut cursor = unsafe { get_cursor(command_ptr, const { size_of::<u32>() * 2 }) };
     
-    cursor.seek(SeekFrom::Current(1)).unwrap();
-    let character_relation = CharacterRelation::decode(cursor.read_u8().unwrap());
+    cursor.seek(SeekFrom::Current(1))?;
+    let character_relation = CharacterRelation::decode(cursor.read_u8()?);
     let variable_character = storage_character.related_character(character_relation);
-    let register_flags = RegisterFlags::read(&mut cursor);
-    let destination = cursor.read_u8().unwrap();
-    let var = cursor.read_u32::<LittleEndian>().unwrap();
+    let register_flags = RegisterFlags::read(&mut cursor)?;
+    let destination = cursor.read_u8()?;
+    let var = cursor.read_u32::<LittleEndian>()?;
     
     let destination = storage::with(
         storage_character.get_ptr(),
@@ -523,27 +694,29 @@ fn load_var_into_register<F>(storage_character : Char, command_ptr : usize, load
             storage_character.set_condition_register(result);
         },
     };
+    
+    Ok(())
 }
 
-fn store_var_from_register<F>(storage_character : Char, command_ptr : usize, storing_fn : F)
+fn store_var_from_register<F>(storage_character : Char, command_ptr : usize, storing_fn : F) -> CommandResult
     where F : FnOnce(&Char, u32, RegisterType, Number)
 {
     let mut cursor = unsafe { get_cursor(command_ptr, const { size_of::<u32>() * 2 }) };
     
-    cursor.seek(SeekFrom::Current(1)).unwrap();
-    let character_relation = CharacterRelation::decode(cursor.read_u8().unwrap());
+    cursor.seek(SeekFrom::Current(1))?;
+    let character_relation = CharacterRelation::decode(cursor.read_u8()?);
     
     let variable_character = {
         match storage_character.related_character(character_relation) {
             Some(variable_character) => variable_character,
             // just early out if we def cant figure out what character we're doing this to
-            None => return,
+            None => return Ok(()),
         }
     };
     
-    let register_flags = RegisterFlags::read(&mut cursor);
-    let source = cursor.read_u8().unwrap();
-    let var = cursor.read_u32::<LittleEndian>().unwrap();
+    let register_flags = RegisterFlags::read(&mut cursor)?;
+    let source = cursor.read_u8()?;
+    let var = cursor.read_u32::<LittleEndian>()?;
     
     let (source_type, source_value) = storage::with(
             storage_character.get_ptr(),
@@ -576,28 +749,30 @@ fn store_var_from_register<F>(storage_character : Char, command_ptr : usize, sto
     storing_fn(&variable_character, var, source_type, source_value);
     
     storage_character.set_condition_register(source_value.into_int());
+    
+    Ok(())
 }
 
 
-fn store_var_from_immediate(storage_character : Char, command_ptr : usize)
+fn store_var_from_immediate(storage_character : Char, command_ptr : usize) -> CommandResult
 {
     let mut cursor = unsafe { get_cursor(command_ptr, const { size_of::<u32>() * 3 }) };
     
-    cursor.seek(SeekFrom::Current(1)).unwrap();
-    let character_relation = CharacterRelation::decode(cursor.read_u8().unwrap());
+    cursor.seek(SeekFrom::Current(1))?;
+    let character_relation = CharacterRelation::decode(cursor.read_u8()?);
     
     let variable_character = {
         match storage_character.related_character(character_relation) {
             Some(variable_character) => variable_character,
             // just early out if we def cant figure out what character we're doing this to
-            None => return,
+            None => return Ok(()),
         }
     };
     
-    let _register_flags = RegisterFlags::read(&mut cursor);
-    cursor.seek(SeekFrom::Current(1)).unwrap();
+    let _register_flags = RegisterFlags::read(&mut cursor)?;
+    cursor.seek(SeekFrom::Current(1))?;
     
-    let var = cursor.read_u32::<LittleEndian>().unwrap();
+    let var = cursor.read_u32::<LittleEndian>()?;
     
     let variable_type = var_rw::MatchState::get_number_type(var);
     
@@ -608,21 +783,23 @@ fn store_var_from_immediate(storage_character : Char, command_ptr : usize)
                     |store| {
                         store.cursor_read_f32_with_replacement(&mut cursor)
                     }
-                );
+                )?;
             
             var_rw::MatchState::store_f32(variable_character.get_ptr(), var, immediate);
         },
         Some(RegisterType::I32 | RegisterType::Bool) => {
-            let immediate = cursor.read_i32::<LittleEndian>().unwrap();
+            let immediate = cursor.read_i32::<LittleEndian>()?;
             
             var_rw::MatchState::store_i32(variable_character.get_ptr(), var, immediate);
         },
         None => {},
     };
+    
+    Ok(())
 }
 
 
-fn binary_operation_var_register(storage_character : Char, command_ptr : usize)
+fn binary_operation_var_register(storage_character : Char, command_ptr : usize) -> CommandResult
 {
     let mut cursor = unsafe { get_cursor(command_ptr, const { size_of::<u32>() * 3 }) };
     
@@ -631,27 +808,27 @@ fn binary_operation_var_register(storage_character : Char, command_ptr : usize)
             |store| {
                 store.cursor_read_u32_with_replacement(&mut cursor)
             }
-        );
+        )?;
     let operation : Option<BinaryOp> = num::FromPrimitive::from_u32(operation);
     
-    let rhs = cursor.read_u8().unwrap();
+    let rhs = cursor.read_u8()?;
     
-    let character_relation = CharacterRelation::decode(cursor.read_u8().unwrap());
+    let character_relation = CharacterRelation::decode(cursor.read_u8()?);
     
     let variable_character = {
         match storage_character.related_character(character_relation) {
             Some(variable_character) => variable_character,
             // just early out if we def cant figure out what character we're doing this to
-            None => return,
+            None => return Ok(()),
         }
     };
     
     
-    let register_flags = RegisterFlags::read(&mut cursor);
+    let register_flags = RegisterFlags::read(&mut cursor)?;
     
-    cursor.seek(SeekFrom::Current(1)).unwrap();
+    cursor.seek(SeekFrom::Current(1))?;
     
-    let var = cursor.read_u32::<LittleEndian>().unwrap();
+    let var = cursor.read_u32::<LittleEndian>()?;
     
     let variable_type = var_rw::MatchState::get_number_type(var);
     
@@ -711,9 +888,11 @@ fn binary_operation_var_register(storage_character : Char, command_ptr : usize)
         },
         _ => {},
     };
+    
+    Ok(())
 }
 
-fn binary_operation_var_immediate(storage_character : Char, command_ptr : usize)
+fn binary_operation_var_immediate(storage_character : Char, command_ptr : usize) -> CommandResult
 {
     let mut cursor = unsafe { get_cursor(command_ptr, const { size_of::<u32>() * 4 }) };
     
@@ -722,24 +901,24 @@ fn binary_operation_var_immediate(storage_character : Char, command_ptr : usize)
             |store| {
                 store.cursor_read_u32_with_replacement(&mut cursor)
             }
-        );
+        )?;
     let operation : Option<BinaryOp> = num::FromPrimitive::from_u32(operation);
     
-    cursor.seek(SeekFrom::Current(1)).unwrap();
+    cursor.seek(SeekFrom::Current(1))?;
     
-    let character_relation = CharacterRelation::decode(cursor.read_u8().unwrap());
+    let character_relation = CharacterRelation::decode(cursor.read_u8()?);
     
     let variable_character = {
         match storage_character.related_character(character_relation) {
             Some(variable_character) => variable_character,
             // just early out if we def cant figure out what character we're doing this to
-            None => return,
+            None => return Ok(()),
         }
     };
     
-    cursor.seek(SeekFrom::Current(2)).unwrap();
+    cursor.seek(SeekFrom::Current(2))?;
     
-    let var = cursor.read_u32::<LittleEndian>().unwrap();
+    let var = cursor.read_u32::<LittleEndian>()?;
     
     let variable_type = var_rw::MatchState::get_number_type(var);
     
@@ -753,14 +932,14 @@ fn binary_operation_var_immediate(storage_character : Char, command_ptr : usize)
                 |store| {
                     store.cursor_read_f32_with_replacement(&mut cursor)
                 }
-            );
+            )?;
             
             let result = operation.operate(lhs, rhs);
             
             var_rw::MatchState::store_f32(variable_character.get_ptr(), var, result);
         },
         (Some(RegisterType::I32), Some(operation)) => {
-            let rhs = cursor.read_i32::<LittleEndian>().unwrap();
+            let rhs = cursor.read_i32::<LittleEndian>()?;
             
             let result = operation.operate(lhs, rhs);
             
@@ -768,9 +947,11 @@ fn binary_operation_var_immediate(storage_character : Char, command_ptr : usize)
         },
         _ => {},
     };
+    
+    Ok(())
 }
 
-fn unary_operation_var(storage_character : Char, command_ptr : usize)
+fn unary_operation_var(storage_character : Char, command_ptr : usize) -> CommandResult
 {
     let mut cursor = unsafe { get_cursor(command_ptr, const { size_of::<u32>() * 4 }) };
     
@@ -779,24 +960,24 @@ fn unary_operation_var(storage_character : Char, command_ptr : usize)
             |store| {
                 store.cursor_read_u32_with_replacement(&mut cursor)
             }
-        );
+        )?;
     let operation : Option<UnaryOp> = num::FromPrimitive::from_u32(operation);
     
-    cursor.seek(SeekFrom::Current(1)).unwrap();
+    cursor.seek(SeekFrom::Current(1))?;
     
-    let character_relation = CharacterRelation::decode(cursor.read_u8().unwrap());
+    let character_relation = CharacterRelation::decode(cursor.read_u8()?);
     
     let variable_character = {
         match storage_character.related_character(character_relation) {
             Some(variable_character) => variable_character,
             // just early out if we def cant figure out what character we're doing this to
-            None => return,
+            None => return Ok(()),
         }
     };
     
-    cursor.seek(SeekFrom::Current(2)).unwrap();
+    cursor.seek(SeekFrom::Current(2))?;
     
-    let var = cursor.read_u32::<LittleEndian>().unwrap();
+    let var = cursor.read_u32::<LittleEndian>()?;
     
     let variable_type = var_rw::MatchState::get_number_type(var);
     let input = var_rw::MatchState::load_number(variable_character.get_ptr(), var);
@@ -815,35 +996,37 @@ fn unary_operation_var(storage_character : Char, command_ptr : usize)
         },
         _ => {},
     };
+    
+    Ok(())
 }
 
-fn conditional_binary_operation(storage_character : Char, command_ptr : usize)
+fn conditional_binary_operation(storage_character : Char, command_ptr : usize) -> CommandResult
 {
     let mut cursor = unsafe { get_cursor(command_ptr, const { size_of::<u32>() * 6 }) };
     
     let (comparator_operation, result_operation) = storage::with(
             storage_character.get_ptr(),
-            |store| {
-                (store.cursor_read_u32_with_replacement(&mut cursor),
-                store.cursor_read_u32_with_replacement(&mut cursor))
+            |store| -> CommandResult<_> {
+                Ok((store.cursor_read_u32_with_replacement(&mut cursor)?,
+                store.cursor_read_u32_with_replacement(&mut cursor)?))
             }
-        );
+        )?;
     let comparator_operation : Option<BinaryOp> = num::FromPrimitive::from_u32(comparator_operation);
     let result_operation = num::FromPrimitive::from_u32(result_operation);
     
     
     
-    let lhs = cursor.read_u8().unwrap();
-    cursor.seek(SeekFrom::Current(1)).unwrap();
-    let register_flags = RegisterFlags::read(&mut cursor);
-    let destination = cursor.read_u8().unwrap();
+    let lhs = cursor.read_u8()?;
+    cursor.seek(SeekFrom::Current(1))?;
+    let register_flags = RegisterFlags::read(&mut cursor)?;
+    let destination = cursor.read_u8()?;
     
     
     if let Some(comparator_operation) = comparator_operation 
         && let Some(result_operation) = result_operation {
         storage::with(
             storage_character.get_ptr(),
-            |store| {
+            |store| -> CommandResult<_> {
                 let lhs = store.resolve_indirect_register(lhs, register_flags.is_lhs_indirect());
                 let destination = store.resolve_indirect_register(destination, register_flags.is_destination_indirect());
                 
@@ -864,19 +1047,19 @@ fn conditional_binary_operation(storage_character : Char, command_ptr : usize)
                 let is_condition_passed = {
                     match lhs_type {
                         RegisterType::I32 => {
-                            let rhs_comparator = cursor.read_i32::<LittleEndian>().unwrap();
+                            let rhs_comparator = cursor.read_i32::<LittleEndian>()?;
                             let comparison = comparator_operation.operate(store.get_number_register(lhs), rhs_comparator);
                             
                             comparison.is_true()
                         },
                         RegisterType::F32 => {
-                            let rhs_comparator = store.cursor_read_f32_with_replacement(&mut cursor);
+                            let rhs_comparator = store.cursor_read_f32_with_replacement(&mut cursor)?;
                             let comparison = comparator_operation.operate(store.get_number_register(lhs), rhs_comparator);
                             
                             comparison.is_true()
                         },
                         RegisterType::Bool => {
-                            let rhs_comparator = cursor.read_i32::<LittleEndian>().unwrap();
+                            let rhs_comparator = cursor.read_i32::<LittleEndian>()?;
                             let comparison = comparator_operation.operate(store.get_bool(lhs), rhs_comparator.is_true());
                             
                             comparison.is_true()
@@ -889,12 +1072,12 @@ fn conditional_binary_operation(storage_character : Char, command_ptr : usize)
                     
                     match op_type {
                         RegisterType::F32 => {
-                            let rhs_for_op = store.cursor_read_f32_with_replacement(&mut cursor);
+                            let rhs_for_op = store.cursor_read_f32_with_replacement(&mut cursor)?;
                             
                             store.register_imm_operation_f32(destination, rhs_for_op, destination, result_operation, result_register_flags);
                         },
                         RegisterType::I32 | RegisterType::Bool => {
-                            let rhs_for_op = cursor.read_i32::<LittleEndian>().unwrap();
+                            let rhs_for_op = cursor.read_i32::<LittleEndian>()?;
                             
                             store.register_imm_operation_i32(destination, rhs_for_op, destination, result_operation, result_register_flags);
                         },
@@ -902,59 +1085,691 @@ fn conditional_binary_operation(storage_character : Char, command_ptr : usize)
                     
                     match lhs_type {
                         RegisterType::I32 => {
-                            let immediate = cursor.read_i32::<LittleEndian>().unwrap();
+                            let immediate = cursor.read_i32::<LittleEndian>()?;
                             
                             store.set_i32_register(lhs, immediate);
                         },
                         RegisterType::F32 => {
-                            let immediate = store.cursor_read_f32_with_replacement(&mut cursor);
+                            let immediate = store.cursor_read_f32_with_replacement(&mut cursor)?;
                             
                             store.set_f32_register(lhs, immediate);
                         },
                         RegisterType::Bool => {
-                            let immediate = cursor.read_i32::<LittleEndian>().unwrap().is_true();
+                            let immediate = cursor.read_i32::<LittleEndian>()?.is_true();
                             
                             store.set_bool(destination, immediate);
                         }
                     }
                 }
+                
+                Ok(())
+            }
+        )?;
+    }
+    
+    Ok(())
+}
+
+/// 66_1d. all three operands are registers
+fn ternary_operation_registers(storage_character : Char, command_ptr : usize) -> CommandResult
+{
+    let mut cursor = unsafe { get_cursor(command_ptr, const { size_of::<u32>() * 3 }) };
+    
+    let operation = storage::with(
+            storage_character.get_ptr(),
+            |store| {
+                store.cursor_read_u32_with_replacement(&mut cursor)
+            }
+        )?;
+    let operation : Option<TernaryOp> = num::FromPrimitive::from_u32(operation);
+    
+    let first = cursor.read_u8()?;
+    let second = cursor.read_u8()?;
+    let register_flags = RegisterFlags::read(&mut cursor)?;
+    let destination = cursor.read_u8()?;
+    let third = cursor.read_u8()?;
+    
+    if let Some(operation) = operation {
+        storage::with(
+            storage_character.get_ptr(),
+            |store| {
+                let first = store.resolve_indirect_register(first, register_flags.is_lhs_indirect());
+                let second = store.resolve_indirect_register(second, register_flags.is_rhs_indirect());
+                let third = store.resolve_indirect_register(third, register_flags.is_third_indirect());
+                let destination = store.resolve_indirect_register(destination, register_flags.is_destination_indirect());
+                
+                let first = store.get_operand(first, register_flags.is_lhs_bool());
+                let second = store.get_operand(second, register_flags.is_rhs_bool());
+                let third = store.get_operand(third, register_flags.is_third_bool());
+                
+                store.ternary_operation(first, second, third, destination, operation, register_flags);
             }
         );
     }
+    
+    Ok(())
+}
+
+/// 66_1e. the first operand is a register, the other two are immediates of the same type as the destination
+fn ternary_operation_register_immediate(storage_character : Char, command_ptr : usize) -> CommandResult
+{
+    let mut cursor = unsafe { get_cursor(command_ptr, const { size_of::<u32>() * 4 }) };
+    
+    let operation = storage::with(
+            storage_character.get_ptr(),
+            |store| {
+                store.cursor_read_u32_with_replacement(&mut cursor)
+            }
+        )?;
+    let operation : Option<TernaryOp> = num::FromPrimitive::from_u32(operation);
+    
+    let first = cursor.read_u8()?;
+    cursor.seek(SeekFrom::Current(1))?;
+    let register_flags = RegisterFlags::read(&mut cursor)?;
+    let destination = cursor.read_u8()?;
+    
+    if let Some(operation) = operation {
+        storage::with(
+            storage_character.get_ptr(),
+            |store| -> CommandResult<_> {
+                let first = store.resolve_indirect_register(first, register_flags.is_lhs_indirect());
+                let destination = store.resolve_indirect_register(destination, register_flags.is_destination_indirect());
+                
+                let first = store.get_operand(first, register_flags.is_lhs_bool());
+                
+                let (second, third) = match RegisterType::identify(destination) {
+                    RegisterType::F32 => {
+                        let second = store.cursor_read_f32_with_replacement(&mut cursor)?;
+                        let third = store.cursor_read_f32_with_replacement(&mut cursor)?;
+                        
+                        (second.into_number(), third.into_number())
+                    },
+                    RegisterType::I32 | RegisterType::Bool => {
+                        let second = cursor.read_i32::<LittleEndian>()?;
+                        let third = cursor.read_i32::<LittleEndian>()?;
+                        
+                        (second.into_number(), third.into_number())
+                    },
+                };
+                
+                store.ternary_operation(first, second, third, destination, operation, register_flags);
+                
+                Ok(())
+            }
+        )?;
+    }
+    
+    Ok(())
+}
+
+/// 66_20 and 66_21. unlike every other register command, these leave the condition register alone, so they can be
+/// used in between a condition and the game command checking it
+fn branch_if(storage_character : Char, command_ptr : usize, is_jump : bool) -> CommandResult
+{
+    use crate::command_flow::BranchTest;
+    
+    let mut cursor = unsafe { get_cursor(command_ptr, const { size_of::<u32>() * 2 }) };
+    
+    let register = cursor.read_u8()?;
+    let test = cursor.read_u8()?;
+    let register_flags = RegisterFlags::read(&mut cursor)?;
+    cursor.seek(SeekFrom::Current(1))?;
+    let amount = cursor.read_i32::<LittleEndian>()?;
+    
+    let test : Option<BranchTest> = num::FromPrimitive::from_u8(test);
+    
+    if let Some(test) = test {
+        storage::with(
+            storage_character.get_ptr(),
+            |store| {
+                let is_taken = match test {
+                    BranchTest::IfTrue | BranchTest::IfFalse => {
+                        let register = store.resolve_indirect_register(register, register_flags.is_lhs_indirect());
+                        
+                        let value = if register_flags.is_lhs_bool() {
+                            store.get_bool(register)
+                        } else {
+                            store.get_number_register(register).is_true()
+                        };
+                        
+                        value == (test == BranchTest::IfTrue)
+                    },
+                    BranchTest::IfCondition => storage_character.get_condition_register().is_true(),
+                    BranchTest::IfNotCondition => !storage_character.get_condition_register().is_true(),
+                    BranchTest::Always => true,
+                };
+                
+                if is_taken {
+                    if is_jump {
+                        store.command_flow.jump(amount);
+                    } else {
+                        store.command_flow.skip(amount as u32);
+                    }
+                }
+            }
+        );
+    }
+    
+    Ok(())
+}
+
+/// 66_23. sets the condition register to 1 if the next command was scheduled. if it can't be scheduled (it isn't a
+/// register or var operation, or there's too much scheduled already), it runs now like normal and this sets it to 0
+fn schedule_next_command(storage_character : Char, command_ptr : usize) -> CommandResult
+{
+    const ARGUMENTS_SIZE : usize = size_of::<u32>() * 3;
+    
+    let mut cursor = unsafe { get_cursor(command_ptr, ARGUMENTS_SIZE) };
+    
+    // a negative delay or interval from a register counts as 0
+    let (delay, interval) = storage::with(
+        storage_character.get_ptr(),
+        |store| -> CommandResult<_> {
+            let delay = store.cursor_read_u32_with_replacement(&mut cursor)? as i32;
+            let interval = store.cursor_read_u32_with_replacement(&mut cursor)? as i32;
+            
+            Ok((delay.max(0) as u32, interval.max(0) as u32))
+        }
+    )?;
+    let tag = cursor.read_u32::<LittleEndian>()?;
+    
+    let next_ptr = command_ptr + ARGUMENTS_SIZE;
+    let next_group = unsafe { read_ptr_no_check::<u32>(next_ptr) };
+    let next_command = unsafe { read_ptr_no_check::<u32>(next_ptr + 4) };
+    
+    let next_size = num::FromPrimitive::from_u32(next_command)
+        .filter(|_| next_group == 0x66 && crate::schedule::is_schedulable(next_command))
+        .map(|next : AnoCmd| next.argument_size());
+    
+    let is_scheduled = match next_size {
+        Some(next_size) => storage::with(
+            storage_character.get_ptr(),
+            |store| {
+                let arguments = unsafe { get_cursor(next_ptr + 8, next_size) }.into_inner();
+                
+                let is_added = store.schedule.add(next_command, arguments, delay, interval, tag);
+                
+                if is_added {
+                    store.command_flow.skip(1);
+                }
+                
+                is_added
+            }
+        ),
+        None => false,
+    };
+    
+    storage_character.set_condition_register(is_scheduled.from_bool());
+    
+    Ok(())
+}
+
+/// 66_24. sets the condition register to how many scheduled commands were cancelled
+fn cancel_scheduled(storage_character : Char, command_ptr : usize) -> CommandResult
+{
+    let tag = unsafe { read_ptr_no_check::<u32>(command_ptr) };
+    
+    let cancelled = storage::with(
+        storage_character.get_ptr(),
+        |store| {
+            store.schedule.cancel(tag)
+        }
+    );
+    
+    storage_character.set_condition_register(cancelled as i32);
+    
+    Ok(())
+}
+
+/// 66_40. sets the condition register to 1 if there was a character (or projectile) to load from, otherwise loads 0, 0
+fn load_vector_from_position(storage_character : Char, command_ptr : usize) -> CommandResult
+{
+    let mut cursor = unsafe { get_cursor(command_ptr, const { size_of::<u32>() * 2 }) };
+    
+    cursor.seek(SeekFrom::Current(1))?;
+    let character_relation = CharacterRelation::decode(cursor.read_u8()?);
+    let register_flags = RegisterFlags::read(&mut cursor)?;
+    let destination = cursor.read_u8()?;
+    let source : Option<PositionSource> = num::FromPrimitive::from_u32(cursor.read_u32::<LittleEndian>()?);
+    
+    let related_character = storage_character.related_character(character_relation);
+    
+    // has to happen outside of storage::with, since the projectile is kept in storage too
+    let position = match (related_character, source) {
+        (Some(related_character), Some(PositionSource::Character)) => {
+            Some(Vec2::new(related_character.get_x_pos(), related_character.get_y_pos()))
+        },
+        (Some(related_character), Some(PositionSource::Projectile)) => {
+            storage::with_stored_projectile(related_character.get_ptr(), None, |projectile| {
+                Some(Vec2::new(projectile.get_x_pos(), projectile.get_y_pos()))
+            })
+        },
+        _ => None,
+    };
+    
+    storage::with(
+        storage_character.get_ptr(),
+        |store| {
+            let destination = store.resolve_indirect_register(destination, register_flags.is_destination_indirect());
+            
+            store.set_vector(destination, position.unwrap_or(Vec2::ZERO));
+        }
+    );
+    
+    storage_character.set_condition_register(position.is_some().from_bool());
+    
+    Ok(())
+}
+
+/// 66_41. sets the condition register to 1 if there was a character (or projectile) to move
+fn store_vector_to_position(storage_character : Char, command_ptr : usize) -> CommandResult
+{
+    let mut cursor = unsafe { get_cursor(command_ptr, const { size_of::<u32>() * 2 }) };
+    
+    cursor.seek(SeekFrom::Current(1))?;
+    let character_relation = CharacterRelation::decode(cursor.read_u8()?);
+    let register_flags = RegisterFlags::read(&mut cursor)?;
+    let source = cursor.read_u8()?;
+    let destination : Option<PositionSource> = num::FromPrimitive::from_u32(cursor.read_u32::<LittleEndian>()?);
+    
+    let position = storage::with(
+        storage_character.get_ptr(),
+        |store| {
+            let source = store.resolve_indirect_register(source, register_flags.is_lhs_indirect());
+            
+            store.get_vector(source)
+        }
+    );
+    
+    let related_character = storage_character.related_character(character_relation);
+    
+    let is_stored = match (related_character, destination) {
+        (Some(related_character), Some(PositionSource::Character)) => {
+            related_character.set_x_pos(position.x);
+            related_character.set_y_pos(position.y);
+            
+            true
+        },
+        (Some(related_character), Some(PositionSource::Projectile)) => {
+            storage::with_stored_projectile(related_character.get_ptr(), false, |projectile| {
+                projectile.set_x_pos(position.x);
+                projectile.set_y_pos(position.y);
+                
+                true
+            })
+        },
+        _ => false,
+    };
+    
+    storage_character.set_condition_register(is_stored.from_bool());
+    
+    Ok(())
+}
+
+/// 66_42. vector results set the condition register to 1 if they're not 0, 0. number results set it like any other register command
+fn vector_operation(storage_character : Char, command_ptr : usize) -> CommandResult
+{
+    let mut cursor = unsafe { get_cursor(command_ptr, const { size_of::<u32>() * 3 }) };
+    
+    let operation = storage::with(
+            storage_character.get_ptr(),
+            |store| {
+                store.cursor_read_u32_with_replacement(&mut cursor)
+            }
+        )?;
+    let operation : Option<VectorOp> = num::FromPrimitive::from_u32(operation);
+    
+    let lhs = cursor.read_u8()?;
+    let rhs = cursor.read_u8()?;
+    let register_flags = RegisterFlags::read(&mut cursor)?;
+    let destination = cursor.read_u8()?;
+    
+    let Some(operation) = operation else {
+        return Ok(());
+    };
+    
+    storage::with(
+        storage_character.get_ptr(),
+        |store| -> CommandResult<_> {
+            let scalar = store.cursor_read_f32_with_replacement(&mut cursor)?;
+            
+            let lhs = store.resolve_indirect_register(lhs, register_flags.is_lhs_indirect());
+            let rhs = store.resolve_indirect_register(rhs, register_flags.is_rhs_indirect());
+            let destination = store.resolve_indirect_register(destination, register_flags.is_destination_indirect());
+            
+            let (lhs, rhs) = if operation.has_number_operands() {
+                (Vec2::new(store.get_operand(lhs, register_flags.is_lhs_bool()).into_float(), 0.0),
+                Vec2::new(store.get_operand(rhs, register_flags.is_rhs_bool()).into_float(), 0.0))
+            } else {
+                (store.get_vector(lhs), store.get_vector(rhs))
+            };
+            
+            match crate::vector::operate(operation, lhs, rhs, scalar) {
+                VectorResult::Vector(result) => {
+                    let result = result.clean();
+                    
+                    store.set_vector(destination, result);
+                    storage_character.set_condition_register((!result.is_zero()).from_bool());
+                },
+                VectorResult::Number(result) => {
+                    let result = clean_float(result);
+                    
+                    if register_flags.is_destination_bool() {
+                        store.set_bool(destination, result.is_true());
+                    } else {
+                        store.set_number_register(destination, Number::F32(result));
+                    }
+                    storage_character.set_condition_register(result as i32);
+                },
+            }
+            
+            Ok(())
+        }
+    )?;
+    
+    Ok(())
+}
+
+/// 66_43. a vector with a length of 1 pointing from this character to the related one. if they're in the same spot,
+/// or there's no related character, it's 0, 0 and the condition register is 0
+fn direction_to_character(storage_character : Char, command_ptr : usize) -> CommandResult
+{
+    let mut cursor = unsafe { get_cursor(command_ptr, const { size_of::<u32>() }) };
+    
+    cursor.seek(SeekFrom::Current(1))?;
+    let character_relation = CharacterRelation::decode(cursor.read_u8()?);
+    let register_flags = RegisterFlags::read(&mut cursor)?;
+    let destination = cursor.read_u8()?;
+    
+    let direction = match storage_character.related_character(character_relation) {
+        Some(related_character) => {
+            let from = Vec2::new(storage_character.get_x_pos(), storage_character.get_y_pos());
+            let to = Vec2::new(related_character.get_x_pos(), related_character.get_y_pos());
+            
+            to.sub(from).normalize()
+        },
+        None => Vec2::ZERO,
+    };
+    
+    storage::with(
+        storage_character.get_ptr(),
+        |store| {
+            let destination = store.resolve_indirect_register(destination, register_flags.is_destination_indirect());
+            
+            store.set_vector(destination, direction);
+        }
+    );
+    
+    storage_character.set_condition_register((!direction.is_zero()).from_bool());
+    
+    Ok(())
+}
+
+/// 66_44. the shared register uses the lhs flags and the character's register uses the destination flags.
+/// sets the condition register to the value loaded. if the team couldn't be figured out, loads 0
+fn load_shared_register(storage_character : Char, command_ptr : usize) -> CommandResult
+{
+    let mut cursor = unsafe { get_cursor(command_ptr, const { size_of::<u32>() }) };
+    
+    let bank : Option<SharedBankSelect> = num::FromPrimitive::from_u8(cursor.read_u8()?);
+    let shared = cursor.read_u8()?;
+    let register_flags = RegisterFlags::read(&mut cursor)?;
+    let destination = cursor.read_u8()?;
+    
+    let Some(bank) = bank else {
+        return Ok(());
+    };
+    
+    let team = storage_character.identify_team();
+    
+    let (shared, destination) = storage::with(
+        storage_character.get_ptr(),
+        |store| {
+            (store.resolve_indirect_register(shared, register_flags.is_lhs_indirect()),
+            store.resolve_indirect_register(destination, register_flags.is_destination_indirect()))
+        }
+    );
+    
+    let value = storage::with_shared_bank(team, bank, Number::I32(0), |shared_bank| {
+        shared_bank.get_operand(shared, register_flags.is_lhs_bool())
+    });
+    
+    storage::with(
+        storage_character.get_ptr(),
+        |store| {
+            if register_flags.is_destination_bool() {
+                store.set_bool(destination, value.is_true());
+            } else {
+                store.set_number_register(destination, value);
+            }
+        }
+    );
+    
+    storage_character.set_condition_register(value.into_int());
+    
+    Ok(())
+}
+
+/// 66_45. the character's register uses the lhs flags and the shared register uses the destination flags, so it's
+/// the other way around from 66_44. sets the condition register to the value stored, or 0 if the team couldn't be figured out
+fn store_shared_register(storage_character : Char, command_ptr : usize) -> CommandResult
+{
+    let mut cursor = unsafe { get_cursor(command_ptr, const { size_of::<u32>() }) };
+    
+    let bank : Option<SharedBankSelect> = num::FromPrimitive::from_u8(cursor.read_u8()?);
+    let shared = cursor.read_u8()?;
+    let register_flags = RegisterFlags::read(&mut cursor)?;
+    let source = cursor.read_u8()?;
+    
+    let Some(bank) = bank else {
+        return Ok(());
+    };
+    
+    let team = storage_character.identify_team();
+    
+    let (shared, value) = storage::with(
+        storage_character.get_ptr(),
+        |store| {
+            let shared = store.resolve_indirect_register(shared, register_flags.is_destination_indirect());
+            let source = store.resolve_indirect_register(source, register_flags.is_lhs_indirect());
+            
+            (shared, store.get_operand(source, register_flags.is_lhs_bool()))
+        }
+    );
+    
+    let is_stored = storage::with_shared_bank(team, bank, false, |shared_bank| {
+        shared_bank.set_operand(shared, register_flags.is_destination_bool(), value);
+        
+        true
+    });
+    
+    storage_character.set_condition_register(if is_stored { value.into_int() } else { 0 });
+    
+    Ok(())
+}
+
+/// 66_46. doesn't touch the condition register
+fn set_register_persistence(storage_character : Char, command_ptr : usize) -> CommandResult
+{
+    let mut cursor = unsafe { get_cursor(command_ptr, const { size_of::<u32>() }) };
+    
+    let first = cursor.read_u8()?;
+    let last = cursor.read_u8()?;
+    let register_flags = RegisterFlags::read(&mut cursor)?;
+    let persistence : Option<Persistence> = num::FromPrimitive::from_u8(cursor.read_u8()?);
+    
+    let Some(persistence) = persistence else {
+        return Ok(());
+    };
+    
+    let char_id = storage_character.get_char_id();
+    
+    storage::with(
+        storage_character.get_ptr(),
+        |store| {
+            store.set_persistence(first, last, register_flags.is_lhs_bool(), persistence, char_id);
+        }
+    );
+    
+    Ok(())
+}
+
+/// the name of a snapshot slot, the 64 byte string in 66_47 and 66_48
+fn read_snapshot_slot_name(command_ptr : usize) -> Vec<u8>
+{
+    let mut cursor = unsafe { get_cursor(command_ptr, const { size_of::<u8>() * 64 }) };
+    
+    match GStr::from_cursor(&mut cursor, 64) {
+        Some(name) => name.into_iter().map(|c| c as u8).collect(),
+        None => Vec::new(),
+    }
+}
+
+/// 66_47. sets the condition register to 1
+fn save_snapshot(storage_character : Char, command_ptr : usize) -> CommandResult
+{
+    let name = read_snapshot_slot_name(command_ptr);
+    
+    let snapshot = storage::with(
+        storage_character.get_ptr(),
+        |store| {
+            store.save_snapshot()
+        }
+    );
+    
+    crate::snapshot::save_slot(storage_character.get_ptr(), &name, snapshot);
+    
+    storage_character.set_condition_register(1);
+    
+    Ok(())
+}
+
+/// 66_48. sets the condition register to 1 if there was a snapshot in the slot to load, otherwise nothing changes and it's 0.
+/// the condition register is set after loading, so it doesn't come from the snapshot
+fn load_snapshot(storage_character : Char, command_ptr : usize) -> CommandResult
+{
+    let name = read_snapshot_slot_name(command_ptr);
+    
+    let is_loaded = match crate::snapshot::load_slot(storage_character.get_ptr(), &name) {
+        Some(snapshot) => storage::with(
+            storage_character.get_ptr(),
+            |store| {
+                store.load_snapshot(&snapshot).is_ok()
+            }
+        ),
+        None => false,
+    };
+    
+    storage_character.set_condition_register(is_loaded.from_bool());
+    
+    Ok(())
 }
 
-fn check_character_name(storage_character : Char, command_ptr : usize)
+/// 66_51. sets the condition register to 1 if the force was set, or 0 if the slot or falloff doesn't exist
+fn set_force(storage_character : Char, command_ptr : usize) -> CommandResult
 {
-    let cursor_size = const { size_of::<u32>() * 1 + size_of::<u8>() * 64 };
+    use character_extensions::{Force, ForceFlags, Falloff};
+    
+    let mut cursor = unsafe { get_cursor(command_ptr, const { size_of::<u32>() * 5 }) };
+    
+    let slot = cursor.read_u8()?;
+    let relation = cursor.read_u8()?;
+    let flags = ForceFlags::new(cursor.read_u8()?);
+    let falloff : Option<Falloff> = num::FromPrimitive::from_u8(cursor.read_u8()?);
+    
+    let [x, y, decay, radius] = storage::with(
+        storage_character.get_ptr(),
+        |store| -> CommandResult<_> {
+            let mut values = [0.0; 4];
+            
+            for value in &mut values {
+                let read = store.cursor_read_f32_with_replacement(&mut cursor)?;
+                
+                *value = if read.is_finite() { read } else { 0.0 };
+            }
+            
+            Ok(values)
+        }
+    )?;
+    
+    let is_set = match falloff {
+        Some(falloff) => {
+            let push = if flags.is_radial() {
+                Vec2::new(x, 0.0)
+            } else {
+                Force::facing(&storage_character, x, y)
+            };
+            
+            let force = Force {
+                relation,
+                flags,
+                falloff,
+                push,
+                decay,
+                radius,
+            };
+            
+            storage::with(
+                storage_character.get_ptr(),
+                |store| {
+                    store.forces.set(slot as usize, force)
+                }
+            )
+        },
+        None => false,
+    };
+    
+    storage_character.set_condition_register(is_set.from_bool());
+    
+    Ok(())
+}
+
+/// 66_52. slot FF clears all of them, 66_50's too. doesn't touch the condition register
+fn clear_force(storage_character : Char, command_ptr : usize) -> CommandResult
+{
+    let slot = unsafe { read_ptr_no_check::<u8>(command_ptr) };
+    
+    let slot = if slot == u8::MAX { None } else { Some(slot as usize) };
+    
+    storage::with(
+        storage_character.get_ptr(),
+        |store| {
+            store.forces.clear(slot)
+        }
+    );
+    
+    Ok(())
+}
+
+fn check_character_name(storage_character : Char, command_ptr : usize) -> CommandResult
+{
+    let cursor_size = const { size_of::<u32>() + size_of::<u8>() * 64 };
     let mut cursor = unsafe { get_cursor(command_ptr, cursor_size) };
     
-    cursor.seek(SeekFrom::Current(1)).unwrap();
+    cursor.seek(SeekFrom::Current(1))?;
     
-    let character_relation = CharacterRelation::decode(cursor.read_u8().unwrap());
+    let character_relation = CharacterRelation::decode(cursor.read_u8()?);
     
     let variable_character = {
         match storage_character.related_character(character_relation) {
             Some(variable_character) => variable_character,
             // just early out if we def cant figure out what character we're doing this to
-            None => return,
+            None => return Ok(()),
         }
     };
     
-    let register_flags = RegisterFlags::read(&mut cursor);
-    let destination = cursor.read_u8().unwrap();
-    
-    let get_character_name_ptr = external_fn!(EXE_BASE + 0x58F90, extern "win64" fn(i32) -> *const u8);
+    let register_flags = RegisterFlags::read(&mut cursor)?;
+    let destination = cursor.read_u8()?;
     
     // not ideal, but we're doing our own strlen style comparison here because none of the rust library functions quite match our use-case. if we have more string stuff then this should really be factored out into a separate function, but for now this is the only instance of this in the code
     let id = variable_character.get_char_id();
     
-    let name_ptr = get_character_name_ptr(id) as usize;
+    let name_ptr = crate::game_memory::get_character_name_ptr(id);
     
     let expected_name = GStr::from_cursor(&mut cursor, 64);
     let actual_name = GStr::from_ptr(name_ptr, 64);
     
-    let is_match = expected_name.map_or(
-            false,
+    let is_match = expected_name.is_some_and(
             |expected_name| expected_name.eq_ignore_ascii_case(&actual_name)
         );
     
@@ -977,26 +1792,80 @@ fn check_character_name(storage_character : Char, command_ptr : usize)
             storage_character.set_condition_register(result);
         }
     );
+    
+    Ok(())
+}
+
+fn check_motion(storage_character : Char, command_ptr : usize) -> CommandResult
+{
+    let mut cursor = unsafe { get_cursor(command_ptr, const { size_of::<u32>() * 2 }) };
+    
+    let motion : Option<Motion> = num::FromPrimitive::from_u8(cursor.read_u8()?);
+    let character_relation = CharacterRelation::decode(cursor.read_u8()?);
+    let register_flags = RegisterFlags::read(&mut cursor)?;
+    let destination = cursor.read_u8()?;
+    let window = cursor.read_u8()? as usize;
+    let charge = cursor.read_u8()? as usize;
+    
+    let Some(motion) = motion else {
+        return Ok(());
+    };
+    
+    let variable_character = {
+        match storage_character.related_character(character_relation) {
+            Some(variable_character) => variable_character,
+            // just early out if we def cant figure out what character we're doing this to
+            None => return Ok(()),
+        }
+    };
+    
+    // separately from the write below, since storage::with can't be nested and this might be another character
+    let is_match = storage::with_no_make(
+        variable_character.get_ptr(),
+        |store| store.input_history.matches(motion, window, charge)
+    ).unwrap_or(false);
+    
+    let result = is_match.from_bool();
+    
+    storage::with(
+        storage_character.get_ptr(),
+        |store| {
+            use crate::math::Number;
+            
+            let destination = store.resolve_indirect_register(destination, register_flags.is_destination_indirect());
+            
+            if register_flags.is_destination_bool()
+            {
+                store.set_bool(destination, is_match);
+            } else {
+                store.set_number_register(destination, Number::I32(result));
+            };
+            
+            storage_character.set_condition_register(result);
+        }
+    );
+    
+    Ok(())
 }
 
-fn get_projectile(storage_character : Char, command_ptr : usize)
+fn get_projectile(storage_character : Char, command_ptr : usize) -> CommandResult
 {
     let mut cursor = unsafe { get_cursor(command_ptr, const { size_of::<u32>() * 2 }) };
     
-    cursor.seek(SeekFrom::Current(1)).unwrap();
+    cursor.seek(SeekFrom::Current(1))?;
     
-    let character_relation = CharacterRelation::decode(cursor.read_u8().unwrap());
+    let character_relation = CharacterRelation::decode(cursor.read_u8()?);
     
     
-    cursor.seek(SeekFrom::Current(2)).unwrap();
+    cursor.seek(SeekFrom::Current(2))?;
     
-    let filter_flags = ProjectileFilterFlags::read(&mut cursor);
+    let filter_flags = ProjectileFilterFlags::read(&mut cursor)?;
     
     let variable_character = {
         match storage_character.related_character(character_relation) {
             Some(variable_character) => variable_character,
             // just early out if we def cant figure out what character we're doing this to
-            None => return,
+            None => return Ok(()),
         }
     };
     
@@ -1030,10 +1899,12 @@ fn get_projectile(storage_character : Char, command_ptr : usize)
     );
     
     storage_character.set_condition_register(result);
+    
+    Ok(())
 }
 
 
-fn get_projectile_filtered_by_operation(storage_character : Char, command_ptr : usize)
+fn get_projectile_filtered_by_operation(storage_character : Char, command_ptr : usize) -> CommandResult
 {
     let mut cursor = unsafe { get_cursor(command_ptr, const { size_of::<u32>() * 5 }) };
     
@@ -1042,27 +1913,27 @@ fn get_projectile_filtered_by_operation(storage_character : Char, command_ptr :
             |store| {
                 store.cursor_read_u32_with_replacement(&mut cursor)
             }
-        );
+        )?;
     let operation : Option<BinaryOp> = num::FromPrimitive::from_u32(operation);
     
-    cursor.seek(SeekFrom::Current(1)).unwrap();
+    cursor.seek(SeekFrom::Current(1))?;
     
-    let character_relation = CharacterRelation::decode(cursor.read_u8().unwrap());
+    let character_relation = CharacterRelation::decode(cursor.read_u8()?);
     
     
-    cursor.seek(SeekFrom::Current(2)).unwrap();
+    cursor.seek(SeekFrom::Current(2))?;
     
-    let filter_flags = ProjectileFilterFlags::read(&mut cursor);
+    let filter_flags = ProjectileFilterFlags::read(&mut cursor)?;
     
     let variable_character = {
         match storage_character.related_character(character_relation) {
             Some(variable_character) => variable_character,
             // just early out if we def cant figure out what character we're doing this to
-            None => return,
+            None => return Ok(()),
         }
     };
     
-    let var = cursor.read_u32::<LittleEndian>().unwrap();
+    let var = cursor.read_u32::<LittleEndian>()?;
     
     let variable_type = var_rw::MatchState::get_number_type(var);
     
@@ -1074,12 +1945,12 @@ fn get_projectile_filtered_by_operation(storage_character : Char, command_ptr :
                 |store| {
                     store.cursor_read_f32_with_replacement(&mut cursor)
                 }
-            ))
+            )?)
         },
         Some(RegisterType::I32 | RegisterType::Bool) => {
-            Number::I32(cursor.read_i32::<LittleEndian>().unwrap())
+            Number::I32(cursor.read_i32::<LittleEndian>()?)
         },
-        None => {return;},
+        None => {return Ok(());},
     };
     
     let op_filter = match operation {
@@ -1090,7 +1961,7 @@ fn get_projectile_filtered_by_operation(storage_character : Char, command_ptr :
                     immediate
                 )
         },
-        None => {return;},
+        None => {return Ok(());},
     };
     
     // note that we get the projectiles of variable_character
@@ -1128,21 +1999,23 @@ fn get_projectile_filtered_by_operation(storage_character : Char, command_ptr :
     };
     
     storage_character.set_condition_register(result);
+    
+    Ok(())
 }
 
 
-fn get_projectile_filtered_by_filename(storage_character : Char, command_ptr : usize)
+fn get_projectile_filtered_by_filename(storage_character : Char, command_ptr : usize) -> CommandResult
 {
     let mut cursor = unsafe { get_cursor(command_ptr, const { size_of::<u32>() * 2  + size_of::<u8>() * 64 }) };
     
-    cursor.seek(SeekFrom::Current(1)).unwrap();
+    cursor.seek(SeekFrom::Current(1))?;
     
-    let character_relation = CharacterRelation::decode(cursor.read_u8().unwrap());
+    let character_relation = CharacterRelation::decode(cursor.read_u8()?);
     
     
-    cursor.seek(SeekFrom::Current(2)).unwrap();
+    cursor.seek(SeekFrom::Current(2))?;
     
-    let filter_flags = ProjectileFilterFlags::read(&mut cursor);
+    let filter_flags = ProjectileFilterFlags::read(&mut cursor)?;
     
     let expected_name = GStr::from_cursor(&mut cursor, 64);
     
@@ -1152,7 +2025,7 @@ fn get_projectile_filtered_by_filename(storage_character : Char, command_ptr : u
         match storage_character.related_character(character_relation) {
             Some(variable_character) => variable_character,
             // just early out if we def cant figure out what character we're doing this to
-            None => return,
+            None => return Ok(()),
         }
     };
     
@@ -1193,10 +2066,12 @@ fn get_projectile_filtered_by_filename(storage_character : Char, command_ptr : u
     };
     
     storage_character.set_condition_register(result);
+    
+    Ok(())
 }
 
 
-fn next_projectile(storage_character : Char, _command_ptr : usize)
+fn next_projectile(storage_character : Char, _command_ptr : usize) -> CommandResult
 {
     let projectile_filter = storage::with(
         storage_character.get_ptr(),
@@ -1238,4 +2113,7 @@ fn next_projectile(storage_character : Char, _command_ptr : usize)
     };
     
     storage_character.set_condition_register(result);
+    
+    Ok(())
 }
+
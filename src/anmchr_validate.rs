@@ -0,0 +1,624 @@
+//! Static checks for a stream of anmchr commands, without running anything.
+//!
+//! `handle_ano_command` quietly does nothing when it's handed something it doesn't understand, so these are the
+//! mistakes that otherwise only show up in-game as "my command didn't do anything".
+
+#![deny(unsafe_op_in_unsafe_fn)]
+
+use std::fmt;
+
+use crate::anmchr_asm::{self, Reader};
+use crate::anmchr_commands::ANO_COMMAND_GROUP;
+use crate::anmchr_commands::AnoCmd;
+use crate::command_flow::BranchTest;
+use crate::binary_operators::BinaryOp;
+use crate::unary_operators::UnaryOp;
+use crate::ternary_operators::TernaryOp;
+use crate::vector::{VectorOp, PositionSource};
+use crate::game_data::CharacterRelation;
+use crate::storage::{CharStore, RegisterType, RegisterFlags, SharedBankSelect, Persistence};
+use crate::character_extensions::{ForceFlags, Falloff, FORCE_SLOTS};
+use crate::motion::{Motion, DEFAULT_CHARGE, INPUT_HISTORY_LEN};
+use crate::var_rw;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    /// the command won't do what was written, or won't do anything at all
+    Error,
+    /// the command works, but a value gets converted between int and float along the way
+    Warning,
+}
+
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    /// byte offset of the command's header within the stream
+    pub offset : usize,
+    pub severity : Severity,
+    pub msg : String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        
+        write!(f, "{:#06X}: {}: {}", self.offset, severity, self.msg)
+    }
+}
+
+/// check every command in `bytes`. game commands (groups 0, 1 and 3) are skipped over, anything else that we
+/// can't find the end of stops the check.
+pub fn validate(bytes : &[u8]) -> Vec<Diagnostic>
+{
+    let mut diagnostics = Vec::new();
+    let mut offset = 0;
+    let mut command_index = 0;
+    
+    while offset < bytes.len() {
+        let remaining = &bytes[offset..];
+        let mut reader = Reader::new(remaining);
+        
+        let mut check = Checker {
+            offset,
+            command_index,
+            name : String::new(),
+            diagnostics : &mut diagnostics,
+        };
+        
+        let (group, command) = match (reader.u32(), reader.u32()) {
+            (Some(group), Some(command)) => (group, command),
+            _ => {
+                check.report(Severity::Error, format!("{} trailing bytes that aren't a whole command", remaining.len()));
+                break;
+            },
+        };
+        
+        if group == ANO_COMMAND_GROUP {
+            check.name = format!("66_{:02X}", command);
+            
+            let command : Option<AnoCmd> = num::FromPrimitive::from_u32(command);
+            
+            let command = match command {
+                Some(command) => command,
+                None => {
+                    check.report(Severity::Error, String::from("unknown command, can't check anything after it"));
+                    break;
+                },
+            };
+            
+            match check.command(command, &mut reader) {
+                Some(()) => offset += reader.position,
+                None => {
+                    check.report(Severity::Error, String::from("arguments are cut off"));
+                    break;
+                },
+            }
+            
+            // skips and jumps only count 66 commands
+            command_index += 1;
+        } else {
+            check.name = format!("{:X}_{:02X}", group, command);
+            
+            match anmchr_asm::game_command_len(remaining) {
+                Some(len) if len <= remaining.len() => offset += len,
+                Some(_) => {
+                    check.report(Severity::Error, String::from("arguments are cut off"));
+                    break;
+                },
+                None => {
+                    check.report(Severity::Warning, String::from("can't tell where this command ends, can't check anything after it"));
+                    break;
+                },
+            }
+        }
+    }
+    
+    diagnostics
+}
+
+/// the register type an operand will have at runtime, None if it's indirect and so can't be known ahead of time
+fn operand_type(index : u8, is_bool : bool, is_indirect : bool) -> Option<RegisterType>
+{
+    if is_bool {
+        Some(RegisterType::Bool)
+    } else if is_indirect {
+        None
+    } else {
+        Some(RegisterType::identify(index))
+    }
+}
+
+fn type_name(register_type : RegisterType) -> &'static str
+{
+    match register_type {
+        RegisterType::F32 => "float",
+        RegisterType::I32 => "integer",
+        RegisterType::Bool => "boolean",
+    }
+}
+
+fn is_replacement(bits : u32) -> bool
+{
+    (bits & CharStore::F32_RELOAD_MASK) == CharStore::F32_RELOAD_MASK
+}
+
+struct Checker<'a> {
+    offset : usize,
+    /// how many 66 commands came before this one
+    command_index : usize,
+    name : String,
+    diagnostics : &'a mut Vec<Diagnostic>,
+}
+
+impl Checker<'_> {
+    fn report(&mut self, severity : Severity, msg : String)
+    {
+        self.diagnostics.push(Diagnostic {
+            offset : self.offset,
+            severity,
+            msg : format!("{}: {}", self.name, msg),
+        });
+    }
+    
+    fn binary_op(&mut self, op : u32)
+    {
+        let known : Option<BinaryOp> = num::FromPrimitive::from_u32(op);
+        
+        // replaced operations are only known at runtime
+        if known.is_none() && !is_replacement(op) {
+            self.report(Severity::Error, format!("unknown binary operation {:#X}", op));
+        }
+    }
+    
+    fn unary_op(&mut self, op : u32)
+    {
+        let known : Option<UnaryOp> = num::FromPrimitive::from_u32(op);
+        
+        if known.is_none() && !is_replacement(op) {
+            self.report(Severity::Error, format!("unknown unary operation {:#X}", op));
+        }
+    }
+    
+    fn ternary_op(&mut self, op : u32)
+    {
+        let known : Option<TernaryOp> = num::FromPrimitive::from_u32(op);
+        
+        if known.is_none() && !is_replacement(op) {
+            self.report(Severity::Error, format!("unknown ternary operation {:#X}", op));
+        }
+    }
+    
+    fn force_slot(&mut self, slot : u8)
+    {
+        if slot as usize >= FORCE_SLOTS {
+            self.report(Severity::Error, format!("force slot {} doesn't exist, they go from 0 to {}", slot, FORCE_SLOTS - 1));
+        }
+    }
+    
+    fn relation(&mut self, byte : u8)
+    {
+        if !CharacterRelation::is_recognized(byte) {
+            self.report(Severity::Error, format!("unknown character relation {:#04X}, it will be treated as 00 (me)", byte));
+        }
+    }
+    
+    /// returns the variable's type, if it exists
+    fn var(&mut self, var : u32, is_write : bool) -> Option<RegisterType>
+    {
+        let var_type = var_rw::MatchState::get_number_type(var);
+        
+        match var_type {
+            None => self.report(Severity::Error, format!("unknown variable {:#X}", var)),
+            Some(_) if is_write && var_rw::MatchState::is_read_only(var) => {
+                self.report(Severity::Error, format!("{} can't be written to", var_rw::MatchState::name(var).unwrap_or_default()));
+            },
+            Some(_) => (),
+        }
+        
+        var_type
+    }
+    
+    fn projectile_var(&mut self, var : u32, is_write : bool) -> Option<RegisterType>
+    {
+        let var_type = var_rw::ProjectileState::get_number_type(var);
+        
+        match var_type {
+            None => self.report(Severity::Error, format!("unknown projectile variable {:#X}", var)),
+            Some(_) if is_write && var_rw::ProjectileState::is_read_only(var) => {
+                self.report(Severity::Error, format!("{} can't be written to", var_rw::ProjectileState::name(var).unwrap_or_default()));
+            },
+            Some(_) => (),
+        }
+        
+        var_type
+    }
+    
+    /// warn when a value moves between the int and float banks. booleans are always explicit so they're fine
+    fn mixing(&mut self, from : (&str, Option<RegisterType>), to : (&str, Option<RegisterType>))
+    {
+        if let (Some(from_type @ (RegisterType::F32 | RegisterType::I32)), Some(to_type @ (RegisterType::F32 | RegisterType::I32))) = (from.1, to.1)
+            && from_type != to_type
+        {
+            self.report(Severity::Warning, format!("{} is {} but {} is {}, the value gets converted", from.0, type_name(from_type), to.0, type_name(to_type)));
+        }
+    }
+    
+    fn command(&mut self, command : AnoCmd, reader : &mut Reader) -> Option<()>
+    {
+        match command {
+            AnoCmd::RelativeTeleportX | AnoCmd::RelativeTeleportY => {
+                reader.u32()?;
+            },
+            AnoCmd::SuckX => {
+                reader.u32()?;
+                reader.u32()?;
+            },
+            AnoCmd::SetForce => {
+                let [slot, relation, flags, falloff] = reader.word()?;
+                let x = reader.u32()?;
+                let y = reader.u32()?;
+                reader.u32()?;
+                reader.u32()?;
+                
+                let flags = ForceFlags::new(flags);
+                
+                self.force_slot(slot);
+                self.relation(relation);
+                
+                let known : Option<Falloff> = num::FromPrimitive::from_u8(falloff);
+                if known.is_none() {
+                    self.report(Severity::Error, format!("unknown falloff {:#X}, it should be 0 for linear or 1 for exponential", falloff));
+                }
+                
+                if flags.raw() & !ForceFlags::KNOWN != 0 {
+                    self.report(Severity::Error, format!("unknown force flags {:#04X}", flags.raw() & !ForceFlags::KNOWN));
+                }
+                
+                if !flags.is_character_affected() && !flags.is_projectiles_affected() {
+                    self.report(Severity::Error, String::from("affects neither the character nor their projectiles, so it doesn't do anything"));
+                }
+                
+                if flags.is_radial() && f32::from_bits(y) != 0.0 && !is_replacement(y) {
+                    self.report(Severity::Warning, String::from("radial forces only use x, y is ignored"));
+                }
+                
+                if f32::from_bits(x) == 0.0 && (flags.is_radial() || f32::from_bits(y) == 0.0) {
+                    self.report(Severity::Warning, String::from("has no strength, so it just clears the slot"));
+                }
+            },
+            AnoCmd::ClearForce => {
+                let [slot, _, _, _] = reader.word()?;
+                
+                if slot != u8::MAX {
+                    self.force_slot(slot);
+                }
+            },
+            AnoCmd::LoadVectorFromPosition | AnoCmd::StoreVectorToPosition => {
+                let [_, relation, _, _] = reader.word()?;
+                let source = reader.u32()?;
+                
+                self.relation(relation);
+                
+                let known : Option<PositionSource> = num::FromPrimitive::from_u32(source);
+                if known.is_none() {
+                    self.report(Severity::Error, format!("unknown position source {:#X}, it should be 0 for the character or 1 for its projectile", source));
+                }
+            },
+            AnoCmd::DirectionToCharacter => {
+                let [_, relation, _, _] = reader.word()?;
+                
+                self.relation(relation);
+            },
+            AnoCmd::LoadSharedRegister | AnoCmd::StoreSharedRegister => {
+                let [bank, shared, flags, register] = reader.word()?;
+                
+                let flags = RegisterFlags::new(flags);
+                
+                let known : Option<SharedBankSelect> = num::FromPrimitive::from_u8(bank);
+                if known.is_none() {
+                    self.report(Severity::Error, format!("unknown shared bank {:#X}, it should be 0 for my team, 1 for the opposing team or 2 for the match", bank));
+                }
+                
+                // the flags are used the other way around when storing
+                let (shared, register) = match command {
+                    AnoCmd::LoadSharedRegister => (
+                        operand_type(shared, flags.is_lhs_bool(), flags.is_lhs_indirect()),
+                        operand_type(register, flags.is_destination_bool(), flags.is_destination_indirect())),
+                    _ => (
+                        operand_type(shared, flags.is_destination_bool(), flags.is_destination_indirect()),
+                        operand_type(register, flags.is_lhs_bool(), flags.is_lhs_indirect())),
+                };
+                
+                self.mixing(("shared register", shared), ("register", register));
+            },
+            AnoCmd::SaveSnapshot | AnoCmd::LoadSnapshot => {
+                reader.string()?;
+            },
+            AnoCmd::SetRegisterPersistence => {
+                let [_, _, _, persistence] = reader.word()?;
+                
+                let known : Option<Persistence> = num::FromPrimitive::from_u8(persistence);
+                if known.is_none() {
+                    self.report(Severity::Error, format!("unknown persistence {:#X}, it should be 0 to reset on round, 1 to reset on match or 2 to keep until unloaded", persistence));
+                }
+            },
+            AnoCmd::VectorOperation => {
+                let op = reader.u32()?;
+                reader.word()?;
+                reader.u32()?;
+                
+                let known : Option<VectorOp> = num::FromPrimitive::from_u32(op);
+                if known.is_none() && !is_replacement(op) {
+                    self.report(Severity::Error, format!("unknown vector operation {:#X}", op));
+                }
+            },
+            AnoCmd::LoadImmediateIntoRegister => {
+                reader.word()?;
+                reader.u32()?;
+            },
+            AnoCmd::BinaryOperationRegisterImmediate => {
+                let op = reader.u32()?;
+                let [lhs, _, flags, destination] = reader.word()?;
+                reader.u32()?;
+                
+                let flags = RegisterFlags::new(flags);
+                
+                self.binary_op(op);
+                self.mixing(
+                    ("left hand side", operand_type(lhs, flags.is_lhs_bool(), flags.is_lhs_indirect())),
+                    ("destination", operand_type(destination, flags.is_destination_bool(), flags.is_destination_indirect())));
+            },
+            AnoCmd::BinaryOperationRegisterRegister => {
+                let op = reader.u32()?;
+                let [lhs, rhs, flags, destination] = reader.word()?;
+                
+                let flags = RegisterFlags::new(flags);
+                let lhs = operand_type(lhs, flags.is_lhs_bool(), flags.is_lhs_indirect());
+                let rhs = operand_type(rhs, flags.is_rhs_bool(), flags.is_rhs_indirect());
+                let destination = operand_type(destination, flags.is_destination_bool(), flags.is_destination_indirect());
+                
+                self.binary_op(op);
+                self.mixing(("left hand side", lhs), ("right hand side", rhs));
+                self.mixing(("left hand side", lhs), ("destination", destination));
+            },
+            AnoCmd::TernaryOperationRegisters => {
+                let op = reader.u32()?;
+                let [_, second, flags, destination] = reader.word()?;
+                let [third, _, _, _] = reader.word()?;
+                
+                let flags = RegisterFlags::new(flags);
+                let second = operand_type(second, flags.is_rhs_bool(), flags.is_rhs_indirect());
+                let third = operand_type(third, flags.is_third_bool(), flags.is_third_indirect());
+                let destination = operand_type(destination, flags.is_destination_bool(), flags.is_destination_indirect());
+                
+                // the first operand is Select's condition, so it's not checked against the others
+                self.ternary_op(op);
+                self.mixing(("second operand", second), ("third operand", third));
+                self.mixing(("second operand", second), ("destination", destination));
+            },
+            AnoCmd::TernaryOperationRegisterImmediate => {
+                let op = reader.u32()?;
+                reader.word()?;
+                reader.u32()?;
+                reader.u32()?;
+                
+                self.ternary_op(op);
+            },
+            AnoCmd::UnaryOperationRegister => {
+                let op = reader.u32()?;
+                let [source, _, flags, destination] = reader.word()?;
+                
+                let flags = RegisterFlags::new(flags);
+                
+                self.unary_op(op);
+                self.mixing(
+                    ("source", operand_type(source, flags.is_lhs_bool(), flags.is_lhs_indirect())),
+                    ("destination", operand_type(destination, flags.is_destination_bool(), flags.is_destination_indirect())));
+            },
+            AnoCmd::UnaryOperationImmediate => {
+                let op = reader.u32()?;
+                reader.word()?;
+                reader.u32()?;
+                
+                self.unary_op(op);
+            },
+            AnoCmd::LoadVarIntoRegister | AnoCmd::LoadProjectileVarIntoRegister => {
+                let [_, relation, flags, destination] = reader.word()?;
+                let var = reader.u32()?;
+                
+                let flags = RegisterFlags::new(flags);
+                
+                self.relation(relation);
+                
+                let var_type = match command {
+                    AnoCmd::LoadVarIntoRegister => self.var(var, false),
+                    _ => self.projectile_var(var, false),
+                };
+                
+                self.mixing(("variable", var_type), ("destination", operand_type(destination, flags.is_destination_bool(), flags.is_destination_indirect())));
+            },
+            AnoCmd::StoreVarFromRegister | AnoCmd::StoreProjectileVarFromRegister => {
+                let [_, relation, flags, source] = reader.word()?;
+                let var = reader.u32()?;
+                
+                let flags = RegisterFlags::new(flags);
+                
+                self.relation(relation);
+                
+                let var_type = match command {
+                    AnoCmd::StoreVarFromRegister => self.var(var, true),
+                    _ => self.projectile_var(var, true),
+                };
+                
+                self.mixing(("source", operand_type(source, flags.is_lhs_bool(), flags.is_lhs_indirect())), ("variable", var_type));
+            },
+            AnoCmd::StoreVarFromImmediate => {
+                let [_, relation, _, _] = reader.word()?;
+                let var = reader.u32()?;
+                
+                self.relation(relation);
+                self.var(var, true);
+                
+                // an unknown variable doesn't read its immediate, but the immediate is still there
+                reader.u32()?;
+            },
+            AnoCmd::BinaryOperationVarRegister => {
+                let op = reader.u32()?;
+                let [rhs, relation, flags, _] = reader.word()?;
+                let var = reader.u32()?;
+                
+                let flags = RegisterFlags::new(flags);
+                
+                self.binary_op(op);
+                self.relation(relation);
+                
+                let var_type = self.var(var, true);
+                
+                self.mixing(("right hand side", operand_type(rhs, flags.is_rhs_bool(), flags.is_rhs_indirect())), ("variable", var_type));
+            },
+            AnoCmd::BinaryOperationVarImmediate => {
+                let op = reader.u32()?;
+                let [_, relation, _, _] = reader.word()?;
+                let var = reader.u32()?;
+                reader.u32()?;
+                
+                self.binary_op(op);
+                self.relation(relation);
+                self.var(var, true);
+            },
+            AnoCmd::UnaryOperationVar => {
+                let op = reader.u32()?;
+                let [_, relation, _, _] = reader.word()?;
+                let var = reader.u32()?;
+                
+                self.unary_op(op);
+                self.relation(relation);
+                self.var(var, true);
+            },
+            AnoCmd::CheckCharacterName => {
+                let [_, relation, _, _] = reader.word()?;
+                reader.string()?;
+                
+                self.relation(relation);
+            },
+            AnoCmd::CheckMotion => {
+                let [motion_byte, relation, _, _] = reader.word()?;
+                let [window, charge, _, _] = reader.word()?;
+                
+                self.relation(relation);
+                
+                let motion : Option<Motion> = num::FromPrimitive::from_u8(motion_byte);
+                
+                match motion {
+                    None => self.report(Severity::Error, format!("unknown motion {:#04X}, so nothing gets written", motion_byte)),
+                    Some(motion) => {
+                        let window = if window == 0 { motion.default_window() } else { window as usize };
+                        let charge = if charge == 0 { DEFAULT_CHARGE } else { charge as usize };
+                        
+                        // the release can be anywhere in the window, and the charge can end up to a window before that
+                        let needed = if motion.is_charge() { window * 2 + charge } else { window };
+                        
+                        if needed > INPUT_HISTORY_LEN {
+                            self.report(Severity::Warning, format!("`{}` can look back {} frames, but only the last {} are kept", motion.name(), needed, INPUT_HISTORY_LEN));
+                        }
+                    },
+                }
+            },
+            AnoCmd::ConditionalBinaryOperation => {
+                let comparison = reader.u32()?;
+                let result = reader.u32()?;
+                let [lhs, _, flags, destination] = reader.word()?;
+                reader.u32()?;
+                reader.u32()?;
+                reader.u32()?;
+                
+                let flags = RegisterFlags::new(flags);
+                
+                self.binary_op(comparison);
+                self.binary_op(result);
+                self.mixing(
+                    ("left hand side", operand_type(lhs, flags.is_lhs_bool(), flags.is_lhs_indirect())),
+                    ("destination", operand_type(destination, flags.is_destination_bool(), flags.is_destination_indirect())));
+            },
+            AnoCmd::SkipIf | AnoCmd::JumpIf => {
+                let [_, test, _, _] = reader.word()?;
+                let amount = reader.u32()? as i32;
+                
+                let known : Option<BranchTest> = num::FromPrimitive::from_u8(test);
+                
+                if known.is_none() {
+                    self.report(Severity::Error, format!("unknown test {:#04X}, this never skips or jumps", test));
+                }
+                
+                match command {
+                    AnoCmd::SkipIf if amount < 0 => {
+                        self.report(Severity::Error, String::from("negative skip count skips everything left this frame, use 66_21 to jump backwards"));
+                    },
+                    AnoCmd::JumpIf if amount < 0 && amount.unsigned_abs() as usize > self.command_index + 1 => {
+                        self.report(Severity::Error, format!("jumps back {} commands, past the start of the stream", amount.unsigned_abs()));
+                    },
+                    _ => (),
+                }
+            },
+            AnoCmd::ScheduleNextCommand => {
+                let delay = reader.u32()?;
+                let interval = reader.u32()?;
+                reader.u32()?;
+                
+                for (name, frames) in [("delay", delay), ("repeat interval", interval)] {
+                    if (frames as i32) < 0 && !is_replacement(frames) {
+                        self.report(Severity::Error, format!("{} {} is negative, it counts as 0", name, frames as i32));
+                    }
+                }
+                
+                let mut next = Reader::new(reader.rest());
+                
+                match (next.u32(), next.u32()) {
+                    (Some(ANO_COMMAND_GROUP), Some(next_command)) if crate::schedule::is_schedulable(next_command) => (),
+                    (Some(_), Some(_)) => {
+                        self.report(Severity::Error, String::from("only register and var operations (66_10 to 66_1E) can be scheduled, the next command runs right away instead"));
+                    },
+                    _ => {
+                        self.report(Severity::Error, String::from("there's no command after it to schedule"));
+                    },
+                }
+            },
+            AnoCmd::CancelScheduled => {
+                reader.u32()?;
+            },
+            AnoCmd::GetProjectile => {
+                let [_, relation, _, _] = reader.word()?;
+                reader.u32()?;
+                
+                self.relation(relation);
+            },
+            AnoCmd::NextProjectile => (),
+            AnoCmd::GetProjectileFilteredByOperation => {
+                let op = reader.u32()?;
+                let [_, relation, _, _] = reader.word()?;
+                reader.u32()?;
+                let var = reader.u32()?;
+                reader.u32()?;
+                
+                self.binary_op(op);
+                self.relation(relation);
+                
+                if self.projectile_var(var, false).is_some() && var_rw::MatchState::get_number_type(var).is_none() {
+                    // the filter picks the immediate's type from the character variable list
+                    self.report(Severity::Error, format!("projectile variable {:#X} can't be used as a filter", var));
+                }
+            },
+            AnoCmd::GetProjectileFilteredByFilename => {
+                let [_, relation, _, _] = reader.word()?;
+                reader.u32()?;
+                reader.string()?;
+                
+                self.relation(relation);
+            },
+        }
+        
+        Some(())
+    }
+}
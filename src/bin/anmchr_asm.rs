@@ -0,0 +1,95 @@
+//! host-side tool for writing 0x66 anmchr commands as text. see src/anmchr_asm.rs for the syntax. nothing in it needs
+//! windows or the game, so it builds on anything (see Cargo.toml).
+//!
+//! anmchr_asm asm <input.txt> [-o <output.bin>]
+//!     assembles text. without -o the bytes are printed as hex, 4 bytes per line
+//! anmchr_asm disasm <input> [--hex]
+//!     disassembles a binary dump, or hex text with --hex
+//! anmchr_asm check <input> [--hex]
+//!     reports anything in a binary dump (or hex text) that mag_patch would quietly ignore
+
+use std::process::ExitCode;
+
+use mag_patch::{anmchr_asm, anmchr_validate};
+
+const USAGE : &str = "usage:
+    anmchr_asm asm <input.txt> [-o <output.bin>]
+    anmchr_asm disasm <input> [--hex]
+    anmchr_asm check <input> [--hex]";
+
+fn main() -> ExitCode
+{
+    let args : Vec<String> = std::env::args().skip(1).collect();
+    
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(msg) => {
+            eprintln!("{}", msg);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(args : &[String]) -> Result<(), String>
+{
+    let (mode, input, options) = match args {
+        [mode, input, options @ ..] => (mode.as_str(), input, options),
+        _ => return Err(String::from(USAGE)),
+    };
+    
+    match (mode, options) {
+        ("asm", []) | ("asm", [_, _]) => {
+            let source = std::fs::read_to_string(input).map_err(|e| format!("couldn't read {}: {}", input, e))?;
+            let bytes = anmchr_asm::assemble(&source).map_err(|e| format!("{}: {}", input, e))?;
+            
+            for diagnostic in anmchr_validate::validate(&bytes) {
+                eprintln!("{}: {}", input, diagnostic);
+            }
+            
+            match options {
+                [flag, output] if flag == "-o" => {
+                    std::fs::write(output, bytes).map_err(|e| format!("couldn't write {}: {}", output, e))?;
+                },
+                [] => println!("{}", anmchr_asm::to_hex_string(&bytes)),
+                _ => return Err(String::from(USAGE)),
+            }
+        },
+        ("disasm", []) | ("check", []) => {
+            let bytes = std::fs::read(input).map_err(|e| format!("couldn't read {}: {}", input, e))?;
+            
+            return inspect(mode, input, &bytes);
+        },
+        ("disasm", [flag]) | ("check", [flag]) if flag == "--hex" => {
+            let text = std::fs::read_to_string(input).map_err(|e| format!("couldn't read {}: {}", input, e))?;
+            let bytes = anmchr_asm::to_bytes(&text).map_err(|e| format!("{}: {}", input, e))?;
+            
+            return inspect(mode, input, &bytes);
+        },
+        _ => return Err(String::from(USAGE)),
+    }
+    
+    Ok(())
+}
+
+fn inspect(mode : &str, input : &str, bytes : &[u8]) -> Result<(), String>
+{
+    if mode == "disasm" {
+        print!("{}", anmchr_asm::disassemble(bytes));
+        
+        return Ok(());
+    }
+    
+    let diagnostics = anmchr_validate::validate(bytes);
+    
+    for diagnostic in &diagnostics {
+        println!("{}: {}", input, diagnostic);
+    }
+    
+    let errors = diagnostics.iter().filter(|d| d.severity == anmchr_validate::Severity::Error).count();
+    
+    if errors == 0 {
+        Ok(())
+    } else {
+        Err(format!("{}: {} error(s)", input, errors))
+    }
+}
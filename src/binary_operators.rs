@@ -24,6 +24,30 @@ macro_rules! binary_operators {
             )+
         }
         
+        impl BinaryOp {
+            /// the name of the operation, as written in the list above
+            pub fn name(self) -> &'static str
+            {
+                match self {
+                    $(
+                    BinaryOp::$name => stringify!($name),
+                    )+
+                }
+            }
+            
+            /// look up an operation by name, ignoring case
+            pub fn from_name(name : &str) -> Option<Self>
+            {
+                $(
+                if name.eq_ignore_ascii_case(stringify!($name)) {
+                    return Some(BinaryOp::$name);
+                }
+                )+
+                
+                None
+            }
+        }
+        
         fn handle_binary_operation_f32_f32_f32(lhs : f32, rhs : f32, op : BinaryOp) -> f32
         {
             let func = match op {
@@ -331,10 +355,7 @@ binary_operators! {
         |lhs : i32, rhs : i32| {
             let result = lhs.abs_diff(rhs).try_into();
             
-            match result {
-                Ok(result) => result,
-                Err(_) => i32::MAX,
-            }
+            result.unwrap_or(i32::MAX)
         },
     ),
     (
@@ -355,6 +376,62 @@ binary_operators! {
     ),
     
     
+    (
+        /// approximate power, left hand side to the power of the right hand side. so 2 and 10 gives 1024.
+        /// whole number powers up to 64 are done by multiplying over and over, so small ones like 3 squared come out exact.
+        /// a negative number to a fraction power works like SqrtWithNegative, so -8.0 to the 0.5 gives -(8.0 to the 0.5).
+        /// 0 to a negative power gives 0, like division by 0. answers too big for a float give the biggest float instead.
+        /// for integers, negative powers give 0 since the fraction is chopped off (except 1 and -1). answers too big saturate to the integer limits, keeping their sign
+        0x30, Pow,
+        |lhs : f32, rhs : f32| {
+            saturate_float(approx_pow(lhs, rhs))
+        },
+        |lhs : i32, rhs : i32| {
+            if rhs >= 0 {
+                lhs.saturating_pow(rhs as u32)
+            } else {
+                match lhs {
+                    1 => 1,
+                    -1 if rhs & 1 == 1 => -1,
+                    -1 => 1,
+                    _ => 0,
+                }
+            }
+        },
+    ),
+    (
+        /// approximate angle of the point (right hand side, left hand side), so left hand side is y and right hand side is x, like atan2 everywhere else.
+        /// for example, aiming at the opponent is atan2 of (opponent y - my y, opponent x - my x).
+        /// for floats, the angle is in radians from -pi to pi. 0, 0 gives 0.
+        /// for integers, the angle is in the same units as Sin and Cos take, rounded to the nearest, from -60 to 60
+        0x31, Atan2,
+        |lhs : f32, rhs : f32| {
+            approx_atan2(lhs, rhs)
+        },
+        |lhs : i32, rhs : i32| {
+            (approx_atan2(lhs as f32, rhs as f32) / DEGREES_TO_RADIANS).round() as i32
+        },
+    ),
+    (
+        /// length of the line from 0, 0 to (left hand side, right hand side), so 3 and 4 gives 5. never negative.
+        /// for integers, the fraction is chopped off, and answers too big saturate to the biggest integer
+        0x32, Hypot,
+        |lhs : f32, rhs : f32| {
+            saturate_float(hypot(lhs, rhs))
+        },
+        |lhs : i32, rhs : i32| {
+            // the squares add up to at most 2^63, which fits in a u64
+            let lhs = lhs.unsigned_abs() as u64;
+            let rhs = rhs.unsigned_abs() as u64;
+            
+            #[allow(clippy::arithmetic_side_effects)]
+            let result = (lhs * lhs + rhs * rhs).isqrt();
+            
+            result.try_into().unwrap_or(i32::MAX)
+        },
+    ),
+    
+    
     
     
     
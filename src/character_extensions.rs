@@ -3,87 +3,360 @@
 
 //! New structs to track new character functionality
 
-use crate::game_data::{Char, Facing};
+use std::io::Cursor;
+
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use num_derive::FromPrimitive;
+
+use crate::game_data::{Char, CharacterRelation};
+use crate::vector::Vec2;
+use crate::checksum::Checksum;
+use crate::snapshot::SnapshotError;
+use crate::bitflag_getset;
 use crate::storage;
 
 
-const SUCK_MAX : f32 = 128.0;
-const SUCK_EPSILON : f32 = 0.001;
+const FORCE_MAX : f32 = 128.0;
+const FORCE_EPSILON : f32 = 0.001;
+
+/// how many forces 66_51 can have going at once per character
+pub const FORCE_SLOTS : usize = 8;
+
+/// 66_50 gets a slot of its own after the others, so it never gets in the way of 66_51's forces
+const SUCK_SLOT : usize = FORCE_SLOTS;
+
+/// 66_50 always pushes the opponent, which is relation 0x80
+const SUCK_RELATION : u8 = 0x80;
 
-pub struct SuckOpponent
+/// the last byte of 66_51's first word, how a force gets weaker over time
+#[derive(FromPrimitive, Clone, Copy, PartialEq, Eq, Debug)]
+#[repr(u8)]
+pub enum Falloff
 {
-    pub delta : f32,
-    pub magnitude : f32,
+    /// gets `decay` weaker every frame, like 66_50
+    Linear = 0,
+    /// gets multiplied by `decay` every frame, so 0.9 loses a tenth of what's left each frame
+    Exponential = 1,
 }
 
-impl SuckOpponent
+impl Falloff
 {
-    pub fn apply_suck(exe_char : Char, magnitude : f32, delta : f32)
+    pub fn name(self) -> &'static str
+    {
+        match self {
+            Falloff::Linear => "linear",
+            Falloff::Exponential => "exp",
+        }
+    }
+    
+    pub fn from_name(name : &str) -> Option<Self>
+    {
+        [Falloff::Linear, Falloff::Exponential].into_iter().find(|falloff| name.eq_ignore_ascii_case(falloff.name()))
+    }
+}
+
+/// the third byte of 66_51's first word
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[repr(transparent)]
+pub struct ForceFlags {
+    raw : u8,
+}
+
+impl ForceFlags {
+    bitflag_getset!(0x01, is_character_affected, set_character_affected);
+    bitflag_getset!(0x02, is_projectiles_affected, set_projectiles_affected);
+    // pushes away from the owner (or pulls toward them) instead of in a fixed direction
+    bitflag_getset!(0x04, is_radial, set_radial);
+    
+    pub const KNOWN : u8 = 0x07;
+    
+    pub fn new(raw : u8) -> Self
+    {
+        Self { raw }
+    }
+    
+    pub fn raw(&self) -> u8
+    {
+        self.raw
+    }
+}
+
+/// something pushing or pulling another character (or their projectiles) around every frame
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Force
+{
+    /// who gets pushed, from the owner's point of view. decoded every frame, so it follows tags and deaths
+    pub relation : u8,
+    pub flags : ForceFlags,
+    pub falloff : Falloff,
+    /// how far it moves things next frame. radial forces only use x, with positive pushing away and negative pulling in
+    pub push : Vec2,
+    pub decay : f32,
+    /// 0 to be the same everywhere, otherwise it fades out to nothing this far away from the owner
+    pub radius : f32,
+}
+
+impl Force
+{
+    /// a fixed push, with x going the way `owner` is facing
+    pub fn facing(owner : &Char, x : f32, y : f32) -> Vec2
+    {
+        Vec2::new(owner.facing_x(x), y)
+    }
+    
+    /// what 66_50 does, pushing the opponent's point character along x
+    fn suck(push : Vec2, delta : f32) -> Self
+    {
+        Self {
+            relation : SUCK_RELATION,
+            flags : ForceFlags::default().set_character_affected(true),
+            falloff : Falloff::Linear,
+            push,
+            decay : delta,
+            radius : 0.0,
+        }
+    }
+    
+    /// gets the force ready for this frame. returns false once it's too weak to do anything
+    fn step(&mut self) -> bool
+    {
+        let strength = self.push.length();
+        
+        if strength < FORCE_EPSILON {
+            return false;
+        }
+        
+        let strength = strength.min(FORCE_MAX);
+        
+        let new_strength = match self.falloff {
+            Falloff::Linear => strength - self.decay,
+            Falloff::Exponential => strength * self.decay,
+        };
+        
+        if new_strength < FORCE_EPSILON {
+            return false;
+        }
+        
+        self.push = self.push.normalize().scale(new_strength);
+        
+        true
+    }
+    
+    /// how far something at `position` gets moved, if at all
+    fn offset_at(&self, origin : Vec2, position : Vec2) -> Option<Vec2>
     {
-        let magnitude = match exe_char.get_facing() {
-            Facing::Left => -magnitude,
-            Facing::Right => magnitude,
+        let difference = position.sub(origin);
+        
+        let scale = if self.radius > 0.0 {
+            let scale = 1.0 - difference.length() / self.radius;
+            
+            if scale <= 0.0 {
+                return None;
+            }
+            
+            scale
+        } else {
+            1.0
         };
         
+        let offset = if self.flags.is_radial() {
+            let away = difference.normalize();
+            
+            // right on top of the owner, so there's no direction to push in
+            if away.is_zero() {
+                return None;
+            }
+            
+            away.scale(self.push.x)
+        } else {
+            self.push
+        };
+        
+        Some(offset.scale(scale))
+    }
+    
+    fn apply(&self, owner : &Char)
+    {
+        let Some(target) = owner.related_character(CharacterRelation::decode(self.relation)) else {
+            return;
+        };
+        
+        let origin = Vec2::new(owner.get_x_pos(), owner.get_y_pos());
+        
+        if self.flags.is_character_affected() {
+            let position = Vec2::new(target.get_x_pos(), target.get_y_pos());
+            
+            if let Some(offset) = self.offset_at(origin, position) {
+                target.set_x_pos(position.x + offset.x);
+                target.set_y_pos(position.y + offset.y);
+            }
+        }
+        
+        if self.flags.is_projectiles_affected() {
+            for projectile in target.get_own_projectiles() {
+                let position = Vec2::new(projectile.get_x_pos(), projectile.get_y_pos());
+                
+                if let Some(offset) = self.offset_at(origin, position) {
+                    projectile.set_x_pos(position.x + offset.x);
+                    projectile.set_y_pos(position.y + offset.y);
+                }
+            }
+        }
+    }
+    
+    fn write_snapshot(&self, out : &mut Vec<u8>)
+    {
+        out.write_u8(self.relation).unwrap();
+        out.write_u8(self.flags.raw).unwrap();
+        out.write_u8(self.falloff as u8).unwrap();
+        out.write_f32::<LittleEndian>(self.push.x).unwrap();
+        out.write_f32::<LittleEndian>(self.push.y).unwrap();
+        out.write_f32::<LittleEndian>(self.decay).unwrap();
+        out.write_f32::<LittleEndian>(self.radius).unwrap();
+    }
+    
+    fn read_snapshot(cursor : &mut Cursor<&[u8]>) -> Result<Self, SnapshotError>
+    {
+        Ok(Self {
+            relation : cursor.read_u8()?,
+            flags : ForceFlags::new(cursor.read_u8()?),
+            falloff : num::FromPrimitive::from_u8(cursor.read_u8()?)
+                .ok_or(SnapshotError::Invalid("force falloff"))?,
+            push : Vec2::new(cursor.read_f32::<LittleEndian>()?, cursor.read_f32::<LittleEndian>()?),
+            decay : cursor.read_f32::<LittleEndian>()?,
+            radius : cursor.read_f32::<LittleEndian>()?,
+        })
+    }
+}
+
+/// all of the forces a character has going
+pub struct Forces
+{
+    slots : [Option<Force>; FORCE_SLOTS + 1],
+}
+
+impl Forces
+{
+    pub fn new() -> Self
+    {
+        Self {
+            slots : [None; FORCE_SLOTS + 1],
+        }
+    }
+    
+    pub fn is_empty(&self) -> bool
+    {
+        self.slots.iter().all(Option::is_none)
+    }
+    
+    /// replaces whatever was in the slot. returns false if there's no such slot
+    pub fn set(&mut self, slot : usize, force : Force) -> bool
+    {
+        if slot >= FORCE_SLOTS {
+            return false;
+        }
+        
+        self.slots[slot] = Some(force);
+        
+        true
+    }
+    
+    /// None clears all of them, including 66_50's
+    pub fn clear(&mut self, slot : Option<usize>)
+    {
+        match slot {
+            Some(slot) => {
+                if let Some(force) = self.slots.get_mut(slot) {
+                    *force = None;
+                }
+            },
+            None => self.slots = [None; FORCE_SLOTS + 1],
+        }
+    }
+    
+    /// 66_50. pushes the opponent's point character along x, slowing down by `delta` each frame
+    pub fn apply_suck(exe_char : Char, magnitude : f32, delta : f32)
+    {
+        let force = Force::suck(Force::facing(&exe_char, magnitude, 0.0), delta);
+        
         storage::with(
             exe_char.get_ptr(),
             |store| {
-                store.suck_opponent = SuckOpponent {
-                    magnitude,
-                    delta,
-                };
+                store.forces.slots[SUCK_SLOT] = Some(force);
             }
         );
     }
     
-    /// called once per character per tick to apply physics to opponent
-    pub fn handle_suck(&mut self, owner : Char)
+    /// called once per character per tick to apply physics to everything the forces push around
+    pub fn handle_forces(&mut self, owner : Char)
     {
-        if self.magnitude.abs() < SUCK_EPSILON {
-            self.magnitude = 0.0;
+        for slot in self.slots.iter_mut() {
+            let Some(force) = slot else {
+                continue;
+            };
             
-            // early out - no need to apply motion
-            return;
+            if force.step() {
+                force.apply(&owner);
+            } else {
+                // early out - no need to apply motion ever again
+                *slot = None;
+            }
         }
-        
-        self.magnitude = 
-        {
-            let mut magnitude = self.magnitude;
-            let delta = self.delta;
+    }
+    
+    pub fn checksum(&self, checksum : &mut Checksum)
+    {
+        for slot in &self.slots {
+            checksum.write_bool(slot.is_some());
             
-            if magnitude > 0.0 {
-                if magnitude > SUCK_MAX {
-                    magnitude = SUCK_MAX;
-                }
-                
-                magnitude -= delta;
-                
-                if magnitude < SUCK_EPSILON {
-                    self.magnitude = 0.0;
-                    // early out - no need to apply motion
-                    return;
-                }
-            } else {
-                if magnitude < -SUCK_MAX {
-                    magnitude = -SUCK_MAX;
-                }
-                
-                magnitude += delta;
-                
-                if magnitude > -SUCK_EPSILON {
-                    self.magnitude = 0.0;
-                    // early out - no need to apply motion
-                    return;
-                }
+            if let Some(force) = slot {
+                checksum.write_u8(force.relation);
+                checksum.write_u8(force.flags.raw);
+                checksum.write_u8(force.falloff as u8);
+                checksum.write_f32(force.push.x);
+                checksum.write_f32(force.push.y);
+                checksum.write_f32(force.decay);
+                checksum.write_f32(force.radius);
             }
+        }
+    }
+    
+    /// a byte for whether each slot has a force, and then the force
+    pub fn write_snapshot(&self, out : &mut Vec<u8>)
+    {
+        for slot in &self.slots {
+            out.write_u8(slot.is_some() as u8).unwrap();
             
-            magnitude
-        };
+            if let Some(force) = slot {
+                force.write_snapshot(out);
+            }
+        }
+    }
+    
+    pub fn read_snapshot(cursor : &mut Cursor<&[u8]>) -> Result<Self, SnapshotError>
+    {
+        let mut forces = Self::new();
         
-        if let Some(op) = owner.get_opponent_point_char() {
-            let destination_x_pos = op.get_x_pos() + self.magnitude;
-            
-            op.set_x_pos(destination_x_pos);
+        for slot in forces.slots.iter_mut() {
+            *slot = match cursor.read_u8()? {
+                0 => None,
+                1 => Some(Force::read_snapshot(cursor)?),
+                _ => return Err(SnapshotError::Invalid("force slot")),
+            };
+        }
+        
+        Ok(forces)
+    }
+    
+    /// version 1 snapshots only had 66_50's magnitude and delta
+    pub fn from_version_1_suck(magnitude : f32, delta : f32) -> Self
+    {
+        let mut forces = Self::new();
+        
+        if magnitude != 0.0 {
+            forces.slots[SUCK_SLOT] = Some(Force::suck(Vec2::new(magnitude, 0.0), delta));
         }
+        
+        forces
     }
 }
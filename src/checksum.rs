@@ -0,0 +1,81 @@
+//! a hash of everything mag_patch keeps track of during a match, so netplay can compare it between machines every
+//! frame and catch desyncs caused by mods.
+//!
+//! it has to come out the same on every machine for the same match, so nothing that depends on where things are in
+//! memory goes into it. characters are put in order by team and where they are in the team's list instead of by
+//! pointer, and pointers (like the projectile list) aren't hashed, only whether they're there.
+
+#![deny(unsafe_op_in_unsafe_fn)]
+
+use crate::storage;
+
+const FNV_OFFSET_BASIS : u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME : u64 = 0x0000_0100_0000_01b3;
+
+/// 64 bit FNV-1a. std's hashers are allowed to change between rust versions (and some are randomly seeded), so we
+/// use our own
+pub struct Checksum
+{
+    state : u64,
+}
+
+impl Checksum
+{
+    pub fn new() -> Self
+    {
+        Self {
+            state : FNV_OFFSET_BASIS,
+        }
+    }
+    
+    pub fn write(&mut self, bytes : &[u8])
+    {
+        for byte in bytes {
+            self.state ^= *byte as u64;
+            self.state = self.state.wrapping_mul(FNV_PRIME);
+        }
+    }
+    
+    pub fn write_u8(&mut self, value : u8)
+    {
+        self.write(&[value]);
+    }
+    
+    pub fn write_i32(&mut self, value : i32)
+    {
+        self.write(&value.to_le_bytes());
+    }
+    
+    /// by the bits, so -0.0 and 0.0 are different
+    pub fn write_f32(&mut self, value : f32)
+    {
+        self.write(&value.to_bits().to_le_bytes());
+    }
+    
+    pub fn write_u64(&mut self, value : u64)
+    {
+        self.write(&value.to_le_bytes());
+    }
+    
+    pub fn write_bool(&mut self, value : bool)
+    {
+        self.write_u8(value as u8);
+    }
+    
+    pub fn finish(&self) -> u64
+    {
+        self.state
+    }
+}
+
+/// every character's storage, the team and match register banks, and the random number generator
+pub fn state_checksum() -> u64
+{
+    let mut checksum = Checksum::new();
+    
+    storage::checksum_all(&mut checksum);
+    
+    checksum.write_i32(crate::random::get_state());
+    
+    checksum.finish()
+}
@@ -0,0 +1,122 @@
+//! what happens when a 66 command can't be run
+//!
+//! custom commands are never supposed to crash the game, even when they're written wrong. so instead of stopping,
+//! a command that can't be run is skipped, and the failure is counted and logged. if mag_patch.ini sets
+//! `error_register`, its error code also goes into that integer register of the character running it, where the
+//! character's own commands can check for it. it's off by default, since characters are free to use every register
+//! for their own things. the code stays there until something else is put in the register, so it can be checked any
+//! time after.
+//!
+//! | Code | Description |
+//! | ---- | ----------- |
+//! | 0 | no error, if the character clears it |
+//! | 2 | the command number after the 66 isn't one mag_patch knows, or it's in another group set aside in mag_patch.ini |
+//! | 3 | something went wrong inside mag_patch while running the command, see mag_patch.log |
+//!
+//! there's no code for a command being cut short. nothing in a command says how long it is, so mag_patch can only
+//! read as much as each command is supposed to have, whatever comes after it.
+
+#![deny(unsafe_op_in_unsafe_fn)]
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use std::sync::Mutex;
+
+use crate::config::LogLevel;
+use crate::game_data::Char;
+use crate::hook_helpers::lock;
+use crate::storage;
+
+#[derive(Debug, PartialEq)]
+pub enum CommandError
+{
+    UnknownCommand(u32),
+    /// a panic, or reading further than mag_patch meant to. either way it's a bug in mag_patch, not the command
+    Failed(String),
+}
+
+pub type CommandResult<T = ()> = Result<T, CommandError>;
+
+impl CommandError
+{
+    /// what goes in the error register, see the table at the top
+    pub fn code(&self) -> i32
+    {
+        match self {
+            CommandError::UnknownCommand(_) => 2,
+            CommandError::Failed(_) => 3,
+        }
+    }
+}
+
+impl std::error::Error for CommandError {}
+
+impl std::fmt::Display for CommandError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            CommandError::UnknownCommand(command) => write!(f, "{:02x} isn't a command in this group", command),
+            CommandError::Failed(msg) => write!(f, "something went wrong inside mag_patch: {}", msg),
+        }
+    }
+}
+
+/// the only io errors reading from a cursor can give are from running off the end of it. every cursor is made as long
+/// as what's supposed to be read from it, so that means mag_patch read the wrong amount
+impl From<std::io::Error> for CommandError {
+    fn from(e : std::io::Error) -> Self {
+        CommandError::Failed(format!("read past what it meant to: {}", e))
+    }
+}
+
+static FAILURES : AtomicU64 = AtomicU64::new(0);
+
+/// from mag_patch.ini, see config.rs
+static ERROR_REGISTER : Mutex<Option<u8>> = Mutex::new(None);
+
+/// which integer register gets the error codes, or None for none of them
+pub fn set_error_register(register : Option<u8>)
+{
+    *lock(&ERROR_REGISTER) = register;
+}
+
+/// how many commands have failed since the dll loaded
+pub fn failure_count() -> u64
+{
+    FAILURES.load(Ordering::Relaxed)
+}
+
+/// counts and logs the failure, and gives `character` the error code if there's a register for it
+pub fn report(character : &Char, group : u32, command : u32, error : &CommandError)
+{
+    let count = FAILURES.fetch_add(1, Ordering::Relaxed) + 1;
+    
+    crate::log!(LogLevel::Warn, Some(character), "{:02x}_{:02x} was skipped, {} (error {}, {} failed so far)",
+        group, command, error, error.code(), count);
+    
+    let Some(register) = *lock(&ERROR_REGISTER) else {
+        return;
+    };
+    
+    storage::with(
+        character.get_ptr(),
+        |store| {
+            store.set_i32_register(register, error.code());
+        }
+    );
+}
+
+/// runs `function`, turning a panic into `CommandError::Failed` so it can't unwind into the game
+pub fn catch<F>(function : F) -> CommandResult
+    where F : FnOnce() -> CommandResult
+{
+    match std::panic::catch_unwind(std::panic::AssertUnwindSafe(function)) {
+        Ok(result) => result,
+        Err(payload) => {
+            let msg = payload.downcast_ref::<&str>().map(|msg| msg.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| String::from("no message"));
+            
+            Err(CommandError::Failed(msg))
+        },
+    }
+}
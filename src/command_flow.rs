@@ -0,0 +1,218 @@
+//! skipping and jumping around inside a stream of anmchr commands (66_20 and 66_21)
+//!
+//! we don't know where the game keeps its own command pointer, so instead of moving it, execute_anmchr_command
+//! steers around the game:
+//! - skipping forward is done by not running the next N 66 commands when the game hands them to us
+//! - jumping backward is done by running the 66 commands we already saw again ourselves, then letting the game carry
+//!   on from the command after the jump like nothing happened
+//!
+//! only 66 commands are counted, skipped or run again. the game's own commands always run once, where they are, so a
+//! skip or a loop can't lose a hitbox or a state change.
+//!
+//! a skip keeps going into the next frames for as long as the character stays in the same anmchr entry. once the
+//! entry changes or starts over, the rest of it is dropped, so it can't eat the commands of some other move.
+//!
+//! a backward jump can only land on a command this character has already run this frame. running commands from an
+//! earlier frame again would run them without the waits in between, which isn't what going back there means
+
+#![deny(unsafe_op_in_unsafe_fn)]
+
+use num_derive::FromPrimitive;
+
+use crate::storage;
+use crate::game_data::Char;
+use crate::hook_helpers::read_ptr;
+use crate::anmchr_commands::ANO_COMMAND_GROUP;
+
+/// how many commands in a row we remember per frame. backward jumps past this don't happen
+const HISTORY_CAPACITY : usize = 256;
+
+/// a backward jump that keeps getting taken is an infinite loop, so give up after this many commands
+const MAX_REPLAYED_COMMANDS : usize = 4096;
+
+/// the second byte of 66_20 and 66_21, what decides if the skip or jump happens
+#[derive(FromPrimitive, Clone, Copy, PartialEq, Debug)]
+#[repr(u8)]
+pub enum BranchTest
+{
+    /// the register is true (not zero)
+    IfTrue = 0,
+    /// the register is false (zero)
+    IfFalse = 1,
+    /// the condition register is true, like 0_08 checks
+    IfCondition = 2,
+    /// the condition register is false
+    IfNotCondition = 3,
+    /// always skip or jump
+    Always = 4,
+}
+
+pub struct CommandFlow
+{
+    /// commands left to not run
+    skip_remaining : u32,
+    /// the anmchr entry and command we saw last, to tell if the next command carries on from it
+    anmchr_id : Option<i32>,
+    last_command_ptr : usize,
+    /// pointers to the 66 commands this character has run this frame, in order
+    history : Vec<usize>,
+    /// where in the history the current command is. None once the history is full
+    position : Option<usize>,
+    /// set by a backward jump, where in the history to start running from again
+    pending_jump : Option<usize>,
+    is_replaying : bool,
+}
+
+impl CommandFlow
+{
+    pub fn new() -> Self
+    {
+        Self {
+            skip_remaining : 0,
+            anmchr_id : None,
+            last_command_ptr : 0,
+            history : Vec::new(),
+            position : None,
+            pending_jump : None,
+            is_replaying : false,
+        }
+    }
+    
+    /// called once per character per tick, before any of that frame's commands run. skips are kept, see the top
+    pub fn new_frame(&mut self)
+    {
+        self.history.clear();
+        self.position = None;
+        self.pending_jump = None;
+        self.is_replaying = false;
+    }
+    
+    /// returns false if the command should be skipped
+    fn begin_command(&mut self, command_ptr : usize, anmchr_id : i32) -> bool
+    {
+        if !self.is_replaying {
+            // going backwards means we're in a different animation (or the start of the same one) now
+            let is_same_stream = self.anmchr_id == Some(anmchr_id) && command_ptr > self.last_command_ptr;
+            
+            if !is_same_stream {
+                self.skip_remaining = 0;
+                self.history.clear();
+            }
+            
+            self.anmchr_id = Some(anmchr_id);
+            self.last_command_ptr = command_ptr;
+            
+            if self.history.len() < HISTORY_CAPACITY {
+                self.history.push(command_ptr);
+                self.position = Some(self.history.len() - 1);
+            } else {
+                self.position = None;
+            }
+        }
+        
+        if self.skip_remaining > 0 {
+            self.skip_remaining -= 1;
+            
+            return false;
+        }
+        
+        true
+    }
+    
+    /// don't run the next `count` commands
+    pub fn skip(&mut self, count : u32)
+    {
+        self.skip_remaining = count;
+    }
+    
+    /// `offset` is in commands, counted from the command after this one. so 0 does nothing, 2 skips two commands,
+    /// and -1 runs this command again
+    pub fn jump(&mut self, offset : i32)
+    {
+        if offset >= 0 {
+            self.skip(offset as u32);
+            
+            return;
+        }
+        
+        if let Some(position) = self.position
+            && let Some(target) = (position + 1).checked_sub(offset.unsigned_abs() as usize) {
+            self.pending_jump = Some(target);
+        }
+    }
+    
+    /// the range of history to run again, if a jump is waiting and we're not already in the middle of one
+    fn start_replay(&mut self) -> Option<(usize, usize)>
+    {
+        if self.is_replaying {
+            return None;
+        }
+        
+        let target = self.pending_jump.take()?;
+        let last = self.position?;
+        
+        self.is_replaying = true;
+        
+        Some((target, last))
+    }
+    
+    fn replay_command(&mut self, index : usize) -> Option<usize>
+    {
+        let command_ptr = *self.history.get(index)?;
+        
+        self.position = Some(index);
+        
+        Some(command_ptr)
+    }
+    
+    fn end_replay(&mut self)
+    {
+        self.is_replaying = false;
+        self.pending_jump = None;
+        self.position = self.history.len().checked_sub(1);
+    }
+}
+
+/// returns false if the 66 command at `command_ptr` is being skipped, in which case nothing about it should run
+pub fn begin_command(char_ptr : usize, command_ptr : usize) -> bool
+{
+    let anmchr_id = Char::new(char_ptr).get_anmchr_id();
+    
+    storage::with(char_ptr, |store| store.command_flow.begin_command(command_ptr, anmchr_id))
+}
+
+/// called after each 66 command. if it was a backward jump, runs everything from the jump target up through the jump
+/// again. it's all done right here in one loop, so a loop in the commands can't pile up calls on the game's stack
+pub fn run_pending_jump(char_ptr : usize)
+{
+    let replay = storage::with_no_make(char_ptr, |store| store.command_flow.start_replay()).flatten();
+    
+    let Some((mut index, last)) = replay else {
+        return;
+    };
+    
+    let mut replayed = 0;
+    
+    while index <= last && replayed < MAX_REPLAYED_COMMANDS {
+        let command_ptr = storage::with_no_make(char_ptr, |store| store.command_flow.replay_command(index)).flatten();
+        
+        let Some(command_ptr) = command_ptr else {
+            break;
+        };
+        
+        // a skip inside of the loop skips the commands after it in the loop
+        if begin_command(char_ptr, command_ptr)
+            && let Some(command) = unsafe { read_ptr::<u32>(command_ptr + 4) } {
+            crate::anmchr_commands::run_ano_command(Char::new(char_ptr), ANO_COMMAND_GROUP, command, command_ptr + 8);
+        }
+        
+        replayed += 1;
+        
+        // jumps taken while replaying just move us around inside the replay
+        let jump = storage::with_no_make(char_ptr, |store| store.command_flow.pending_jump.take()).flatten();
+        
+        index = jump.unwrap_or(index + 1);
+    }
+    
+    storage::with_no_make(char_ptr, |store| store.command_flow.end_replay());
+}
@@ -0,0 +1,412 @@
+//! the settings file that sits next to mag_patch.asi, read once when the dll loads
+//!
+//! it's an ini file. `[section]` starts a section, `key = value` sets something in it, and `;` starts a comment.
+//! anything that's left out keeps its default, and a missing file is the same as an empty one. anything that's there
+//! but wrong (an unknown section or key, a value that doesn't parse, a key set twice) is an error. each error goes into
+//! mag_patch.log with its line, and only that line is skipped, so one typo doesn't throw away the rest of the file.
+//!
+//! ```text
+//! [hooks]
+//! character_ticks = true    ; forces, scheduled commands, motion inputs, and cleaning up registers after a restart
+//! anmchr_commands = true    ; the 66 commands
+//! input_parse = true        ; SOCD cleanup
+//!
+//! [socd]
+//! vertical = up             ; last, first, neutral, up or off, see socd.rs
+//!
+//! [log]
+//! level = warn              ; off, error, warn, info or debug, for mag_patch.log (see logging.rs)
+//!
+//! [persistence]
+//! registers = round         ; what registers start out as before 66_46, round, match or unload
+//! bools = round
+//!
+//! [commands]
+//! groups = 0x66             ; command groups set aside for mag_patch, separated by commas. 66 is always one of them
+//! error_register = off      ; an integer register to put error codes in when a command can't be run, like 0x7F
+//! ```
+
+#![deny(unsafe_op_in_unsafe_fn)]
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use crate::socd::{SocdPolicy, SocdMode};
+use crate::storage::Persistence;
+use crate::anmchr_commands::ANO_COMMAND_GROUP;
+
+pub const CONFIG_FILE_NAME : &str = "mag_patch.ini";
+
+/// 00-7F are integer registers, the rest are floats
+const LAST_INTEGER_REGISTER : u8 = 0x7F;
+
+/// the game's own commands are in groups 0 through 7
+const GAME_COMMAND_GROUPS : u32 = 8;
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum LogLevel
+{
+    Off,
+    Error,
+    Warn,
+    Info,
+    Debug,
+}
+
+const LOG_LEVELS : [LogLevel; 5] = [
+    LogLevel::Off,
+    LogLevel::Error,
+    LogLevel::Warn,
+    LogLevel::Info,
+    LogLevel::Debug,
+];
+
+impl LogLevel
+{
+    pub fn name(self) -> &'static str
+    {
+        match self {
+            LogLevel::Off => "off",
+            LogLevel::Error => "error",
+            LogLevel::Warn => "warn",
+            LogLevel::Info => "info",
+            LogLevel::Debug => "debug",
+        }
+    }
+    
+    pub fn from_name(name : &str) -> Option<Self>
+    {
+        LOG_LEVELS.into_iter().find(|level| name.eq_ignore_ascii_case(level.name()))
+    }
+}
+
+/// which of the game's functions get hooked
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct HookConfig
+{
+    pub character_ticks : bool,
+    pub anmchr_commands : bool,
+    pub input_parse : bool,
+}
+
+/// what every register's policy is before a character sets it with 66_46
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct PersistenceConfig
+{
+    pub registers : Persistence,
+    pub bools : Persistence,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Config
+{
+    pub hooks : HookConfig,
+    pub socd : SocdPolicy,
+    pub log_level : LogLevel,
+    pub persistence : PersistenceConfig,
+    /// the groups set aside for mag_patch, so other mods know not to use them. only 66 has any commands in it so far,
+    /// so a command in any of the others is reported as unknown, see command_error.rs
+    pub command_groups : Vec<u32>,
+    /// where a command that can't be run leaves its error code, see command_error.rs. off unless the file says which
+    /// one, since any register could already be in use by a character
+    pub error_register : Option<u8>,
+}
+
+impl Default for Config
+{
+    fn default() -> Self
+    {
+        Self {
+            hooks : HookConfig {
+                character_ticks : true,
+                anmchr_commands : true,
+                input_parse : true,
+            },
+            socd : SocdPolicy::DEFAULT,
+            // every teleport and replaced argument at debug is a lot of log for a release build
+            log_level : if cfg!(debug_assertions) { LogLevel::Debug } else { LogLevel::Warn },
+            persistence : PersistenceConfig {
+                registers : Persistence::default(),
+                bools : Persistence::default(),
+            },
+            command_groups : vec![ANO_COMMAND_GROUP],
+            error_register : None,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ConfigError {
+    /// 1-based line in the file
+    pub line : usize,
+    pub msg : String,
+}
+
+impl std::error::Error for ConfigError {}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} line {}: {}", CONFIG_FILE_NAME, self.line, self.msg)
+    }
+}
+
+/// everything in the file that made sense, along with all of the problems with the rest, so they can be fixed in one
+/// go. a line with a problem is skipped, and whatever it would have set stays on its default
+pub fn parse(text : &str) -> (Config, Vec<ConfigError>)
+{
+    let mut config = Config::default();
+    let mut errors = Vec::new();
+    let mut section : Option<String> = None;
+    let mut seen : Vec<(String, String)> = Vec::new();
+    
+    for (index, line) in text.lines().enumerate() {
+        let line_number = index + 1;
+        let line = line.split(';').next().unwrap_or("").trim();
+        
+        if line.is_empty() {
+            continue;
+        }
+        
+        if let Some(name) = line.strip_prefix('[') {
+            let Some(name) = name.strip_suffix(']') else {
+                errors.push(ConfigError { line : line_number, msg : format!("section `{}` is missing its `]`", line) });
+                continue;
+            };
+            
+            let name = name.trim().to_ascii_lowercase();
+            
+            if !matches!(name.as_str(), "hooks" | "socd" | "log" | "persistence" | "commands") {
+                errors.push(ConfigError { line : line_number, msg : format!("unknown section `[{}]`, it should be hooks, socd, log, persistence or commands", name) });
+            }
+            
+            section = Some(name);
+            continue;
+        }
+        
+        let Some((key, value)) = line.split_once('=') else {
+            errors.push(ConfigError { line : line_number, msg : format!("expected `key = value` or `[section]`, got `{}`", line) });
+            continue;
+        };
+        
+        let key = key.trim().to_ascii_lowercase();
+        let value = value.trim();
+        
+        let Some(section) = &section else {
+            errors.push(ConfigError { line : line_number, msg : format!("`{}` has to be inside a section, like [hooks]", key) });
+            continue;
+        };
+        
+        if value.is_empty() {
+            errors.push(ConfigError { line : line_number, msg : format!("`{}` is missing its value", key) });
+            continue;
+        }
+        
+        let seen_key = (section.clone(), key.clone());
+        
+        if seen.contains(&seen_key) {
+            errors.push(ConfigError { line : line_number, msg : format!("`{}` is set more than once in [{}]", key, section) });
+            continue;
+        }
+        
+        seen.push(seen_key);
+        
+        if let Err(msg) = set(&mut config, section, &key, value) {
+            errors.push(ConfigError { line : line_number, msg });
+        }
+    }
+    
+    (config, errors)
+}
+
+fn set(config : &mut Config, section : &str, key : &str, value : &str) -> Result<(), String>
+{
+    match (section, key) {
+        ("hooks", "character_ticks") => config.hooks.character_ticks = parse_bool(key, value)?,
+        ("hooks", "anmchr_commands") => config.hooks.anmchr_commands = parse_bool(key, value)?,
+        ("hooks", "input_parse") => config.hooks.input_parse = parse_bool(key, value)?,
+        // TODO - investigate more. left and right stay off until their bits are checked, see socd.rs
+        ("socd", "horizontal") => return Err(String::from("left and right can't be cleaned up yet, since it hasn't been checked which bits they are in the game's input. only `vertical` can be set")),
+        ("socd", "vertical") => config.socd.vertical = parse_socd_mode(value)?,
+        ("log", "level") => {
+            config.log_level = LogLevel::from_name(value)
+                .ok_or_else(|| format!("unknown log level `{}`, it should be off, error, warn, info or debug", value))?;
+        },
+        ("persistence", "registers") => config.persistence.registers = parse_persistence(value)?,
+        ("persistence", "bools") => config.persistence.bools = parse_persistence(value)?,
+        ("commands", "groups") => config.command_groups = parse_command_groups(value)?,
+        ("commands", "error_register") => config.error_register = parse_error_register(value)?,
+        // the section was already reported
+        ("hooks" | "socd" | "log" | "persistence" | "commands", _) => {
+            let keys = match section {
+                "hooks" => "character_ticks, anmchr_commands or input_parse",
+                "socd" => "vertical",
+                "log" => "level",
+                "persistence" => "registers or bools",
+                _ => "groups or error_register",
+            };
+            
+            return Err(format!("unknown key `{}` in [{}], it should be {}", key, section, keys));
+        },
+        _ => (),
+    }
+    
+    Ok(())
+}
+
+fn parse_bool(key : &str, value : &str) -> Result<bool, String>
+{
+    if value.eq_ignore_ascii_case("true") {
+        Ok(true)
+    } else if value.eq_ignore_ascii_case("false") {
+        Ok(false)
+    } else {
+        Err(format!("`{}` should be true or false, got `{}`", key, value))
+    }
+}
+
+fn parse_socd_mode(value : &str) -> Result<SocdMode, String>
+{
+    SocdMode::from_name(value)
+        .ok_or_else(|| format!("unknown SOCD mode `{}`, it should be last, neutral, first, up or off", value))
+}
+
+fn parse_persistence(value : &str) -> Result<Persistence, String>
+{
+    Persistence::from_name(value)
+        .ok_or_else(|| format!("unknown persistence `{}`, it should be round, match or unload", value))
+}
+
+/// 66 is always in there, whether it's listed or not, since leaving it out would turn off every 66 command
+fn parse_command_groups(value : &str) -> Result<Vec<u32>, String>
+{
+    let mut groups = vec![ANO_COMMAND_GROUP];
+    
+    for token in value.split(',').map(str::trim) {
+        let group = match token.strip_prefix("0x").or_else(|| token.strip_prefix("0X")) {
+            Some(hex) => u32::from_str_radix(hex, 16),
+            None => token.parse(),
+        }.map_err(|_| format!("command group `{}` isn't a number", token))?;
+        
+        if group < GAME_COMMAND_GROUPS {
+            return Err(format!("command group `{}` belongs to the game, it uses 0 through {}", token, GAME_COMMAND_GROUPS - 1));
+        }
+        
+        if !groups.contains(&group) {
+            groups.push(group);
+        }
+    }
+    
+    Ok(groups)
+}
+
+fn parse_error_register(value : &str) -> Result<Option<u8>, String>
+{
+    if value.eq_ignore_ascii_case("off") {
+        return Ok(None);
+    }
+    
+    let register = match value.strip_prefix("0x").or_else(|| value.strip_prefix("0X")) {
+        Some(hex) => u8::from_str_radix(hex, 16),
+        None => value.parse(),
+    };
+    
+    match register {
+        Ok(register @ 0..=LAST_INTEGER_REGISTER) => Ok(Some(register)),
+        _ => Err(format!("error_register should be off or an integer register from 0x00 to {:#04X}, got `{}`", LAST_INTEGER_REGISTER, value)),
+    }
+}
+
+static CONFIG : OnceLock<Config> = OnceLock::new();
+
+/// the settings, or the defaults if the file hasn't been loaded
+pub fn get() -> &'static Config
+{
+    CONFIG.get_or_init(Config::default)
+}
+
+/// the folder with mag_patch.asi in it, which is where mag_patch.ini and mag_patch.log go
+#[cfg(windows)]
+pub fn game_directory() -> Result<PathBuf, String>
+{
+    parent_directory(&dll_path()?)
+}
+
+/// outside of the game there's no mag_patch.asi to look for, like during tests, so it's the folder with the exe in it
+#[cfg(not(windows))]
+pub fn game_directory() -> Result<PathBuf, String>
+{
+    let exe = std::env::current_exe()
+        .map_err(|e| format!("couldn't find the game's folder: {}", e))?;
+    
+    parent_directory(&exe)
+}
+
+fn parent_directory(path : &Path) -> Result<PathBuf, String>
+{
+    path.parent()
+        .map(Path::to_path_buf)
+        .ok_or_else(|| format!("couldn't find the game's folder from {}", path.display()))
+}
+
+/// where mag_patch.asi was loaded from, which doesn't have to be where the game exe is
+#[cfg(windows)]
+fn dll_path() -> Result<PathBuf, String>
+{
+    use windows::core::PCWSTR;
+    use windows::Win32::Foundation::HMODULE;
+    use windows::Win32::System::LibraryLoader::{
+        GetModuleHandleExW, GetModuleFileNameW, GET_MODULE_HANDLE_EX_FLAG_FROM_ADDRESS, GET_MODULE_HANDLE_EX_FLAG_UNCHANGED_REFCOUNT
+    };
+    
+    // any address inside of mag_patch.asi finds it, like this function's own. with FROM_ADDRESS the "name" is that
+    // address, and UNCHANGED_REFCOUNT means there's nothing to free afterwards
+    let mut module = HMODULE::default();
+    
+    unsafe {
+        GetModuleHandleExW(
+            GET_MODULE_HANDLE_EX_FLAG_FROM_ADDRESS | GET_MODULE_HANDLE_EX_FLAG_UNCHANGED_REFCOUNT,
+            PCWSTR(dll_path as *const u16),
+            &mut module,
+        )
+    }.map_err(|e| format!("couldn't find mag_patch.asi: {}", e))?;
+    
+    // the longest path windows allows
+    let mut buffer = vec![0u16; 0x8000];
+    let len = unsafe { GetModuleFileNameW(Some(module), &mut buffer) } as usize;
+    
+    // it fills the whole buffer when the path doesn't fit
+    if len == 0 || len >= buffer.len() {
+        return Err(String::from("couldn't find where mag_patch.asi is"));
+    }
+    
+    String::from_utf16(&buffer[..len])
+        .map(PathBuf::from)
+        .map_err(|_| String::from("couldn't read the path to mag_patch.asi"))
+}
+
+/// reads the file next to mag_patch.asi and makes it what `get` gives back, along with anything that was wrong with it.
+/// does nothing if it was already loaded
+pub fn load() -> (&'static Config, Vec<String>)
+{
+    let text = game_directory().and_then(|directory| {
+        let path = directory.join(CONFIG_FILE_NAME);
+        
+        match std::fs::read_to_string(&path) {
+            Ok(text) => Ok(text),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(String::new()),
+            Err(e) => Err(format!("couldn't read {}: {}", path.display(), e)),
+        }
+    });
+    
+    let (config, errors) = match text {
+        Ok(text) => {
+            let (config, errors) = parse(&text);
+            
+            (config, errors.iter().map(ToString::to_string).collect())
+        },
+        Err(msg) => (Config::default(), vec![msg]),
+    };
+    
+    (CONFIG.get_or_init(|| config), errors)
+}
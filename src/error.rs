@@ -1,15 +1,19 @@
 
 
-use windows::core::s;
-use windows::core::{PCSTR};
-use windows::Win32::UI::WindowsAndMessaging;
-use std::ffi::CString;
-
 #[derive(Debug)]
 pub struct MagError {
     msg : String,
 }
 
+impl MagError {
+    pub fn new<S: Into<String>>(msg : S) -> Self
+    {
+        MagError {
+            msg : msg.into(),
+        }
+    }
+}
+
 impl std::error::Error for MagError {
 }
 
@@ -19,29 +23,40 @@ impl std::fmt::Display for MagError {
     }
 }
 
-pub fn panic_msg<S: Into<String>>(msg : S)
+/// for when mag_patch can't start. it only goes into the log, since a message box would hold the game up until someone
+/// clicked it, and there'd be nothing they could do about it from there anyway
+#[cfg(windows)]
+pub fn report(error : Box<dyn std::error::Error>)
 {
-    let e = MagError {
-        msg : msg.into(),
-    };
+    // the log itself might be what couldn't start. the debugger (or DebugView) is the only other place left to say so
+    if !crate::logging::is_open() {
+        for line in error.to_string().lines() {
+            debug_output(&format!("mag_patch couldn't start: {}", line));
+        }
+        
+        return;
+    }
     
-    panic(Box::new(e));
+    // every line in the log is one line long
+    for line in error.to_string().lines() {
+        crate::log!(crate::config::LogLevel::Error, None, "mag_patch couldn't start: {}", line);
+    }
+    
+    let backtrace = std::backtrace::Backtrace::force_capture().to_string();
+    
+    for line in backtrace.lines() {
+        crate::log!(crate::config::LogLevel::Debug, None, "{}", line);
+    }
 }
 
-pub fn panic(error : Box<dyn std::error::Error>)
+/// one line for OutputDebugString, which shows up in a debugger attached to the game, or in DebugView
+#[cfg(windows)]
+fn debug_output(line : &str)
 {
-    let backtrace = std::backtrace::Backtrace::force_capture();
-    
-    let msg = format!("mag_patch error:\n{}\n{}",error,backtrace);
-    let msg = CString::new(msg).unwrap();
+    use windows::Win32::System::Diagnostics::Debug::OutputDebugStringA;
     
-    unsafe {
-        WindowsAndMessaging::MessageBoxA(None,
-            PCSTR(msg.as_ptr()  as *const u8),
-            s!("mag_patch error"),
-            Default::default()
-        );
-    };
+    // a nul in the middle would cut it short, so those go
+    let line = std::ffi::CString::new(format!("{}\n", line.replace('\0', ""))).unwrap_or_default();
     
-    panic!("{}", error.to_string());
+    unsafe { OutputDebugStringA(windows::core::PCSTR(line.as_ptr().cast())) };
 }
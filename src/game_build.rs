@@ -0,0 +1,276 @@
+//! which umvc3.exe this is, and where everything is in it
+//!
+//! every hook and every one of the game's structs is at a fixed spot inside the exe, and those spots move around
+//! whenever the exe is rebuilt, like for a patch or a different region. hooking the wrong spot patches random code and
+//! takes the game down with it, so before anything gets hooked, the exe's headers are hashed and looked up in `BUILDS`.
+//! if it isn't there, nothing gets hooked and the game runs like mag_patch isn't there at all.
+//!
+//! the one exception is `FALLBACK`. mag_patch always used the steam build's spots before it checked which build it
+//! was in, so for as long as the steam build's fingerprint hasn't been written down, any exe it fits inside of is
+//! still treated as the steam build, with a warning in mag_patch.log. once the fingerprint is there, that stops.
+//!
+//! to support another build, find everything in `Offsets` in it, then add it to `BUILDS` with the fingerprint that
+//! mag_patch.log gives for it.
+
+#![deny(unsafe_op_in_unsafe_fn)]
+
+use std::fmt;
+use std::sync::OnceLock;
+
+use crate::checksum::Checksum;
+use crate::character_tick::CharacterTickOffsets;
+
+/// the most of the exe's headers that get hashed. they're smaller than this in every exe we know of
+const MAX_HEADERS_SIZE : usize = 0x1000;
+
+const IMAGE_FILE_MACHINE_AMD64 : u16 = 0x8664;
+
+/// what tells one build of the exe apart from another, all from the PE headers at the start of it
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Fingerprint
+{
+    /// when the exe was linked
+    pub timestamp : u32,
+    /// how big the exe is once it's loaded
+    pub image_size : u32,
+    /// all of the headers, which includes where every section is and how big it is
+    pub headers_hash : u64,
+}
+
+impl Fingerprint
+{
+    /// None if `headers` isn't the start of a 64 bit exe
+    pub fn from_headers(headers : &[u8]) -> Option<Self>
+    {
+        let u16_at = |offset : usize| headers.get(offset..offset + 2).map(|bytes| u16::from_le_bytes([bytes[0], bytes[1]]));
+        let u32_at = |offset : usize| headers.get(offset..offset + 4).map(|bytes| u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]));
+        
+        if headers.get(0..2)? != b"MZ" {
+            return None;
+        }
+        
+        let pe_offset = u32_at(0x3C)? as usize;
+        
+        if headers.get(pe_offset..pe_offset + 4)? != b"PE\0\0" || u16_at(pe_offset + 4)? != IMAGE_FILE_MACHINE_AMD64 {
+            return None;
+        }
+        
+        let timestamp = u32_at(pe_offset + 8)?;
+        let optional_header = pe_offset + 24;
+        let image_size = u32_at(optional_header + 56)?;
+        let headers_size = u32_at(optional_header + 60)? as usize;
+        
+        let mut checksum = Checksum::new();
+        checksum.write(headers.get(..headers_size.min(MAX_HEADERS_SIZE))?);
+        
+        Some(Self {
+            timestamp,
+            image_size,
+            headers_hash : checksum.finish(),
+        })
+    }
+    
+    /// the exe that's running, which is always loaded at `EXE_BASE`
+    pub fn of_running_exe() -> Option<Self>
+    {
+        use crate::hook_helpers::{EXE_BASE, get_cursor};
+        
+        // the headers are always the first page of the exe, so all of it is there to read
+        let headers = unsafe { get_cursor(EXE_BASE, MAX_HEADERS_SIZE) }.into_inner();
+        
+        Self::from_headers(headers)
+    }
+}
+
+impl fmt::Display for Fingerprint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Fingerprint {{ timestamp : {:#010X}, image_size : {:#X}, headers_hash : {:#018X} }}",
+            self.timestamp, self.image_size, self.headers_hash)
+    }
+}
+
+/// where everything is in one build of the exe. everything is relative to `EXE_BASE`
+#[derive(Clone, Copy, Debug)]
+pub struct Offsets
+{
+    /// the game's structs, see game_memory.rs
+    pub char_nodes_base : usize,
+    pub match_action_base : usize,
+    pub p1_projectile_base : usize,
+    pub p2_projectile_base : usize,
+    
+    /// the game's functions that mag_patch calls
+    pub get_char_id : usize,
+    pub get_character_name : usize,
+    
+    /// the game's functions that mag_patch hooks
+    pub execute_anmchr_command : usize,
+    pub input_parse : usize,
+    pub character_ticks : CharacterTickOffsets,
+}
+
+impl Offsets
+{
+    /// the furthest anything is into the exe, to check that it even fits in the one that's running
+    fn furthest(&self) -> usize
+    {
+        [
+            self.char_nodes_base,
+            self.match_action_base,
+            self.p1_projectile_base,
+            self.p2_projectile_base,
+            self.get_char_id,
+            self.get_character_name,
+            self.execute_anmchr_command,
+            self.input_parse,
+        ].into_iter()
+            .chain(self.character_ticks.iter())
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+pub struct GameBuild
+{
+    pub name : &'static str,
+    /// None if it hasn't been written down yet, which means this build is only ever used as the `FALLBACK`
+    pub fingerprint : Option<Fingerprint>,
+    pub offsets : Offsets,
+}
+
+pub const STEAM : GameBuild = GameBuild {
+    name : "steam",
+    // TODO - investigate more. the steam exe's fingerprint hasn't been written down yet, so until it is, this build
+    // is only used as the fallback. mag_patch.log says what the fingerprint of the exe it ran in was, so it can be
+    // copied from there
+    fingerprint : None,
+    offsets : Offsets {
+        char_nodes_base : 0xD44A70,
+        match_action_base : 0xD47E68,
+        p1_projectile_base : 0xD47F98,
+        p2_projectile_base : 0xD47FC0,
+        
+        get_char_id : 0x4AD0,
+        get_character_name : 0x58F90,
+        
+        execute_anmchr_command : 0xFB7A0,
+        input_parse : 0x2DA30,
+        character_ticks : CharacterTickOffsets {
+            amaterasu_character_tick : 0x600b0,
+            captain_america_character_tick : 0x66c20,
+            chris_character_tick : 0x69310,
+            chun_li_character_tick : 0x6b7c0,
+            crimson_viper_character_tick : 0x6d670,
+            
+            dante_character_tick : 0x700a0,
+            deadpool_character_tick : 0x74470,
+            dormammu_character_tick : 0x77670,
+            doctor_doom_character_tick : 0x79eb0,
+            dr_strange_character_tick : 0x7ad00,
+            dr_strange_sh_character_tick : 0x7d990,
+            felicia_character_tick : 0x7F870,
+            
+            felicia_c_or_f_character_tick : 0x804D0,
+            felicia_f_or_c_character_tick : 0x81310,
+            frank_west_character_tick : 0x835E0,
+            galactus_character_tick : 0x86F90,
+            ghost_rider_character_tick : 0x89400,
+            akuma_character_tick : 0x8c3c0,
+            haggar_character_tick : 0x8e370,
+            
+            hawkeye_character_tick : 0x90810,
+            strider_character_tick : 0x92b60,
+            hulk_character_tick : 0x95b40,
+            iron_fist_character_tick : 0x97740,
+            iron_man_character_tick : 0x99e30,
+            jill_character_tick : 0x9ceb0,
+            
+            hsien_ko_character_tick : 0xa1410,
+            magneto_character_tick : 0xa66e0,
+            maya_character_tick : 0xa8830,
+            modok_character_tick : 0xaaeb0,
+            morrigan_character_tick : 0xac6e0,
+            morrigan_sh_character_tick : 0xae4a0,
+            
+            nemesis_character_tick : 0xb5010,
+            nova_character_tick : 0xb8a00,
+            phoenix_character_tick : 0xbbba0,
+            firebrand_character_tick : 0xbe8f0,
+            firebrand_sh_character_tick : 0xBFD10,
+            
+            rocket_raccoon_character_tick : 0xC4C10,
+            ryu_character_tick : 0xc84f0,
+            sentinel_character_tick : 0xca240,
+            she_hulk_character_tick : 0xCD200,
+            shuma_gorath_character_tick : 0xcdc90,
+            spencer_character_tick : 0xD1A00,
+            
+            spider_man_character_tick : 0xd4b30,
+            storm_character_tick : 0xd76d0,
+            super_skrull_character_tick : 0xdabd0,
+            taskmaster_character_tick : 0xdd840,
+            thor_character_tick : 0xdff60,
+            
+            tron_bonne_character_tick : 0xE52D0,
+            vergil_joe_character_tick : 0xE81B0,
+            viewtiful_joe_character_tick : 0xebbb0,
+            wesker_character_tick : 0xedf80,
+            
+            wolverine_character_tick : 0xf0d70,
+            x_23_character_tick : 0xf2eb0,
+            zero_character_tick : 0xf6850,
+            zero_sh_character_tick : 0xf9e30,
+            zombie_character_tick : 0xfac60,
+        },
+    },
+};
+
+pub const BUILDS : &[GameBuild] = &[STEAM];
+
+/// the build that's used when none of `BUILDS` match, see `fallback`
+pub const FALLBACK : &GameBuild = &STEAM;
+
+/// which of `builds` has exactly this fingerprint. two builds the same size can still have everything in different
+/// spots, so nothing short of all of it matching is good enough
+pub fn identify(fingerprint : &Fingerprint, builds : &'static [GameBuild]) -> Option<&'static GameBuild>
+{
+    builds.iter().find(|build| build.fingerprint.as_ref() == Some(fingerprint))
+}
+
+/// `build`, if it can stand in for an exe that `identify` didn't know. that's only while its own fingerprint hasn't
+/// been written down, and only if everything it has fits inside of the exe
+pub fn fallback(fingerprint : &Fingerprint, build : &'static GameBuild) -> Option<&'static GameBuild>
+{
+    (build.fingerprint.is_none() && build.offsets.furthest() < fingerprint.image_size as usize).then_some(build)
+}
+
+static BUILD : OnceLock<&'static GameBuild> = OnceLock::new();
+
+/// figures out which build is running, so `offsets` gives the right ones. an error means nothing should be hooked
+pub fn detect() -> Result<&'static GameBuild, String>
+{
+    let fingerprint = Fingerprint::of_running_exe()
+        .ok_or_else(|| String::from("umvc3.exe doesn't look like a 64 bit exe, so nothing was hooked"))?;
+    
+    let build = if let Some(build) = identify(&fingerprint, BUILDS) {
+        crate::log!(crate::config::LogLevel::Info, None, "this is the {} build of umvc3.exe", build.name);
+        
+        build
+    } else if let Some(build) = fallback(&fingerprint, FALLBACK) {
+        crate::log!(crate::config::LogLevel::Warn, None,
+            "assuming this is the {} build of umvc3.exe, since its fingerprint hasn't been written down yet. its {}", build.name, fingerprint);
+        
+        build
+    } else {
+        return Err(format!("this build of umvc3.exe isn't one mag_patch knows, so nothing was hooked. its {}", fingerprint));
+    };
+    
+    Ok(BUILD.get_or_init(|| build))
+}
+
+/// where everything is in the build that's running. None until `detect` has found it, which is before anything
+/// gets hooked
+pub fn offsets() -> Option<&'static Offsets>
+{
+    BUILD.get().map(|build| &build.offsets)
+}
@@ -4,7 +4,7 @@
 use std::fmt;
 use num_derive::FromPrimitive;
 use std::io::{Cursor};
-use byteorder::{LittleEndian, ReadBytesExt};
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
 
 use crate::hook_helpers::*;
 use crate::var_rw;
@@ -12,13 +12,12 @@ use crate::bitflag_getter;
 use crate::math::*;
 use crate::binary_operators::{BinaryOp,BinaryOpHandler};
 use crate::storage::RegisterType;
+use crate::command_error::CommandResult;
 use crate::strings::{GStr};
+use crate::game_memory;
+use crate::snapshot;
+use crate::snapshot::SnapshotError;
 
-const CHAR_NODES_BASE : usize = 0xD44A70;
-const TEAMS_BASE : usize = 0xD47E68;
-
-const P1_PROJECTILE_BASE : usize = EXE_BASE + 0xD47F98;
-const P2_PROJECTILE_BASE : usize = EXE_BASE + 0xD47FC0;
 
 /// We set up getters and setters for basic offsetted values inside structs like so. Setting them up this way reduces code duplication / chance mistakes.
 macro_rules! offset_getter_and_setter {
@@ -198,6 +197,14 @@ impl CharacterRelation
             CharacterRelation::Ally(relation_within_team)
         }
     }
+    
+    /// false if `decode` would have to fall back to Me for this byte
+    pub fn is_recognized(byte : u8) -> bool
+    {
+        let relation_within_team : Option<RelationWithinTeam> = num::FromPrimitive::from_u8(byte & const { !RELATION_OPPONENT_MASK} );
+        
+        relation_within_team.is_some()
+    }
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -226,25 +233,18 @@ pub struct CharNode
 impl CharNode {
     pub fn player1() -> Self {
         Self {
-            ptr : unsafe { read_usize(read_usize(EXE_BASE + CHAR_NODES_BASE) + 0x58) }
+            ptr : unsafe { read_usize(game_memory::char_nodes_ptr() + 0x58) }
         }
     }
     
     pub fn player2() -> Self {
         Self {
-            ptr : unsafe { read_usize(read_usize(EXE_BASE + CHAR_NODES_BASE) + 0x328) }
+            ptr : unsafe { read_usize(game_memory::char_nodes_ptr() + 0x328) }
         }
     }
     
     pub fn from_char(character : &Char) -> Option<Self> {
-        for c in CharNode::all_nodes() {
-            if c.char_ptr() == character.ptr
-            {
-                return Some(c);
-            }
-        }
-        
-        None
+        CharNode::all_nodes().find(|c| c.char_ptr() == character.ptr)
     }
     
     #[expect(dead_code)]
@@ -349,14 +349,14 @@ impl DoubleEndedIterator for CharNode {
 pub fn get_p1_point_char_ptr() -> usize
 {
     unsafe {
-        read_usize(read_usize(EXE_BASE + TEAMS_BASE) + 0x350 + 0x48)
+        read_usize(game_memory::match_action_ptr() + 0x350 + 0x48)
     }
 }
 
 pub fn get_p2_point_char_ptr() -> usize
 {
     unsafe {
-        read_usize(read_usize(EXE_BASE + TEAMS_BASE) + 0x610 + 0x48)
+        read_usize(game_memory::match_action_ptr() + 0x610 + 0x48)
     }
 }
 
@@ -453,16 +453,14 @@ impl Char {
         }
     }
     
-    #[cfg(test)]
-    pub fn identify_team(&self) -> Team
-    {
-        // during tests iterating over the char nodes would just crash because they dont exist
-        Team::Player1
-    }
-    
-    #[cfg(not(test))]
     pub fn identify_team(&self) -> Team
     {
+        // during tests without a simulated game iterating over the char nodes would just crash because they dont exist
+        #[cfg(test)]
+        if !game_memory::is_simulated() {
+            return Team::Player1;
+        }
+        
         for c in CharNode::player1() {
             if self.ptr == c.char_ptr()
             {
@@ -480,6 +478,27 @@ impl Char {
         Team::Unknown
     }
     
+    /// the character's team, and where it is in that team's list of characters (children included).
+    /// unlike the pointer, this comes out the same on every machine, so it's what checksums sort by
+    pub fn team_slot(&self) -> Option<(Team, usize)>
+    {
+        // same as identify_team, there's no char nodes to look through without a simulated game
+        #[cfg(test)]
+        if !game_memory::is_simulated() {
+            return None;
+        }
+        
+        if let Some(slot) = CharNode::player1().position(|c| c.char_ptr() == self.ptr) {
+            return Some((Team::Player1, slot));
+        }
+        
+        if let Some(slot) = CharNode::player2().position(|c| c.char_ptr() == self.ptr) {
+            return Some((Team::Player2, slot));
+        }
+        
+        None
+    }
+    
     pub fn player(&self) -> Option<Player>
     {
         self.identify_team().player()
@@ -518,24 +537,16 @@ impl Char {
                     }
                 },
                 RelationWithinTeam::Assist1WithFallback => {
-                    if let Some(player) = base.player() {
-                        Some(player.assist1_char_fallback())
-                    } else {
-                        None
-                    }
+                    base.player().map(|player| player.assist1_char_fallback())
                 },
                 RelationWithinTeam::Assist2WithFallback => {
-                    if let Some(player) = base.player() {
-                        Some(player.assist2_char_fallback())
-                    } else {
-                        None
-                    }
+                    base.player().map(|player| player.assist2_char_fallback())
                 },
                 RelationWithinTeam::Char1NotMe => {
                     if let Some(player) = base.player() {
                         let maybe = player.point_char();
                         
-                        let ancestor = CharNode::from_char(self).and_then(|c| Some(c.true_ancestor().get_char()));
+                        let ancestor = CharNode::from_char(self).map(|c| c.true_ancestor().get_char());
                         
                         if maybe == *self || Some(maybe.clone()) == ancestor {
                             player.assist1_char()
@@ -547,7 +558,7 @@ impl Char {
                     }
                 },
                 RelationWithinTeam::TrueAncestor => {
-                    CharNode::from_char(self).and_then(|c| Some(c.true_ancestor().get_char()))
+                    CharNode::from_char(self).map(|c| c.true_ancestor().get_char())
                 },
                 RelationWithinTeam::Parent => {
                     CharNode::from_char(self).and_then(
@@ -582,25 +593,22 @@ impl Char {
         }
     }
     
-    // for unit tests we have to use this version because CharNodes don't exist during unit tests
-    #[cfg(test)]
-    pub fn if_valid_ancestor<F, T>(addr : usize, default : T, function : F) -> T
-        where F : FnOnce(Char) -> T
-    {
-        Self::if_valid(addr, default, function)
-    }
-    
-    #[cfg(not(test))]
     pub fn if_valid_ancestor<F, T>(addr : usize, default : T, function : F) -> T
         where F : FnOnce(Char) -> T
     {
+        // for unit tests without a simulated game CharNodes don't exist
+        #[cfg(test)]
+        if !game_memory::is_simulated() {
+            return Self::if_valid(addr, default, function);
+        }
+        
         if addr == 0 {
             default
         } else {
             let character = Self { ptr : addr };
             
             let character = CharNode::from_char(&character)
-                .and_then(|node| Some(node.true_ancestor().get_char()))
+                .map(|node| node.true_ancestor().get_char())
                 .unwrap_or(character.clone());
             
             if character.identify_team() == Team::Unknown {
@@ -649,7 +657,7 @@ impl Char {
             let iter = player.get_projectiles();
             
             let iter = if filter_flags.is_filter_backwards() {
-                iter.map(|iter| iter.create_backward()).flatten()
+                iter.and_then(|iter| iter.create_backward())
             } else {
                 iter
             };
@@ -672,6 +680,23 @@ impl Char {
         }
     }
     
+    /// every projectile this character owns right now, newest first
+    pub fn get_own_projectiles(&self) -> Vec<Projectile>
+    {
+        let mut projectiles = Vec::new();
+        
+        if let Some(mut filter) = self.get_projectiles(ProjectileFilterFlags { raw : 0 }, None, None) {
+            filter.step();
+            
+            while let Some(projectile) = filter.projectile {
+                projectiles.push(projectile);
+                filter.step();
+            }
+        }
+        
+        projectiles
+    }
+    
     pub fn get_ptr(&self) -> usize {
         self.ptr
     }
@@ -688,6 +713,15 @@ impl Char {
         }
     }
     
+    /// turns an x going the way the character is facing into stage x, and back
+    pub fn facing_x(&self, x : f32) -> f32
+    {
+        match self.get_facing() {
+            Facing::Left => -x,
+            Facing::Right => x,
+        }
+    }
+    
     
     pub fn get_hitstun_non_knockdown(&self) -> HitstunFlagA
     {
@@ -835,9 +869,7 @@ impl Char {
         if id > 0 {
             id
         } else {
-            let get_char_id = external_fn!(EXE_BASE + 0x4AD0, extern "win64" fn(usize) -> i32);
-            
-            get_char_id(self.get_ptr())
+            game_memory::get_char_id(self.get_ptr())
         }
     }
     
@@ -845,6 +877,14 @@ impl Char {
     offset_getter_and_setter!(get_clone_engine_char_id, _set_clone_engine_char_id, i32, 0x08);
     offset_getter_and_setter!(get_x_pos, set_x_pos, f32, 0x50);
     offset_getter_and_setter!(get_y_pos, set_y_pos, f32, 0x54);
+    // the physics block. the game adds acceleration to velocity and velocity to position every frame.
+    // x is in stage space here, not relative to facing. y acceleration is what gravity gets put into while airborne
+    // TODO - investigate more. these have only been read on a few characters, and nobody has checked what the game does
+    // if they're written in the middle of a move, so they're read only for now (see docs/variables-wishlist.txt)
+    offset_getter_and_setter!(get_x_velocity, _set_x_velocity_dont_use, f32, 0xE0);
+    offset_getter_and_setter!(get_y_velocity, _set_y_velocity_dont_use, f32, 0xE4);
+    offset_getter_and_setter!(get_x_acceleration, _set_x_acceleration_dont_use, f32, 0xF0);
+    offset_getter_and_setter!(get_y_acceleration, _set_y_acceleration_dont_use, f32, 0xF4);
     offset_getter_and_setter!(get_anmchr_id, set_anmchr_id, i32, 0x1310);
     offset_getter_and_setter!(get_max_health, set_max_health_raw, i32, 0x154c);
     offset_getter_and_setter!(get_health, set_health_raw, f32, 0x1550);
@@ -869,24 +909,28 @@ impl Char {
     offset_getter_and_setter!(get_normal_air_action_counter, set_normal_air_action_counter, i32, 0x4190);
     offset_getter_and_setter!(get_special_air_action_counter, set_special_air_action_counter, i32, 0x41a0);
     offset_getter_and_setter!(get_assist_cooldown, set_assist_cooldown_raw, f32, 0x41ac);
+    // these are on the character getting hit, and the game puts them back when the combo ends
+    // TODO - investigate more. these have only been read during a few combos, nobody has checked what happens to them on
+    // tag or what the game does if they're written, so they're read only for now (see docs/variables-wishlist.txt)
+    offset_getter_and_setter!(get_damage_scaling, _set_damage_scaling_dont_use, f32, 0x4168);
+    offset_getter_and_setter!(get_hitstun_scaling, _set_hitstun_scaling_dont_use, f32, 0x416c);
+    offset_getter_and_setter!(get_times_hit, _set_times_hit_dont_use, i32, 0x4170);
     
     
     
     offset_getter_and_setter_flag!(get_flying_screen_install, set_flying_screen_install, u8, 0x1509, 0x04);
+    offset_getter_and_setter_flag!(get_ground_bounce_used, _set_ground_bounce_used_dont_use, u8, 0x4174, 0x01);
+    offset_getter_and_setter_flag!(get_wall_bounce_used, _set_wall_bounce_used_dont_use, u8, 0x4174, 0x02);
 }
 
 pub fn get_p1_ptr() -> usize
 {
-    unsafe {
-        read_usize(EXE_BASE + TEAMS_BASE) + 0x350 
-    }
+    game_memory::match_action_ptr() + 0x350
 }
 
 pub fn get_p2_ptr() -> usize
 {
-    unsafe {
-        read_usize(EXE_BASE + TEAMS_BASE) + 0x610 
-    }
+    game_memory::match_action_ptr() + 0x610
 }
 
 #[derive(PartialEq, Eq, Debug)]
@@ -998,10 +1042,20 @@ impl Player {
         }
     }
     
+    pub fn is_xfactor_active(&self) -> bool
+    {
+        self.get_xfactor_timer() > 0.0
+    }
+    
     offset_getter_and_setter!(get_meter, set_meter_raw, f32, 0x78);
     offset_getter_and_setter!(get_team_combo_counter, _set_team_combo_counter_dont_use, i32, 0x90);
-    // FIXME - clamp this properly
-    //offset_getter_and_setter!(get_xfactor_timer, set_xfactor_timer_raw, f32, 0xC0);
+    // FIXME - clamp this properly. the max and level are both set when x-factor is activated, from how many characters
+    // are left, and the level stays after it runs out
+    // TODO - investigate more. these have only been read, so until the max is known to be right, none of them get
+    // written (see docs/variables-wishlist.txt)
+    offset_getter_and_setter!(get_xfactor_timer, _set_xfactor_timer_dont_use, f32, 0xC0);
+    offset_getter_and_setter!(get_xfactor_max_timer, _set_xfactor_max_timer_dont_use, f32, 0xC4);
+    offset_getter_and_setter!(get_xfactor_level, _set_xfactor_level_dont_use, i32, 0xC8);
 }
 
 
@@ -1048,11 +1102,7 @@ impl ProjectileFilter {
     {
         let is_correct_owner = match &self.current_owner {
             Some(current_owner) => {
-                if projectile.get_current_owner() != *current_owner {
-                    false
-                } else {
-                    true
-                }
+                projectile.get_current_owner() == *current_owner
             },
             None => true,
         };
@@ -1091,22 +1141,85 @@ impl ProjectileFilter {
             return false;
         }
         
-        let does_filename_match = match &self.filename {
+        match &self.filename {
             Some(desired_filename) => {
                 let potential_filename = projectile.get_shot_resource().get_filename();
                 
                 desired_filename.path_suffix_compare(&potential_filename)
             },
             None => true,
+        }
+    }
+    
+    /// see snapshot.rs. it's all pointers into the game's memory, so it's only good for as long as those stay loaded
+    pub fn write_snapshot(&self, out : &mut Vec<u8>)
+    {
+        snapshot::write_ptr(out, self.current_owner.as_ref().map(Char::get_ptr));
+        snapshot::write_ptr(out, self.iter.ptr);
+        out.write_u8(match self.iter.direction {
+            IterationDirection::Forward => 0,
+            IterationDirection::Backward => 1,
+        }).unwrap();
+        snapshot::write_ptr(out, self.projectile.as_ref().map(Projectile::get_ptr));
+        
+        match &self.op_filter {
+            Some(op_filter) => {
+                out.write_u8(1).unwrap();
+                out.write_u32::<LittleEndian>(op_filter.op as u32).unwrap();
+                out.write_u32::<LittleEndian>(op_filter.variable_id).unwrap();
+                snapshot::write_number(out, op_filter.immediate);
+            },
+            None => out.write_u8(0).unwrap(),
+        }
+        
+        snapshot::write_ptr(out, self.filename.as_ref().map(GStr::get_ptr));
+        out.write_u64::<LittleEndian>(self.filename.as_ref().map_or(0, GStr::capacity) as u64).unwrap();
+    }
+    
+    pub fn read_snapshot(cursor : &mut Cursor<&[u8]>) -> Result<Self, SnapshotError>
+    {
+        let current_owner = snapshot::read_ptr(cursor)?.map(Char::new);
+        let iter = ProjectileIterator {
+            ptr : snapshot::read_ptr(cursor)?,
+            direction : match cursor.read_u8()? {
+                0 => IterationDirection::Forward,
+                1 => IterationDirection::Backward,
+                _ => return Err(SnapshotError::Invalid("projectile list direction")),
+            },
         };
+        let projectile = snapshot::read_ptr(cursor)?.map(|ptr| Projectile { ptr });
         
-        does_filename_match
+        let op_filter = match cursor.read_u8()? {
+            0 => None,
+            1 => {
+                let op = num::FromPrimitive::from_u32(cursor.read_u32::<LittleEndian>()?)
+                    .ok_or(SnapshotError::Invalid("projectile filter operation"))?;
+                let variable_id = cursor.read_u32::<LittleEndian>()?;
+                let immediate = snapshot::read_number(cursor)?;
+                
+                Some(ProjectileOpFilter::binary_op_immediate(op, variable_id, immediate))
+            },
+            _ => return Err(SnapshotError::Invalid("projectile filter")),
+        };
+        
+        let filename_ptr = snapshot::read_ptr(cursor)?;
+        let filename_capacity = usize::try_from(cursor.read_u64::<LittleEndian>()?)
+            .map_err(|_| SnapshotError::Invalid("filename capacity"))?;
+        let filename = filename_ptr.map(|ptr| GStr::from_ptr(ptr, filename_capacity));
+        
+        Ok(Self {
+            current_owner,
+            iter,
+            projectile,
+            op_filter,
+            filename,
+        })
     }
     
     pub fn step(&mut self) {
-        let mut iter = self.iter.clone();
+        let mut iter = self.iter;
         
-        self.projectile = iter.by_ref().filter(|p| self.filter(p)).next();
+        self.projectile = iter.by_ref().find(|p| self.filter(p));
         
         self.iter = iter;
     }
@@ -1116,22 +1229,17 @@ impl Iterator for ProjectileIterator {
     type Item = Projectile;
     
     fn next(&mut self) -> Option<Self::Item> {
-        if self.ptr == None
-        {
-            return None;
-        }
+        let ptr = self.ptr?;
         
         // check if we are at the root of the list
-        if self.ptr == Some(P1_PROJECTILE_BASE)
-          || self.ptr == Some(P2_PROJECTILE_BASE)
+        if game_memory::is_projectile_root(ptr)
         {
             // note, no direction check here because it just doesnt make sense
             self.ptr = Some(unsafe {
-                read_usize(self.ptr.unwrap() + 0x08)
+                read_usize(ptr + 0x08)
             });
             
-            if self.ptr == Some(P1_PROJECTILE_BASE)
-              || self.ptr == Some(P2_PROJECTILE_BASE)
+            if self.ptr.is_some_and(game_memory::is_projectile_root)
             {
                 self.ptr = None;
                 // list is just empty so don't loop forever
@@ -1157,8 +1265,7 @@ impl Iterator for ProjectileIterator {
             });
             
             // check if we are at the root of the list
-            if self.ptr == Some(P1_PROJECTILE_BASE)
-              || self.ptr == Some(P2_PROJECTILE_BASE)
+            if self.ptr.is_some_and(game_memory::is_projectile_root)
             {
                 self.ptr = None;
             }
@@ -1172,7 +1279,7 @@ impl ProjectileIterator {
     pub fn get_p1() -> ProjectileIterator
     {
         ProjectileIterator {
-            ptr : Some(P1_PROJECTILE_BASE),
+            ptr : Some(game_memory::p1_projectile_root()),
             direction : IterationDirection::Forward,
         }
     }
@@ -1180,7 +1287,7 @@ impl ProjectileIterator {
     pub fn get_p2() -> ProjectileIterator
     {
         ProjectileIterator {
-            ptr : Some(P2_PROJECTILE_BASE),
+            ptr : Some(game_memory::p2_projectile_root()),
             direction : IterationDirection::Forward,
         }
     }
@@ -1189,14 +1296,10 @@ impl ProjectileIterator {
     {
         let last = self.last();
         
-        match last {
-            Some(last) => 
-                Some(ProjectileIterator {
-                    ptr : Some(last.ptr),
-                    direction : IterationDirection::Backward,
-                }),
-            None => None,
-        }
+        last.map(|last| ProjectileIterator {
+            ptr : Some(last.ptr),
+            direction : IterationDirection::Backward,
+        })
     }
 }
 
@@ -1233,6 +1336,12 @@ impl Projectile {
     
     offset_getter_and_setter!(get_x_pos, set_x_pos, f32, 0x50 + PROJ_OFFSET);
     offset_getter_and_setter!(get_y_pos, set_y_pos, f32, 0x54 + PROJ_OFFSET);
+    // same physics block as characters have
+    // the same physics block as a character's, and read only for the same reason
+    offset_getter_and_setter!(get_x_velocity, _set_x_velocity_dont_use, f32, 0xE0 + PROJ_OFFSET);
+    offset_getter_and_setter!(get_y_velocity, _set_y_velocity_dont_use, f32, 0xE4 + PROJ_OFFSET);
+    offset_getter_and_setter!(get_x_acceleration, _set_x_acceleration_dont_use, f32, 0xF0 + PROJ_OFFSET);
+    offset_getter_and_setter!(get_y_acceleration, _set_y_acceleration_dont_use, f32, 0xF4 + PROJ_OFFSET);
     offset_getter_and_setter!(get_duration, set_duration, f32, 0x2078 + PROJ_OFFSET);
     offset_getter_and_setter!(get_current_owner_raw, set_current_owner_raw, usize, 0x2000 + PROJ_OFFSET);
     offset_getter_and_setter!(get_shot_resource_raw, set_shot_resource_raw, usize, 0x1f68 + PROJ_OFFSET);
@@ -1291,11 +1400,11 @@ pub struct ProjectileFilterFlags {
 impl ProjectileFilterFlags {
     bitflag_getter!(0x01, is_filter_backwards);
     
-    pub fn read(cursor : &mut Cursor<&'static [u8]>) -> Self
+    pub fn read(cursor : &mut Cursor<&'static [u8]>) -> CommandResult<Self>
     {
-        Self {
-            raw : cursor.read_u32::<LittleEndian>().unwrap()
-        }
+        Ok(Self {
+            raw : cursor.read_u32::<LittleEndian>()?
+        })
     }
 }
 
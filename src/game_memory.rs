@@ -0,0 +1,151 @@
+//! Where the game's own global structs live.
+//!
+//! Everything else in game_data and match_state is found by following pointers out of these, so swapping these out
+//! is enough to point the whole crate at a different copy of the game's structs. In the real game these are fixed
+//! addresses inside umvc3.exe, which depend on the build (see game_build.rs). During tests, a `SimulatedGame` can be
+//! installed instead (see simulated_game.rs).
+
+#![deny(unsafe_op_in_unsafe_fn)]
+
+use crate::hook_helpers::*;
+use crate::game_build::offsets;
+
+pub trait GameMemory {
+    /// the struct at [EXE_BASE + 0xD47E68] in the steam build. holds the match state and timers, and both players at +0x350 and +0x610
+    fn match_action_ptr(&self) -> usize;
+    
+    /// the struct at [EXE_BASE + 0xD44A70] in the steam build. player 1's first char node is at +0x58, player 2's at +0x328
+    fn char_nodes_ptr(&self) -> usize;
+    
+    /// the root node of player 1's projectile list. the list loops back around to this
+    fn p1_projectile_root(&self) -> usize;
+    
+    /// the root node of player 2's projectile list. the list loops back around to this
+    fn p2_projectile_root(&self) -> usize;
+    
+    /// the game's own lookup for a character's id, for characters that aren't clone engine characters
+    fn get_char_id(&self, char_ptr : usize) -> i32;
+    
+    /// pointer to the name of character `id`, at least 64 bytes long
+    fn get_character_name_ptr(&self, id : i32) -> usize;
+}
+
+/// the actual game's memory. until the build has been found (see game_build.rs) there's nowhere to look, so
+/// everything is null and every character's id is -1. nothing gets hooked before then, so that's never seen in game
+pub struct RealMemory;
+
+impl GameMemory for RealMemory {
+    fn match_action_ptr(&self) -> usize
+    {
+        offsets().map_or(0, |offsets| unsafe { read_usize(EXE_BASE + offsets.match_action_base) })
+    }
+    
+    fn char_nodes_ptr(&self) -> usize
+    {
+        offsets().map_or(0, |offsets| unsafe { read_usize(EXE_BASE + offsets.char_nodes_base) })
+    }
+    
+    fn p1_projectile_root(&self) -> usize
+    {
+        offsets().map_or(0, |offsets| EXE_BASE + offsets.p1_projectile_base)
+    }
+    
+    fn p2_projectile_root(&self) -> usize
+    {
+        offsets().map_or(0, |offsets| EXE_BASE + offsets.p2_projectile_base)
+    }
+    
+    fn get_char_id(&self, char_ptr : usize) -> i32
+    {
+        let Some(offsets) = offsets() else {
+            return -1;
+        };
+        
+        let get_char_id = external_fn!(EXE_BASE + offsets.get_char_id, extern "win64" fn(usize) -> i32);
+        
+        get_char_id(char_ptr)
+    }
+    
+    fn get_character_name_ptr(&self, id : i32) -> usize
+    {
+        let Some(offsets) = offsets() else {
+            return 0;
+        };
+        
+        let get_character_name_ptr = external_fn!(EXE_BASE + offsets.get_character_name, extern "win64" fn(i32) -> *const u8);
+        
+        get_character_name_ptr(id) as usize
+    }
+}
+
+#[cfg(not(test))]
+#[inline]
+pub fn with<F, T>(function : F) -> T
+    where F : FnOnce(&dyn GameMemory) -> T
+{
+    function(&RealMemory)
+}
+
+#[cfg(test)]
+thread_local! {
+    static SIMULATED : std::cell::Cell<Option<crate::simulated_game::SimulatedGame>> = const { std::cell::Cell::new(None) };
+}
+
+/// during tests, the current thread's simulated game if there is one, otherwise the real game
+#[cfg(test)]
+pub fn with<F, T>(function : F) -> T
+    where F : FnOnce(&dyn GameMemory) -> T
+{
+    match SIMULATED.get() {
+        Some(simulated) => function(&simulated),
+        None => function(&RealMemory),
+    }
+}
+
+#[cfg(test)]
+pub fn set_simulated(simulated : Option<crate::simulated_game::SimulatedGame>)
+{
+    SIMULATED.set(simulated);
+}
+
+/// tests that don't set up a simulated game have no game memory at all, so some lookups have to be faked for them
+#[cfg(test)]
+pub fn is_simulated() -> bool
+{
+    SIMULATED.get().is_some()
+}
+
+pub fn match_action_ptr() -> usize
+{
+    with(|memory| memory.match_action_ptr())
+}
+
+pub fn char_nodes_ptr() -> usize
+{
+    with(|memory| memory.char_nodes_ptr())
+}
+
+pub fn is_projectile_root(ptr : usize) -> bool
+{
+    with(|memory| ptr == memory.p1_projectile_root() || ptr == memory.p2_projectile_root())
+}
+
+pub fn p1_projectile_root() -> usize
+{
+    with(|memory| memory.p1_projectile_root())
+}
+
+pub fn p2_projectile_root() -> usize
+{
+    with(|memory| memory.p2_projectile_root())
+}
+
+pub fn get_char_id(char_ptr : usize) -> i32
+{
+    with(|memory| memory.get_char_id(char_ptr))
+}
+
+pub fn get_character_name_ptr(id : i32) -> usize
+{
+    with(|memory| memory.get_character_name_ptr(id))
+}
@@ -2,38 +2,28 @@
 #![deny(unsafe_op_in_unsafe_fn)]
 #![macro_use]
 
-use std::sync::{Arc};
-#[cfg(debug_assertions)]
-use std::ffi::CString;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
 use std::io::Cursor;
 
-#[cfg(debug_assertions)]
-use windows::core::s;
-
-#[cfg(debug_assertions)]
-use windows::core::{PCSTR};
-
-#[cfg(debug_assertions)]
-use windows::Win32::UI::WindowsAndMessaging;
-
-use retour::{GenericDetour};
-
-use crate::error::*;
+#[cfg(windows)]
+pub use retour::{Function, GenericDetour};
+#[cfg(not(windows))]
+pub use no_detours::{Function, GenericDetour};
 
 /// base address of umvc3.exe
 pub const EXE_BASE : usize = 0x140000000;
 
 pub trait Hook {
-    fn make_hook(replaced_ptr : usize, replacer : Self) -> Result<(), Box<dyn std::error::Error>> where Self: retour::Function;
+    fn make_hook(replaced_ptr : usize, replacer : Self) -> Result<(), Box<dyn std::error::Error>> where Self: Function;
     
     /// used in cases where the optimizer might replace our many replacer functions with one function (character ticks)
     fn get_original_from_original_addr(original : usize)
         -> Arc<GenericDetour<Self>>
-        where Self: retour::Function;
+        where Self: Function;
     
     fn get_original(replacer : Self)
         -> Arc<GenericDetour<Self>>
-        where Self: retour::Function;
+        where Self: Function;
 }
 
 macro_rules! typed_hooks {
@@ -42,7 +32,7 @@ macro_rules! typed_hooks {
         mod $statics_mod {
             use std::collections::HashMap;
             use std::sync::{LazyLock, Mutex, Arc};
-            use retour::{GenericDetour};
+            use crate::hook_helpers::GenericDetour;
             
             pub static HOOKS : LazyLock<Mutex<HashMap<usize, Arc<GenericDetour<$hooked_func_type>>>>> = LazyLock::new(|| {
                 Mutex::new(HashMap::new())
@@ -55,7 +45,7 @@ macro_rules! typed_hooks {
         }
         
         impl Hook for $hooked_func_type {
-            fn make_hook(replaced_ptr : usize, replacer : Self) -> Result<(), Box<dyn std::error::Error>> where Self: retour::Function
+            fn make_hook(replaced_ptr : usize, replacer : Self) -> Result<(), Box<dyn std::error::Error>> where Self: Function
             {
                 {
                     let mut hooks_replacer = $statics_mod::HOOKS_REPLACER.lock()?;
@@ -71,7 +61,8 @@ macro_rules! typed_hooks {
                     {
                         let addr = replaced_ptr as usize;
                         
-                        panic_msg(format!("major mag_patch error: to hook address {:#X} with duplicate ptr", addr));
+                        return Err(Box::new(crate::error::MagError::new(
+                            format!("major mag_patch error: to hook address {:#X} with duplicate ptr", addr))));
                     }
                     
                     let replaced = unsafe { std::mem::transmute::<usize, $hooked_func_type>(replaced_ptr) };
@@ -88,7 +79,7 @@ macro_rules! typed_hooks {
             
             fn get_original_from_original_addr(original : usize)
                 -> Arc<GenericDetour<Self>>
-                where Self: retour::Function
+                where Self: Function
             {
                 let hooks = $statics_mod::HOOKS.lock().unwrap();
                 
@@ -99,7 +90,7 @@ macro_rules! typed_hooks {
             
             fn get_original(replacer : Self)
                 -> Arc<GenericDetour<Self>>
-                where Self: retour::Function
+                where Self: Function
             {
                 let ptr = replacer as usize;
                 
@@ -119,6 +110,57 @@ macro_rules! typed_hooks {
     }
 }
 
+/// there's no game to patch anywhere but windows, so off of it a hook just remembers the original function and calls it
+/// directly. that's all the tests need to run the hooked functions against `SimulatedGame`
+#[cfg(not(windows))]
+mod no_detours {
+    use std::convert::Infallible;
+    
+    pub trait Function : Copy + 'static {}
+    
+    pub struct GenericDetour<T : Function>
+    {
+        original : T,
+    }
+    
+    impl<T : Function> GenericDetour<T>
+    {
+        /// # Safety
+        /// nothing gets patched, so there's nothing unsafe about it. it's only unsafe to match retour
+        pub unsafe fn new(original : T, _replacer : T) -> Result<Self, Infallible>
+        {
+            Ok(Self { original })
+        }
+        
+        /// # Safety
+        /// see `new`
+        pub unsafe fn enable(&self) -> Result<(), Infallible>
+        {
+            Ok(())
+        }
+    }
+    
+    macro_rules! no_detour_call {
+        ($( $arg:ident : $arg_type:ident ),*) => {
+            impl<$( $arg_type : 'static, )* R : 'static> Function for unsafe extern "win64" fn($( $arg_type ),*) -> R {}
+            
+            impl<$( $arg_type : 'static, )* R : 'static> GenericDetour<unsafe extern "win64" fn($( $arg_type ),*) -> R>
+            {
+                /// # Safety
+                /// the same as calling the original function
+                pub unsafe fn call(&self, $( $arg : $arg_type ),*) -> R
+                {
+                    unsafe { (self.original)($( $arg ),*) }
+                }
+            }
+        }
+    }
+    
+    no_detour_call!(a : A);
+    no_detour_call!(a : A, b : B);
+    no_detour_call!(a : A, b : B, c : C);
+}
+
 typed_hooks!(crate::character_tick::TickFn, __tick_hooks);
 typed_hooks!(crate::ExecuteAnmChrCommandFn, __execute_anmchr_command);
 typed_hooks!(crate::input_parse::InputParseFn, __input_parse_hook);
@@ -162,6 +204,13 @@ pub unsafe fn get_mut_cursor(addr : usize, len : usize) -> Cursor<&'static mut [
     })
 }
 
+/// locks even if a panic happened while it was locked before. commands that panic are caught (see command_error.rs),
+/// and whatever they were in the middle of is better than every command after them panicking too
+pub fn lock<T>(mutex : &Mutex<T>) -> MutexGuard<'_, T>
+{
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
 pub unsafe fn read_ptr_no_check<T>(addr : usize) -> T
     where T : Copy
 {
@@ -176,15 +225,16 @@ const ADDR_MIN : usize = 0x00010000;
 const ADDR_MAX : usize = 0x180000000;
 
 // while testing we're not in the address space of the game's exe so the norm ADDR_MAX doesnt really work right
+// this is the top of user space, since heap addresses on linux sit up around 0x5555_0000_0000
 #[cfg(test)]
-const ADDR_MAX : usize = 0xFFFFFFFFFFF;
+const ADDR_MAX : usize = 0x7FFFFFFFFFFF;
 
 pub unsafe fn read_ptr<T>(addr : usize) -> Option<T>
     where T : Copy
 {
     // check nullness among other things
     
-    if addr < ADDR_MIN || addr >= ADDR_MAX {
+    if !(ADDR_MIN..ADDR_MAX).contains(&addr) {
         None
     } else {
         Some(unsafe { read_ptr_no_check(addr) })
@@ -207,33 +257,17 @@ pub unsafe fn read_usize(addr : usize) -> usize
 }
 
 
-#[cfg(debug_assertions)]
+/// goes into mag_patch.log when the log level in mag_patch.ini is debug, which it is by default in debug builds
 pub fn debug_msg<S: Into<String>>(msg : S)
 {
-    let msg = CString::new(msg.into()).unwrap();
-    
-    unsafe {
-        WindowsAndMessaging::MessageBoxA::<PCSTR,PCSTR>(
-            None,
-            PCSTR(msg.as_ptr()  as *const u8),
-            s!("mag_patch debug message"),
-            Default::default()
-        );
-    };
-}
-
-#[cfg(not(debug_assertions))]
-pub fn debug_msg<S: Into<String>>(_msg : S)
-{
+    crate::log!(crate::config::LogLevel::Debug, None, "{}", msg.into());
 }
 
 
 macro_rules! external_fn {
     ($addr:expr, $fn_type:ty) => {
         {
-            let result = $addr as *const $fn_type;
-            
-            let result : $fn_type = unsafe { std::mem::transmute(result) };
+            let result : $fn_type = unsafe { std::mem::transmute::<usize, $fn_type>($addr) };
             
             result
         }
@@ -10,12 +10,9 @@ pub extern "win64" fn input_parse(param1 : u32, param2 : u32, param3 : u32) -> u
     let hook = InputParseFn::get_original(input_parse);
     let output = unsafe { hook.call(param1, param2, param3) };
     
-    if (output & 0x0C) == 0x0C {
-        // clean up+down SOCD to prevent cheating and serious bugs
-        output & 0xFFFFFFF7
-    } else {
-        output
-    }
+    // clean up SOCD to prevent cheating and serious bugs, see socd.rs
+    // TODO - investigate more. we're assuming param1 is different for each player, which hasn't been checked
+    crate::socd::resolve(param1, output)
 }
 
 
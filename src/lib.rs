@@ -4,29 +4,51 @@
 
 mod hook_helpers;
 mod game_data;
+mod game_memory;
 mod anmchr_commands;
 mod character_tick;
 mod character_extensions;
+mod command_flow;
 mod match_state;
 mod storage;
 mod unary_operators;
 mod binary_operators;
+mod ternary_operators;
 mod var_rw;
 mod math;
+mod vector;
+mod random;
+mod snapshot;
+mod checksum;
+mod schedule;
+mod motion;
 mod reload;
 mod error;
+mod command_error;
 mod input_parse;
+mod socd;
+mod config;
+mod game_build;
+mod logging;
 #[cfg(test)]
 mod tests;
+#[cfg(test)]
+mod simulated_game;
 mod strings;
+pub mod anmchr_asm;
+pub mod anmchr_validate;
 
+#[cfg(windows)]
 use windows::Win32::System::SystemServices;
+#[cfg(windows)]
 use windows::Win32::Foundation::HINSTANCE;
 
 use crate::hook_helpers::*;
+#[cfg(windows)]
 use crate::error::*;
 
 
+#[cfg(windows)]
 #[unsafe(no_mangle)]
 pub extern "system" fn DllMain(
         _dll_module: HINSTANCE,
@@ -40,10 +62,29 @@ pub extern "system" fn DllMain(
         _ => Ok(())
     };
     
-    match result {
-        Err(e) => {panic(e); false},
-        Ok(()) => true,
+    // still true when attach fails. false would unload mag_patch, and any hooks it made before failing would be left
+    // pointing at nothing, which crashes the game the first time one of them runs. the ones that didn't get made just
+    // don't do anything
+    if let Err(e) = result {
+        report(e);
     }
+    
+    true
+}
+
+/// for netplay: a hash of all of mag_patch's state for this frame (see checksum.rs). if two machines get a different
+/// number on the same frame, they've desynced
+#[unsafe(no_mangle)]
+pub extern "C" fn mag_patch_state_checksum() -> u64
+{
+    crate::checksum::state_checksum()
+}
+
+/// how many custom commands have been skipped since the game started because they couldn't be run (see command_error.rs)
+#[unsafe(no_mangle)]
+pub extern "C" fn mag_patch_command_failures() -> u64
+{
+    crate::command_error::failure_count()
 }
 
 #[derive(Debug)]
@@ -59,51 +100,101 @@ impl std::fmt::Display for MpError {
     }
 }
 
+/// only DllMain calls this, but it's still built everywhere else so everything it sets up gets checked along with the tests
+#[cfg_attr(not(windows), allow(dead_code))]
 fn attach() -> Result<(), Box<dyn std::error::Error>> {
-    crate::character_tick::hook_character_ticks()?;
+    // a mistake in mag_patch.ini isn't worth taking the game down over, so it's logged once the log is open instead
+    let (config, config_errors) = crate::config::load();
+    
+    let directory = crate::config::game_directory().map_err(|msg| MpError { msg })?;
+    crate::logging::init(&directory)
+        .map_err(|e| MpError { msg : format!("couldn't open the log file in {}: {}", directory.display(), e) })?;
+    
+    // each mistake gets its own line in the log
+    for msg in &config_errors {
+        crate::log!(crate::config::LogLevel::Error, None, "{}", msg);
+    }
     
-    ExecuteAnmChrCommandFn::make_hook(EXE_BASE + 0xFB7A0, execute_anmchr_command)?;
+    if !config_errors.is_empty() {
+        crate::log!(crate::config::LogLevel::Error, None, "so those were left on their defaults. the rest of {} was still used", crate::config::CONFIG_FILE_NAME);
+    }
     
+    let groups : Vec<String> = config.command_groups.iter().map(|group| format!("{:X}", group)).collect();
+    crate::log!(crate::config::LogLevel::Info, None, "command groups set aside for mag_patch: {}", groups.join(", "));
     
-    crate::input_parse::InputParseFn::make_hook(EXE_BASE + 0x2DA30, crate::input_parse::input_parse)?;
+    crate::anmchr_commands::set_command_groups(&config.command_groups);
+    crate::command_error::set_error_register(config.error_register);
     
-    debug_msg("mag_patch hooking success!\nthis is a beta 1");
+    // patching the wrong spots would crash the game, so on a build we don't know, it's better to do nothing at all
+    let build = match crate::game_build::detect() {
+        Ok(build) => build,
+        Err(msg) => {
+            crate::log!(crate::config::LogLevel::Error, None, "{}", msg);
+            
+            return Ok(());
+        },
+    };
+    let offsets = &build.offsets;
+    
+    if config.hooks.character_ticks {
+        crate::character_tick::hook_character_ticks(&offsets.character_ticks)?;
+    }
+    
+    if config.hooks.anmchr_commands {
+        ExecuteAnmChrCommandFn::make_hook(EXE_BASE + offsets.execute_anmchr_command, execute_anmchr_command)?;
+    }
+    
+    
+    crate::socd::set_policy(config.socd);
+    
+    if config.hooks.input_parse {
+        crate::input_parse::InputParseFn::make_hook(EXE_BASE + offsets.input_parse, crate::input_parse::input_parse)?;
+    }
+    
+    crate::log!(crate::config::LogLevel::Info, None, "mag_patch hooking success! this is a beta 1");
     
     Ok(())
 }
 
 pub type ExecuteAnmChrCommandFn = unsafe extern "win64" fn(usize, usize);
-/// original is at EXE_BASE + 0xFB7A0,
+/// original is at EXE_BASE + 0xFB7A0 in the steam build, see game_build.rs
 pub extern "win64" fn execute_anmchr_command(executor_ptr : usize, anmchr_command_ptr : usize)
 {
     use crate::game_data::Char;
     
-    let command_type_group = unsafe { read_ptr::<u32>(anmchr_command_ptr as usize) };
+    let command_type_group = unsafe { read_ptr::<u32>(anmchr_command_ptr) };
     let command = unsafe { read_ptr::<u32>(anmchr_command_ptr + 4) };
     // i am not certain this is the correct way to do this
     // but it seems like it is working thus far
     let exe_char_ptr = executor_ptr - 0x1348;
     
+    let is_ano_command = command_type_group == Some(crate::anmchr_commands::ANO_COMMAND_GROUP);
+    let is_mag_patch_command = command_type_group.is_some_and(crate::anmchr_commands::is_command_group);
+    
+    // skipped by 66_20 or 66_21. those only count 66 commands, so the game's own always run, see command_flow.rs
+    if is_ano_command && !crate::command_flow::begin_command(exe_char_ptr, anmchr_command_ptr) {
+        return;
+    }
+    
     let reloads = crate::reload::save_anmchr_command(exe_char_ptr, anmchr_command_ptr + 8, command_type_group, command);
     
     // (game uses commands 0 through 7 inclusive)
-    // 0x66 commands are ones added by anotak
-    if command_type_group == Some(0x66)
+    // 0x66 commands are ones added by anotak. the other groups mag_patch.ini sets aside for mag_patch (see config.rs)
+    // don't have any commands yet, so they're all reported as unknown
+    // a command that can't be run is skipped and reported, see command_error.rs
+    if is_mag_patch_command
+        && let Some(group) = command_type_group
+        && let Some(command) = command
     {
-        if let Some(command) = command {
-            let command = num::FromPrimitive::from_u32(command);
-            
-            if let Some(command) = command {
-                
-                let exe_char = Char::new(exe_char_ptr);
-                
-                crate::anmchr_commands::handle_ano_command(command, exe_char, anmchr_command_ptr + 8);
-            }
-        }
+        crate::anmchr_commands::run_ano_command(Char::new(exe_char_ptr), group, command, anmchr_command_ptr + 8);
     }
     
     let hook = ExecuteAnmChrCommandFn::get_original(execute_anmchr_command);
     unsafe { hook.call(executor_ptr, anmchr_command_ptr) };
     
     reloads.restore();
+    
+    if is_ano_command {
+        crate::command_flow::run_pending_jump(exe_char_ptr);
+    }
 }
@@ -0,0 +1,269 @@
+//! writing what mag_patch is up to into mag_patch.log, next to mag_patch.asi
+//!
+//! message boxes stop the game until someone clicks them, which is no good in the middle of a netplay set, so
+//! everything goes into a file instead. every line starts with the frame, the match state and which character it's
+//! about, like `[frame 1234 | Fighting | p2.0 id 0x2A] warn: ...`. the level comes from mag_patch.ini (see config.rs).
+//!
+//! a bad command can run every frame for every character, so only `MAX_LINES_PER_FRAME` lines get written each frame,
+//! and the rest are counted and reported once the frame's over. once the file gets to `MAX_LOG_SIZE` it's moved to
+//! mag_patch.1.log (and that one to mag_patch.2.log and so on), so it never eats the whole disk.
+
+#![deny(unsafe_op_in_unsafe_fn)]
+
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{LazyLock, Mutex};
+
+use crate::config::LogLevel;
+use crate::game_data::{Char, Team};
+use crate::hook_helpers::lock;
+
+pub const LOG_FILE_NAME : &str = "mag_patch";
+
+/// bytes, before the file gets rotated
+pub const MAX_LOG_SIZE : u64 = 1024 * 1024;
+
+/// how many old files are kept, as mag_patch.1.log through mag_patch.3.log
+pub const KEPT_LOGS : usize = 3;
+
+pub const MAX_LINES_PER_FRAME : usize = 32;
+
+/// writes `format!` style arguments at `level`, about a character if there is one. nothing is formatted unless the
+/// level is turned on
+#[macro_export]
+macro_rules! log {
+    ($level:expr, $character:expr, $($arg:tt)+) => {
+        if $crate::logging::is_enabled($level) {
+            $crate::logging::write($level, $character, format!($($arg)+));
+        }
+    };
+}
+
+/// a log file that moves itself out of the way once it's big enough
+pub struct RotatingFile
+{
+    directory : PathBuf,
+    max_size : u64,
+    file : File,
+    size : u64,
+}
+
+impl RotatingFile
+{
+    pub fn open(directory : &Path, max_size : u64) -> std::io::Result<Self>
+    {
+        let path = Self::path(directory, 0);
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let size = file.metadata()?.len();
+        
+        Ok(Self {
+            directory : directory.to_path_buf(),
+            max_size,
+            file,
+            size,
+        })
+    }
+    
+    /// 0 is the current one
+    pub fn path(directory : &Path, index : usize) -> PathBuf
+    {
+        match index {
+            0 => directory.join(format!("{}.log", LOG_FILE_NAME)),
+            index => directory.join(format!("{}.{}.log", LOG_FILE_NAME, index)),
+        }
+    }
+    
+    pub fn write_line(&mut self, line : &str) -> std::io::Result<()>
+    {
+        if self.size > 0 && self.size + line.len() as u64 + 1 > self.max_size {
+            self.rotate()?;
+        }
+        
+        // one write per line, so a crash right after still leaves the line in the file
+        self.file.write_all(format!("{}\n", line).as_bytes())?;
+        self.size += line.len() as u64 + 1;
+        
+        Ok(())
+    }
+    
+    fn rotate(&mut self) -> std::io::Result<()>
+    {
+        let _ = fs::remove_file(Self::path(&self.directory, KEPT_LOGS));
+        
+        for index in (0..KEPT_LOGS).rev() {
+            let from = Self::path(&self.directory, index);
+            
+            if from.exists() {
+                fs::rename(from, Self::path(&self.directory, index + 1))?;
+            }
+        }
+        
+        self.file = OpenOptions::new().create(true).append(true).open(Self::path(&self.directory, 0))?;
+        self.size = 0;
+        
+        Ok(())
+    }
+}
+
+/// counts lines for the current frame
+pub struct RateLimiter
+{
+    limit : usize,
+    written : usize,
+    dropped : usize,
+}
+
+impl RateLimiter
+{
+    pub fn new(limit : usize) -> Self
+    {
+        Self {
+            limit,
+            written : 0,
+            dropped : 0,
+        }
+    }
+    
+    /// whether there's room for another line this frame
+    pub fn allow(&mut self) -> bool
+    {
+        if self.written < self.limit {
+            self.written += 1;
+            true
+        } else {
+            self.dropped += 1;
+            false
+        }
+    }
+    
+    /// starts the next frame. returns how many lines didn't make it into the last one
+    pub fn new_frame(&mut self) -> usize
+    {
+        let dropped = self.dropped;
+        
+        self.written = 0;
+        self.dropped = 0;
+        
+        dropped
+    }
+}
+
+/// `[frame 1234 | Fighting | p2.0 id 0x2A] warn: the message`
+pub fn format_line(frame : u64, match_state : &str, character : &str, level : LogLevel, msg : &str) -> String
+{
+    format!("[frame {} | {} | {}] {}: {}", frame, match_state, character, level.name(), msg)
+}
+
+/// which team and slot, and which character id. anything that isn't on a team gets its pointer instead
+fn describe(character : Option<&Char>) -> String
+{
+    let Some(character) = character else {
+        return String::from("-");
+    };
+    
+    match character.team_slot() {
+        Some((team, slot)) => {
+            let team = if team == Team::Player1 { "p1" } else { "p2" };
+            
+            format!("{}.{} id {:#X}", team, slot, character.get_char_id())
+        },
+        None => character.to_string(),
+    }
+}
+
+struct Logger
+{
+    file : Option<RotatingFile>,
+    limiter : RateLimiter,
+    /// counted by `new_frame`, since the game doesn't keep a frame count of its own that we know of
+    frame : u64,
+    last_timer : Option<f32>,
+}
+
+static LOGGER : LazyLock<Mutex<Logger>> = LazyLock::new(|| {
+    Mutex::new(Logger {
+        file : None,
+        limiter : RateLimiter::new(MAX_LINES_PER_FRAME),
+        frame : 0,
+        last_timer : None,
+    })
+});
+
+/// starts writing to mag_patch.log in `directory`
+pub fn init(directory : &Path) -> std::io::Result<()>
+{
+    if !is_enabled(LogLevel::Error) {
+        return Ok(());
+    }
+    
+    let file = RotatingFile::open(directory, MAX_LOG_SIZE)?;
+    
+    lock(&LOGGER).file = Some(file);
+    
+    Ok(())
+}
+
+/// whether mag_patch.log is open to be written to. it isn't if `init` failed, or if logging is off
+#[cfg_attr(not(windows), allow(dead_code))]
+pub fn is_open() -> bool
+{
+    lock(&LOGGER).file.is_some()
+}
+
+pub fn is_enabled(level : LogLevel) -> bool
+{
+    level != LogLevel::Off && level <= crate::config::get().log_level
+}
+
+/// called every character tick with the match timer. the first tick after the timer moves is a new frame
+pub fn new_frame(timer : f32)
+{
+    let mut guard = lock(&LOGGER);
+    let logger = &mut *guard;
+    
+    if logger.last_timer == Some(timer) {
+        return;
+    }
+    
+    logger.last_timer = Some(timer);
+    logger.frame += 1;
+    
+    let dropped = logger.limiter.new_frame();
+    
+    if dropped > 0 {
+        let line = format!("[frame {}] {} more lines were dropped", logger.frame - 1, dropped);
+        
+        if let Some(file) = &mut logger.file {
+            let _ = file.write_line(&line);
+        }
+    }
+}
+
+/// use `log!` instead, so nothing gets formatted when the level is off
+pub fn write(level : LogLevel, character : Option<&Char>, msg : String)
+{
+    let mut guard = lock(&LOGGER);
+    let logger = &mut *guard;
+    
+    // there's no file during tests, and no game to describe the character with either
+    let Some(file) = &mut logger.file else {
+        return;
+    };
+    
+    // errors always get through, but they still count toward the frame's lines
+    if !logger.limiter.allow() && level != LogLevel::Error {
+        return;
+    }
+    
+    // until a character has ticked, the game might not even know which build it is yet, let alone have a match to
+    // read the state of, like while mag_patch is starting up
+    let match_state = match logger.last_timer {
+        Some(_) => format!("{:?}", crate::match_state::get_match_state()),
+        None => String::from("-"),
+    };
+    let line = format_line(logger.frame, &match_state, &describe(character), level, &msg);
+    
+    // nowhere left to complain about it
+    let _ = file.write_line(&line);
+}
@@ -3,11 +3,10 @@
 #![deny(unsafe_op_in_unsafe_fn)]
 
 use crate::hook_helpers::*;
+use crate::game_memory;
 
 use num_derive::FromPrimitive;
 
-pub const MATCH_ACTION_OFFSET : usize = 0xd47e68;
-
 #[derive(Copy,Clone,PartialEq, Eq, Debug, FromPrimitive)]
 #[repr(i32)]
 // at address [EXE_BASE + 0xd47e68] + 0xD8
@@ -45,7 +44,7 @@ pub enum MatchState {
 
 pub fn get_match_state() -> MatchState
 {
-    let match_state = unsafe { read_ptr_no_check(read_usize(EXE_BASE + MATCH_ACTION_OFFSET) + 0xD8) };
+    let match_state = unsafe { read_ptr_no_check(game_memory::match_action_ptr() + 0xD8) };
     let match_state = num::FromPrimitive::from_i32(match_state);
     
     if let Some(match_state) = match_state {
@@ -59,7 +58,7 @@ pub fn get_match_state() -> MatchState
 
 pub fn get_match_frame_time() -> f32
 {
-    unsafe { read_ptr_no_check(read_usize(EXE_BASE + MATCH_ACTION_OFFSET) + 0xE4) }
+    unsafe { read_ptr_no_check(game_memory::match_action_ptr() + 0xE4) }
 }
 
 /// the timer internally is 180 real seconds for 99 marvel seconds so we just do some math so our modders dont have to worry about that
@@ -67,7 +66,7 @@ const GET_TIMER_RATIO : f32 = 99.0 / 180.0;
 /// The timer that shows 99 at round start normally. -1 is infinite
 pub fn get_match_game_time() -> f32
 {
-    let timer = unsafe { read_ptr_no_check(read_usize(EXE_BASE + MATCH_ACTION_OFFSET) + 0xf8) };
+    let timer = unsafe { read_ptr_no_check(game_memory::match_action_ptr() + 0xf8) };
     
     if timer > 0.0 {
         timer * GET_TIMER_RATIO
@@ -91,7 +90,7 @@ pub fn set_match_game_time(new_time : f32)
         if old_time > 0.0 {
             let new_timer = new_time * SET_TIMER_RATIO;
             
-            let ptr = unsafe { read_usize(EXE_BASE + MATCH_ACTION_OFFSET) } + 0xf8;
+            let ptr = game_memory::match_action_ptr() + 0xf8;
             
             unsafe { write_ptr(ptr, new_timer) };
         }
@@ -7,17 +7,25 @@ use std::fmt;
 
 pub const DEGREES_TO_RADIANS : f32 = PI / 60.0;
 
+/// a full circle, in the same angle units DEGREES_TO_RADIANS converts from. the integer versions of the angle operations use these
+pub const DEGREES_PER_TURN : i32 = (TAU / DEGREES_TO_RADIANS + 0.5) as i32;
+
+/// the fixed point scale the integer versions of Sin, Cos and Tan give their answer in, so 1.0 is 16384
+pub const TRIG_INT_SCALE : f32 = 16384.0;
+
 pub const COMPARISON_EPSILON : f32 = 0.000001;
 
 pub trait Truthful {
     const TRUE: Self;
     const FALSE: Self;
     
+    #[expect(clippy::wrong_self_convention, reason = "everything truthful is Copy")]
     fn is_true(self) -> bool;
 }
 
 // want this to be distinct from regular rust From / Into infrastructure because it's not quite the same thing
 pub trait NumFromBool<T> where T : Truthful {
+    #[expect(clippy::wrong_self_convention, reason = "it converts self, like 1.from_bool()")]
     fn from_bool(self) -> T;
 }
 
@@ -57,11 +65,7 @@ impl Truthful for f32 {
     #[inline]
     fn is_true(self) -> bool
     {
-        if abs_diff(self,Self::FALSE) < COMPARISON_EPSILON {
-            false
-        } else {
-            true
-        }
+        abs_diff(self,Self::FALSE) >= COMPARISON_EPSILON
     }
 }
 
@@ -133,6 +137,168 @@ pub fn approx_cos(x : f32) -> f32 {
     x
 }
 
+/// Approximate tangent. right on the asymptotes (90 degrees and so on), where it would be infinity, gives 0
+pub fn approx_tan(x : f32) -> f32 {
+    let cos = approx_cos(x);
+    
+    if cos.abs() < COMPARISON_EPSILON {
+        0.0
+    } else {
+        approx_sin(x) / cos
+    }
+}
+
+/// Approximate arctangent of y / x, in radians from -pi to pi, like f32::atan2. 0, 0 gives 0.
+/// only basic arithmetic in here, for the same determinism reasons as approx_sin
+pub fn approx_atan2(y : f32, x : f32) -> f32 {
+    if x == 0.0 && y == 0.0 {
+        return 0.0;
+    }
+    
+    // keep the ratio between -1 and 1, where the polynomial is good
+    let is_swapped = y.abs() > x.abs();
+    let z = if is_swapped { x / y } else { y / x };
+    
+    // minimax polynomial for atan on -1 to 1, see https://mazzo.li/posts/vectorized-atan2.html
+    let z2 = z * z;
+    let mut atan = z * (0.99997726 + z2 * (-0.33262347 + z2 * (0.19354346 + z2 * (-0.11643287 + z2 * (0.05265332 + z2 * -0.0117212)))));
+    
+    if is_swapped {
+        atan = FRAC_PI_2.copysign(z) - atan;
+    }
+    
+    if x < 0.0 {
+        atan += PI.copysign(y);
+    }
+    
+    atan
+}
+
+/// Approximate 2 to the power of x. gives infinity when it's too big for a float, and 0 when it's too small to be a normal float
+pub fn approx_exp2(x : f32) -> f32 {
+    if x >= 128.0 {
+        return f32::INFINITY;
+    }
+    
+    if x.is_nan() || x < -126.0 {
+        return 0.0;
+    }
+    
+    let whole = x.floor();
+    let fraction = (x - whole) * LN_2;
+    
+    // taylor series of e^fraction, fraction is less than ln(2) so this is plenty
+    let mut power = 1.0 / 40320.0;
+    for divisor in [5040.0, 720.0, 120.0, 24.0, 6.0, 2.0, 1.0, 1.0] {
+        power = power * fraction + 1.0 / divisor;
+    }
+    
+    // 2^whole straight into the exponent bits
+    let scale = f32::from_bits(((whole as i32 + 127) as u32) << 23);
+    
+    power * scale
+}
+
+/// Approximate log base 2 of x. x has to be positive, anything else gives 0
+pub fn approx_log2(x : f32) -> f32 {
+    if x.is_nan() || x <= 0.0 || x.is_infinite() {
+        return 0.0;
+    }
+    
+    // subnormals don't have the hidden 1 bit, so make them normal first
+    let (x, exponent_offset) = if x < f32::MIN_POSITIVE { (x * const { (1 << 23) as f32 }, -23) } else { (x, 0) };
+    
+    let bits = x.to_bits();
+    let mut exponent = ((bits >> 23) & 0xFF) as i32 - 127 + exponent_offset;
+    let mut mantissa = f32::from_bits((bits & 0x007F_FFFF) | 0x3F80_0000);
+    
+    if mantissa > SQRT_2 {
+        mantissa *= 0.5;
+        exponent += 1;
+    }
+    
+    // ln(m) = 2 * atanh((m - 1) / (m + 1)), and that's small enough here for a few terms of the series
+    let t = (mantissa - 1.0) / (mantissa + 1.0);
+    let t2 = t * t;
+    let ln_mantissa = 2.0 * t * (1.0 + t2 * (1.0 / 3.0 + t2 * (1.0 / 5.0 + t2 * (1.0 / 7.0 + t2 * (1.0 / 9.0)))));
+    
+    exponent as f32 + ln_mantissa * LOG2_E
+}
+
+/// Approximate e to the power of x
+pub fn approx_exp(x : f32) -> f32 {
+    approx_exp2(x * LOG2_E)
+}
+
+/// Approximate natural log of x. x has to be positive, anything else gives 0
+pub fn approx_ln(x : f32) -> f32 {
+    approx_log2(x) * LN_2
+}
+
+/// base to the power of exponent.
+/// whole number exponents (up to 64) are done by multiplying, so small ones come out exact.
+/// a negative base with a fraction exponent works like SqrtWithNegative, -(|base| to the power of exponent).
+/// 0 to a negative power gives 0, like division by 0 does
+pub fn approx_pow(base : f32, exponent : f32) -> f32 {
+    if base == 0.0 {
+        return if exponent == 0.0 { 1.0 } else { 0.0 };
+    }
+    
+    if exponent.fract() == 0.0 && exponent.abs() <= 64.0 {
+        let mut remaining = exponent.abs() as u32;
+        let mut square = base;
+        let mut result = 1.0;
+        
+        while remaining > 0 {
+            if remaining & 1 == 1 {
+                result *= square;
+            }
+            square *= square;
+            remaining >>= 1;
+        }
+        
+        return if exponent < 0.0 { 1.0 / result } else { result };
+    }
+    
+    let magnitude = approx_exp2(exponent * approx_log2(base.abs()));
+    
+    if base < 0.0 {
+        -magnitude
+    } else {
+        magnitude
+    }
+}
+
+/// length of x, y without overflowing in the middle of it
+pub fn hypot(x : f32, y : f32) -> f32 {
+    let largest = x.abs().max(y.abs());
+    
+    if largest == 0.0 || largest.is_infinite() {
+        return largest;
+    }
+    
+    let x = x / largest;
+    let y = y / largest;
+    
+    largest * (x * x + y * y).sqrt()
+}
+
+/// wraps an angle in radians into -pi up to (not including) pi
+pub fn normalize_angle(x : f32) -> f32 {
+    let wrapped = x.rem_euclid(TAU);
+    
+    if wrapped >= PI {
+        wrapped - TAU
+    } else {
+        wrapped
+    }
+}
+
+/// floats that are too big become the biggest float there is, instead of infinity (which would become 0)
+pub fn saturate_float(float : f32) -> f32 {
+    float.clamp(f32::MIN, f32::MAX)
+}
+
 
 pub fn clean_float(float : f32) -> f32 {
     if float.is_finite() {
@@ -209,6 +375,7 @@ impl fmt::UpperHex for Number {
 }
 
 pub trait IntoNumber {
+    #[expect(clippy::wrong_self_convention, reason = "it's only implemented for Copy types")]
     fn into_number(&self) -> Number;
 }
 
@@ -0,0 +1,279 @@
+//! the last couple of seconds of a character's inputs, and checking them for motions like quarter circles (66_60)
+//!
+//! every tick records the character's inputs into their storage. the inputs the game gives us are already forward and
+//! backward instead of left and right, so a motion works the same on both sides. directions here use numpad notation,
+//! so 6 is forward, 2 is down, 3 is down-forward and 5 is neutral.
+
+#![deny(unsafe_op_in_unsafe_fn)]
+
+use std::io::Cursor;
+
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use num_derive::FromPrimitive;
+
+use crate::checksum::Checksum;
+use crate::snapshot::SnapshotError;
+
+/// how many frames of inputs are kept. two seconds
+pub const INPUT_HISTORY_LEN : usize = 120;
+
+const INPUT_FORWARD : u32 = 0x01;
+const INPUT_BACKWARD : u32 = 0x02;
+const INPUT_UP : u32 = 0x04;
+const INPUT_DOWN : u32 = 0x08;
+
+/// the first byte of 66_60
+#[derive(FromPrimitive, Clone, Copy, PartialEq, Eq, Debug)]
+#[repr(u8)]
+pub enum Motion
+{
+    /// 236
+    QuarterCircleForward = 0,
+    /// 214
+    QuarterCircleBack = 1,
+    /// 623
+    DragonPunch = 2,
+    /// forward, back, up and down in any order. diagonals count for both of their directions
+    FullCircle = 3,
+    /// back held for the charge time, then forward
+    ChargeBackForward = 4,
+    /// down held for the charge time, then up
+    ChargeDownUp = 5,
+    /// forward, let go of forward, forward again
+    DoubleTapForward = 6,
+    /// back, let go of back, back again
+    DoubleTapBack = 7,
+}
+
+const MOTIONS : [Motion; 8] = [
+    Motion::QuarterCircleForward,
+    Motion::QuarterCircleBack,
+    Motion::DragonPunch,
+    Motion::FullCircle,
+    Motion::ChargeBackForward,
+    Motion::ChargeDownUp,
+    Motion::DoubleTapForward,
+    Motion::DoubleTapBack,
+];
+
+impl Motion
+{
+    pub fn name(self) -> &'static str
+    {
+        match self {
+            Motion::QuarterCircleForward => "qcf",
+            Motion::QuarterCircleBack => "qcb",
+            Motion::DragonPunch => "dp",
+            Motion::FullCircle => "360",
+            Motion::ChargeBackForward => "charge.bf",
+            Motion::ChargeDownUp => "charge.du",
+            Motion::DoubleTapForward => "tap.f",
+            Motion::DoubleTapBack => "tap.b",
+        }
+    }
+    
+    pub fn from_name(name : &str) -> Option<Self>
+    {
+        MOTIONS.into_iter().find(|motion| name.eq_ignore_ascii_case(motion.name()))
+    }
+    
+    pub fn is_charge(self) -> bool
+    {
+        matches!(self, Motion::ChargeBackForward | Motion::ChargeDownUp)
+    }
+    
+    /// how many frames the motion gets to happen in, when 66_60 says 0
+    pub fn default_window(self) -> usize
+    {
+        match self {
+            Motion::FullCircle => 30,
+            Motion::ChargeBackForward | Motion::ChargeDownUp => 10,
+            _ => 15,
+        }
+    }
+}
+
+/// frames a charge has to be held for, when 66_60 says 0
+pub const DEFAULT_CHARGE : usize = 40;
+
+/// -1, 0 or 1 for back, neutral or forward, and the same for down, neutral or up. holding both ways is neutral
+fn axes(inputs : u32) -> (i32, i32)
+{
+    let axis = |positive : u32, negative : u32| {
+        match (inputs & positive != 0, inputs & negative != 0) {
+            (true, false) => 1,
+            (false, true) => -1,
+            _ => 0,
+        }
+    };
+    
+    (axis(INPUT_FORWARD, INPUT_BACKWARD), axis(INPUT_UP, INPUT_DOWN))
+}
+
+/// numpad notation
+fn numpad(inputs : u32) -> u8
+{
+    let (x, y) = axes(inputs);
+    
+    (5 + x + y * 3) as u8
+}
+
+pub struct InputHistory
+{
+    /// oldest first
+    frames : Vec<u32>,
+}
+
+impl InputHistory
+{
+    pub fn new() -> Self
+    {
+        Self {
+            frames : Vec::new(),
+        }
+    }
+    
+    pub fn is_empty(&self) -> bool
+    {
+        self.frames.is_empty()
+    }
+    
+    /// called once per tick with what the character is holding
+    pub fn record(&mut self, inputs : u32)
+    {
+        if self.frames.len() >= INPUT_HISTORY_LEN {
+            self.frames.remove(0);
+        }
+        
+        self.frames.push(inputs);
+    }
+    
+    /// the last `count` frames, oldest first
+    fn recent(&self, count : usize) -> &[u32]
+    {
+        &self.frames[self.frames.len().saturating_sub(count)..]
+    }
+    
+    /// whether `motion` was done within the last `window` frames. 0 for either uses the motion's default
+    pub fn matches(&self, motion : Motion, window : usize, charge : usize) -> bool
+    {
+        let window = if window == 0 { motion.default_window() } else { window };
+        let charge = if charge == 0 { DEFAULT_CHARGE } else { charge };
+        
+        match motion {
+            Motion::QuarterCircleForward => self.matches_sequence(window, &[2, 3, 6]),
+            Motion::QuarterCircleBack => self.matches_sequence(window, &[2, 1, 4]),
+            Motion::DragonPunch => self.matches_sequence(window, &[6, 2, 3]),
+            Motion::FullCircle => {
+                let seen = self.recent(window).iter().fold(0, |seen, inputs| {
+                    let (x, y) = axes(*inputs);
+                    
+                    seen | match x { 1 => INPUT_FORWARD, -1 => INPUT_BACKWARD, _ => 0 }
+                        | match y { 1 => INPUT_UP, -1 => INPUT_DOWN, _ => 0 }
+                });
+                
+                seen == INPUT_FORWARD | INPUT_BACKWARD | INPUT_UP | INPUT_DOWN
+            },
+            Motion::ChargeBackForward => self.matches_charge(window, charge, |inputs| axes(inputs).0),
+            Motion::ChargeDownUp => self.matches_charge(window, charge, |inputs| axes(inputs).1),
+            Motion::DoubleTapForward => self.matches_double_tap(window, 1),
+            Motion::DoubleTapBack => self.matches_double_tap(window, -1),
+        }
+    }
+    
+    /// each direction in order, with anything allowed in between
+    fn matches_sequence(&self, window : usize, directions : &[u8]) -> bool
+    {
+        let mut remaining = directions.iter().peekable();
+        
+        for inputs in self.recent(window) {
+            if remaining.peek() == Some(&&numpad(*inputs)) {
+                remaining.next();
+            }
+        }
+        
+        remaining.peek().is_none()
+    }
+    
+    /// `axis` gives -1 for the charging direction and 1 for the release. the release has to be within the window, and
+    /// the charge has to have ended no more than a window before it
+    fn matches_charge<F>(&self, window : usize, charge : usize, axis : F) -> bool
+        where F : Fn(u32) -> i32
+    {
+        let mut newest_first = self.frames.iter().rev().enumerate();
+        
+        let Some(release) = newest_first.by_ref().take(window).find(|(_, inputs)| axis(**inputs) == 1).map(|(age, _)| age) else {
+            return false;
+        };
+        
+        let mut held = 0;
+        
+        for (age, inputs) in newest_first {
+            if axis(*inputs) == -1 {
+                held += 1;
+                
+                if held >= charge {
+                    return true;
+                }
+            } else if held > 0 || age - release > window {
+                return false;
+            }
+        }
+        
+        false
+    }
+    
+    /// pressed, let go, and pressed again, along x
+    fn matches_double_tap(&self, window : usize, direction : i32) -> bool
+    {
+        let mut step = 0;
+        
+        for inputs in self.recent(window) {
+            let is_pressed = axes(*inputs).0 == direction;
+            
+            step = match (step, is_pressed) {
+                (0, true) => 1,
+                (1, false) => 2,
+                (2, true) => return true,
+                (step, _) => step,
+            };
+        }
+        
+        false
+    }
+    
+    pub fn checksum(&self, checksum : &mut Checksum)
+    {
+        checksum.write_u64(self.frames.len() as u64);
+        
+        for inputs in &self.frames {
+            checksum.write_u64(*inputs as u64);
+        }
+    }
+    
+    pub fn write_snapshot(&self, out : &mut Vec<u8>)
+    {
+        out.write_u32::<LittleEndian>(self.frames.len() as u32).unwrap();
+        
+        for inputs in &self.frames {
+            out.write_u32::<LittleEndian>(*inputs).unwrap();
+        }
+    }
+    
+    pub fn read_snapshot(cursor : &mut Cursor<&[u8]>) -> Result<Self, SnapshotError>
+    {
+        let count = cursor.read_u32::<LittleEndian>()? as usize;
+        
+        if count > INPUT_HISTORY_LEN {
+            return Err(SnapshotError::Invalid("input history length"));
+        }
+        
+        let mut frames = Vec::with_capacity(INPUT_HISTORY_LEN);
+        
+        for _ in 0..count {
+            frames.push(cursor.read_u32::<LittleEndian>()?);
+        }
+        
+        Ok(Self { frames })
+    }
+}
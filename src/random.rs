@@ -0,0 +1,90 @@
+//! random numbers for anmchr commands that come out the same every time.
+//!
+//! there's one generator for the whole match, started over from the same seed by `storage::reset_all`, so the same
+//! commands run in the same order roll the same numbers. that's what keeps replays and netplay from desyncing,
+//! so nothing in here should ever be seeded from the clock or anything else outside of the match.
+
+#![deny(unsafe_op_in_unsafe_fn)]
+
+use std::sync::Mutex;
+
+use crate::hook_helpers::lock;
+
+/// what the generator starts from every round, unless a character reseeds it
+pub const DEFAULT_SEED : u32 = 0x6D61_6770;
+
+static RANDOM : Mutex<Random> = Mutex::new(Random::new(DEFAULT_SEED));
+
+/// xorshift32. small, and the whole state fits in one register, so it can be saved and put back exactly
+struct Random
+{
+    state : u32,
+}
+
+impl Random
+{
+    const fn new(seed : u32) -> Self
+    {
+        // xorshift gets stuck on 0 forever
+        let state = if seed == 0 { DEFAULT_SEED } else { seed };
+        
+        Self {
+            state
+        }
+    }
+    
+    fn next_u32(&mut self) -> u32
+    {
+        let mut x = self.state;
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        self.state = x;
+        
+        x
+    }
+}
+
+/// called when a new match starts
+pub fn reset()
+{
+    *lock(&RANDOM) = Random::new(DEFAULT_SEED);
+}
+
+/// the whole state of the generator. setting this back with `set_state` repeats the same rolls again
+pub fn get_state() -> i32
+{
+    lock(&RANDOM).state as i32
+}
+
+/// 0 isn't a usable state, so it means `DEFAULT_SEED` instead
+pub fn set_state(seed : i32)
+{
+    *lock(&RANDOM) = Random::new(seed as u32);
+}
+
+pub fn next_u32() -> u32
+{
+    lock(&RANDOM).next_u32()
+}
+
+/// 0 up to but not including `n`. negative `n` goes the other way, so -6 gives -5 through 0. 0 always gives 0
+pub fn below_i32(n : i32) -> i32
+{
+    let roll = ((next_u32() as u64 * n.unsigned_abs() as u64) >> 32) as i32;
+    
+    if n < 0 {
+        -roll
+    } else {
+        roll
+    }
+}
+
+/// 0.0 up to `x`, or down to `x` if it's negative
+pub fn below_f32(x : f32) -> f32
+{
+    // 24 bits is all the precision an f32 has between 0 and 1
+    let fraction = (next_u32() >> 8) as f32 * const { 1.0 / (1 << 24) as f32 };
+    
+    fraction * x
+}
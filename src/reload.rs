@@ -3,7 +3,10 @@
 #![deny(unreachable_patterns)]
 
 use crate::storage;
+use crate::config::LogLevel;
+use crate::game_data::Char;
 use crate::hook_helpers::get_mut_cursor;
+use crate::command_error::CommandResult;
 use byteorder::{WriteBytesExt, ReadBytesExt, LittleEndian};
 use std::io::{Seek, SeekFrom};
 
@@ -48,7 +51,6 @@ pub fn save_anmchr_command(exe_ptr : usize, command_ptr : usize, command_type_gr
         None => return reload,
     };
     
-    #[cfg_attr(not(test), expect(unused_variables))]
     let command = match command {
         Some(c) => c,
         None => return reload,
@@ -60,19 +62,19 @@ pub fn save_anmchr_command(exe_ptr : usize, command_ptr : usize, command_type_gr
     
     match command_type_group {
         0 | 1 | 3 => {
-            storage::with(
+            let result = storage::with(
                 exe_ptr,
-                |store| {
-                    let len = cursor.read_u32::<LittleEndian>().unwrap();
+                |store| -> CommandResult {
+                    let len = cursor.read_u32::<LittleEndian>()?;
                     
                     let mut target_ptr = SIZE_U32 * (len + 2) as u64;
                     
                     // read in table of types
                     for table_index  in 0..len {
                         let seek_offset = SIZE_U32 * (2 + table_index as u64);
-                        cursor.seek(SeekFrom::Start(seek_offset)).unwrap();
+                        cursor.seek(SeekFrom::Start(seek_offset))?;
                         
-                        let value_type = cursor.read_u32::<LittleEndian>().unwrap();
+                        let value_type = cursor.read_u32::<LittleEndian>()?;
                         match value_type {
                             1 => {
                                 // 1 byte integer
@@ -83,27 +85,27 @@ pub fn save_anmchr_command(exe_ptr : usize, command_ptr : usize, command_type_gr
                                 target_ptr += SIZE_U32;
                             },
                             6 => {
-                                store.store_f32_for_reload(&mut reload, &mut cursor, target_ptr);
+                                store.store_f32_for_reload(&mut reload, &mut cursor, target_ptr)?;
                                 target_ptr += size_of::<f32>() as u64;
                             },
                             0xC => {
                                 // vector of 3
-                                store.store_f32_for_reload(&mut reload, &mut cursor, target_ptr);
+                                store.store_f32_for_reload(&mut reload, &mut cursor, target_ptr)?;
                                 target_ptr += size_of::<f32>() as u64;
-                                store.store_f32_for_reload(&mut reload, &mut cursor, target_ptr);
+                                store.store_f32_for_reload(&mut reload, &mut cursor, target_ptr)?;
                                 target_ptr += size_of::<f32>() as u64;
-                                store.store_f32_for_reload(&mut reload, &mut cursor, target_ptr);
+                                store.store_f32_for_reload(&mut reload, &mut cursor, target_ptr)?;
                                 target_ptr += size_of::<f32>() as u64;
                             },
                             0xD => {
                                 // vector of 4
-                                store.store_f32_for_reload(&mut reload, &mut cursor, target_ptr);
+                                store.store_f32_for_reload(&mut reload, &mut cursor, target_ptr)?;
                                 target_ptr += size_of::<f32>() as u64;
-                                store.store_f32_for_reload(&mut reload, &mut cursor, target_ptr);
+                                store.store_f32_for_reload(&mut reload, &mut cursor, target_ptr)?;
                                 target_ptr += size_of::<f32>() as u64;
-                                store.store_f32_for_reload(&mut reload, &mut cursor, target_ptr);
+                                store.store_f32_for_reload(&mut reload, &mut cursor, target_ptr)?;
                                 target_ptr += size_of::<f32>() as u64;
-                                store.store_f32_for_reload(&mut reload, &mut cursor, target_ptr);
+                                store.store_f32_for_reload(&mut reload, &mut cursor, target_ptr)?;
                                 target_ptr += size_of::<f32>() as u64;
                             },
                             0x10 | 0x07 => {
@@ -113,17 +115,22 @@ pub fn save_anmchr_command(exe_ptr : usize, command_ptr : usize, command_type_gr
                             // don't support anything else
                             _ => 
                             {
-                                #[cfg(test)]
-                                {
-                                    println!("unknown arg type {:02X} in command {:02X}_{:02X}", value_type, command_type_group, command);
-                                }
+                                crate::log!(LogLevel::Debug, Some(&Char::new(exe_ptr)),
+                                    "unknown arg type {:02X} in command {:02X}_{:02X}", value_type, command_type_group, command);
                                 
                                 break;
                             },
                         }
                     }
+                    
+                    Ok(())
                 }
             );
+            
+            // whatever was replaced before it went wrong still gets put back by `restore`
+            if let Err(error) = result {
+                crate::command_error::report(&Char::new(exe_ptr), command_type_group, command, &error);
+            }
         },
         _ => (),
     }
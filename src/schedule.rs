@@ -0,0 +1,200 @@
+//! running a command some frames later (66_23), and cancelling it (66_24)
+//!
+//! 66_23 takes the command right after it out of the command stream, so it doesn't run now, and keeps a copy of it in
+//! the character's storage. every tick counts it down, and when it gets to zero it runs as that character, no matter
+//! what animation they're in by then. so an effect that has to happen later still happens if the move gets interrupted.
+//!
+//! only the register and var operations (66_10 through 66_1E) can be scheduled. anything they read from a register
+//! is read when they run, not when they're scheduled. everything scheduled is dropped when the round restarts.
+
+#![deny(unsafe_op_in_unsafe_fn)]
+
+use std::io::Cursor;
+
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+
+use crate::anmchr_commands::AnoCmd;
+use crate::checksum::Checksum;
+use crate::game_data::Char;
+use crate::snapshot::SnapshotError;
+use crate::storage;
+
+/// more than this per character is almost certainly a repeat that's scheduling itself by accident
+pub const MAX_SCHEDULED : usize = 64;
+
+#[derive(Clone)]
+pub struct ScheduledCommand
+{
+    /// the number after the 0x66
+    command : u32,
+    /// everything after the 8 byte header
+    arguments : Box<[u8]>,
+    frames_left : u32,
+    /// 0 to run once, otherwise how many frames to wait before running it again
+    interval : u32,
+    tag : u32,
+}
+
+pub struct Schedule
+{
+    commands : Vec<ScheduledCommand>,
+}
+
+impl Schedule
+{
+    pub fn new() -> Self
+    {
+        Self {
+            commands : Vec::new(),
+        }
+    }
+    
+    pub fn is_empty(&self) -> bool
+    {
+        self.commands.is_empty()
+    }
+    
+    /// returns false if the schedule is full. a delay of 0 is the same as 1, since this frame's tick has already happened
+    pub fn add(&mut self, command : u32, arguments : &[u8], delay : u32, interval : u32, tag : u32) -> bool
+    {
+        if self.commands.len() >= MAX_SCHEDULED {
+            return false;
+        }
+        
+        self.commands.push(ScheduledCommand {
+            command,
+            arguments : arguments.into(),
+            frames_left : delay.max(1),
+            interval,
+            tag,
+        });
+        
+        true
+    }
+    
+    /// returns how many were cancelled
+    pub fn cancel(&mut self, tag : u32) -> usize
+    {
+        let before = self.commands.len();
+        
+        self.commands.retain(|scheduled| scheduled.tag != tag);
+        
+        before - self.commands.len()
+    }
+    
+    /// counts everything down a frame, and hands back the ones that are due, in the order they were scheduled
+    fn tick(&mut self) -> Vec<ScheduledCommand>
+    {
+        let mut due = Vec::new();
+        
+        self.commands.retain_mut(|scheduled| {
+            scheduled.frames_left -= 1;
+            
+            if scheduled.frames_left > 0 {
+                return true;
+            }
+            
+            due.push(scheduled.clone());
+            
+            if scheduled.interval == 0 {
+                false
+            } else {
+                scheduled.frames_left = scheduled.interval;
+                
+                true
+            }
+        });
+        
+        due
+    }
+    
+    pub fn checksum(&self, checksum : &mut Checksum)
+    {
+        checksum.write_u64(self.commands.len() as u64);
+        
+        for scheduled in &self.commands {
+            checksum.write_u64(scheduled.command as u64);
+            checksum.write(&scheduled.arguments);
+            checksum.write_u64(scheduled.frames_left as u64);
+            checksum.write_u64(scheduled.interval as u64);
+            checksum.write_u64(scheduled.tag as u64);
+        }
+    }
+    
+    pub fn write_snapshot(&self, out : &mut Vec<u8>)
+    {
+        out.write_u32::<LittleEndian>(self.commands.len() as u32).unwrap();
+        
+        for scheduled in &self.commands {
+            out.write_u32::<LittleEndian>(scheduled.command).unwrap();
+            out.write_u32::<LittleEndian>(scheduled.frames_left).unwrap();
+            out.write_u32::<LittleEndian>(scheduled.interval).unwrap();
+            out.write_u32::<LittleEndian>(scheduled.tag).unwrap();
+            out.write_u32::<LittleEndian>(scheduled.arguments.len() as u32).unwrap();
+            out.extend_from_slice(&scheduled.arguments);
+        }
+    }
+    
+    pub fn read_snapshot(cursor : &mut Cursor<&[u8]>) -> Result<Self, SnapshotError>
+    {
+        let count = cursor.read_u32::<LittleEndian>()? as usize;
+        
+        if count > MAX_SCHEDULED {
+            return Err(SnapshotError::Invalid("schedule length"));
+        }
+        
+        let mut commands = Vec::with_capacity(count);
+        
+        for _ in 0..count {
+            let command = cursor.read_u32::<LittleEndian>()?;
+            let frames_left = cursor.read_u32::<LittleEndian>()?;
+            let interval = cursor.read_u32::<LittleEndian>()?;
+            let tag = cursor.read_u32::<LittleEndian>()?;
+            let len = cursor.read_u32::<LittleEndian>()? as usize;
+            
+            let expected_len = num::FromPrimitive::from_u32(command)
+                .filter(|_| is_schedulable(command))
+                .map(|command : AnoCmd| command.argument_size());
+            
+            if expected_len != Some(len) {
+                return Err(SnapshotError::Invalid("scheduled command"));
+            }
+            
+            if frames_left == 0 {
+                return Err(SnapshotError::Invalid("schedule delay"));
+            }
+            
+            let mut arguments = vec![0; len].into_boxed_slice();
+            std::io::Read::read_exact(cursor, &mut arguments)?;
+            
+            commands.push(ScheduledCommand {
+                command,
+                arguments,
+                frames_left,
+                interval,
+                tag,
+            });
+        }
+        
+        Ok(Self { commands })
+    }
+}
+
+/// the register and var operations, 66_10 through 66_1E. anything that moves around in the command stream or keeps
+/// pointers around wouldn't make sense outside of one
+pub fn is_schedulable(command : u32) -> bool
+{
+    (0x10..=0x1E).contains(&command)
+}
+
+/// called once per character per tick. runs everything that's due as `owner`
+pub fn run_scheduled(owner : Char)
+{
+    let due = storage::with_no_make(owner.get_ptr(), |store| store.schedule.tick()).unwrap_or_default();
+    
+    // outside of storage::with, since the commands use storage themselves
+    // only 66 commands get scheduled, see `anmchr_commands::schedule_next_command`
+    for scheduled in due {
+        crate::anmchr_commands::run_ano_command(owner.clone(), 0x66, scheduled.command, scheduled.arguments.as_ptr() as usize);
+    }
+}
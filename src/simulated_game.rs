@@ -0,0 +1,321 @@
+//! A fake copy of the game's structs for tests, laid out at the same offsets game_data.rs reads from.
+//!
+//! Everything is allocated for real (and leaked, it's only for tests), so all the usual raw pointer reading and
+//! writing in game_data works unchanged. Installing it only swaps out the global roots in game_memory.rs for the
+//! current thread.
+
+#![deny(unsafe_op_in_unsafe_fn)]
+#![cfg(test)]
+
+use crate::hook_helpers::*;
+use crate::game_data::{Char, Player, Projectile, Team};
+use crate::game_memory::{self, GameMemory};
+use crate::match_state::MatchState;
+
+const MATCH_ACTION_SIZE : usize = 0x800;
+const CHAR_NODES_SIZE : usize = 0x400;
+const CHAR_NODE_SIZE : usize = 0x40;
+const CHAR_SIZE : usize = 0x4200;
+const PROJECTILE_LIST_ROOT_SIZE : usize = 0x20;
+const PROJECTILE_SIZE : usize = 0x2100;
+/// projectile pointers point at the list node in the middle of the struct, see PROJ_OFFSET in game_data.rs
+const PROJECTILE_NODE_OFFSET : usize = 0x1450;
+const SHOT_RESOURCE_SIZE : usize = 0x80;
+const SHOT_FILE_SIZE : usize = 0x10;
+const NAME_CAPACITY : usize = 64;
+const NAME_COUNT : usize = 0x40;
+
+const PLAYER1_OFFSET : usize = 0x350;
+const PLAYER2_OFFSET : usize = 0x610;
+
+/// the internal timer that shows as 99
+const FULL_TIMER : f32 = 180.0;
+
+#[derive(Clone, Copy, Debug)]
+pub struct SimulatedGame {
+    match_action : usize,
+    char_nodes : usize,
+    p1_projectile_root : usize,
+    p2_projectile_root : usize,
+    names : usize,
+}
+
+/// zeroed memory that never gets freed. 8 byte aligned like the game's allocations
+fn allocate(size : usize) -> usize
+{
+    let memory = vec![0u64; size.div_ceil(size_of::<u64>())].into_boxed_slice();
+    
+    Box::leak(memory).as_mut_ptr() as usize
+}
+
+impl SimulatedGame {
+    /// two empty teams in the middle of a match
+    pub fn new() -> Self
+    {
+        let game = Self {
+            match_action : allocate(MATCH_ACTION_SIZE),
+            char_nodes : allocate(CHAR_NODES_SIZE),
+            p1_projectile_root : allocate(PROJECTILE_LIST_ROOT_SIZE),
+            p2_projectile_root : allocate(PROJECTILE_LIST_ROOT_SIZE),
+            names : allocate(NAME_CAPACITY * NAME_COUNT),
+        };
+        
+        for root in [game.p1_projectile_root, game.p2_projectile_root] {
+            unsafe {
+                write_ptr(root + 0x08, root);
+                write_ptr(root + 0x10, root);
+            }
+        }
+        
+        game.set_match_state(MatchState::Fighting);
+        game.set_frame_time(100.0);
+        game.set_timer_raw(FULL_TIMER);
+        
+        game
+    }
+    
+    /// make this the game memory for the current thread
+    pub fn install(&self)
+    {
+        game_memory::set_simulated(Some(*self));
+    }
+    
+    pub fn uninstall()
+    {
+        game_memory::set_simulated(None);
+    }
+    
+    pub fn set_match_state(&self, state : MatchState)
+    {
+        unsafe { write_ptr(self.match_action + 0xD8, state as i32) };
+    }
+    
+    pub fn set_frame_time(&self, time : f32)
+    {
+        unsafe { write_ptr(self.match_action + 0xE4, time) };
+    }
+    
+    pub fn set_timer_raw(&self, time : f32)
+    {
+        unsafe { write_ptr(self.match_action + 0xF8, time) };
+    }
+    
+    fn player_ptr(&self, team : &Team) -> usize
+    {
+        match team {
+            Team::Player1 => self.match_action + PLAYER1_OFFSET,
+            Team::Player2 => self.match_action + PLAYER2_OFFSET,
+            Team::Unknown => panic!("simulated game only has player 1 and player 2"),
+        }
+    }
+    
+    pub fn player(&self, team : &Team) -> Player
+    {
+        Player::new(self.player_ptr(team))
+    }
+    
+    fn first_node_slot(&self, team : &Team) -> usize
+    {
+        match team {
+            Team::Player1 => self.char_nodes + 0x58,
+            Team::Player2 => self.char_nodes + 0x328,
+            Team::Unknown => panic!("simulated game only has player 1 and player 2"),
+        }
+    }
+    
+    /// adds a node for `character` to the end of the team's char node list, returning the node
+    fn link_node(&self, team : &Team, character : usize, parent_node : usize) -> usize
+    {
+        let node = allocate(CHAR_NODE_SIZE);
+        
+        unsafe {
+            write_ptr(node + 0x08, character);
+            write_ptr(node + 0x30, parent_node);
+        }
+        
+        let first = unsafe { read_usize(self.first_node_slot(team)) };
+        
+        if first == 0 {
+            unsafe {
+                write_ptr(self.first_node_slot(team), node);
+                write_ptr(self.player_ptr(team) + 0x18, node);
+            }
+        } else {
+            let mut last = first;
+            
+            loop {
+                let next = unsafe { read_usize(last + 0x10) };
+                
+                if next == 0 {
+                    break;
+                }
+                
+                last = next;
+            }
+            
+            unsafe {
+                write_ptr(last + 0x10, node);
+                write_ptr(node + 0x18, last);
+            }
+        }
+        
+        if parent_node != 0 && unsafe { read_usize(parent_node + 0x38) } == 0 {
+            unsafe { write_ptr(parent_node + 0x38, node) };
+        }
+        
+        node
+    }
+    
+    fn node_of(&self, character : &Char) -> usize
+    {
+        for team in [Team::Player1, Team::Player2] {
+            let mut node = unsafe { read_usize(self.first_node_slot(&team)) };
+            
+            while node != 0 {
+                if unsafe { read_usize(node + 0x08) } == character.get_ptr() {
+                    return node;
+                }
+                
+                node = unsafe { read_usize(node + 0x10) };
+            }
+        }
+        
+        panic!("{} isn't in the simulated game", character);
+    }
+    
+    /// adds a playable character. order 0 is point (and becomes the player's point character), 1 is assist 1, 2 is assist 2.
+    /// `id` is used as the clone engine id, so it needs to be above 0 for `get_char_id` to find it
+    pub fn add_character(&self, team : &Team, order : i32, id : i32) -> Char
+    {
+        let character = Char::new(allocate(CHAR_SIZE));
+        
+        character.set_char_order(order);
+        character.set_max_health_raw(1000000);
+        character.set_health_raw(1000000.0);
+        character.set_red_health_raw(1000000.0);
+        
+        unsafe { write_ptr(character.get_ptr() + 0x08, id) };
+        
+        self.link_node(team, character.get_ptr(), 0);
+        
+        if order == 0 {
+            unsafe { write_ptr(self.player_ptr(team) + 0x48, character.get_ptr()) };
+        }
+        
+        character
+    }
+    
+    /// adds a child character (like a summon) of `parent`
+    pub fn add_child(&self, team : &Team, parent : &Char) -> Char
+    {
+        let character = Char::new(allocate(CHAR_SIZE));
+        
+        character.set_char_order(-1);
+        
+        let parent_node = self.node_of(parent);
+        self.link_node(team, character.get_ptr(), parent_node);
+        
+        character
+    }
+    
+    pub fn set_facing_left(&self, character : &Char, is_left : bool)
+    {
+        let ptr = character.get_ptr() + 0x14FA;
+        let flags = unsafe { read_ptr_no_check::<u8>(ptr) };
+        let flags = if is_left { flags | 0x20 } else { flags & !0x20 };
+        
+        unsafe { write_ptr(ptr, flags) };
+    }
+    
+    /// the name the game would return for character `id`
+    pub fn set_character_name(&self, id : i32, name : &str)
+    {
+        let id = id as usize;
+        
+        assert!(id < NAME_COUNT && name.len() < NAME_CAPACITY);
+        
+        let ptr = self.names + id * NAME_CAPACITY;
+        
+        for (index, byte) in name.bytes().chain(std::iter::once(0)).enumerate() {
+            unsafe { write_ptr(ptr + index, byte) };
+        }
+    }
+    
+    /// spawns a projectile owned by `owner`. it becomes the newest projectile on the team's list
+    pub fn add_projectile(&self, team : &Team, owner : &Char, filename : &str, type_hash : i32) -> Projectile
+    {
+        let root = match team {
+            Team::Player1 => self.p1_projectile_root,
+            Team::Player2 => self.p2_projectile_root,
+            Team::Unknown => panic!("simulated game only has player 1 and player 2"),
+        };
+        
+        let shot_file = allocate(SHOT_FILE_SIZE);
+        let shot_resource = allocate(SHOT_RESOURCE_SIZE);
+        let node = allocate(PROJECTILE_SIZE) + PROJECTILE_NODE_OFFSET;
+        
+        assert!(filename.len() < 64);
+        
+        unsafe {
+            write_ptr(shot_file + 0x08, type_hash);
+            write_ptr(shot_resource + 0x78, shot_file);
+            
+            for (index, byte) in filename.bytes().enumerate() {
+                write_ptr(shot_resource + 0x0C + index, byte);
+            }
+        }
+        
+        let projectile = Projectile::if_valid(node, None, Some).unwrap();
+        
+        projectile.set_shot_resource_raw(shot_resource);
+        projectile.set_current_owner_raw(owner.get_ptr());
+        projectile.set_duration(-1.0);
+        
+        // newest first, so it goes right after the root
+        unsafe {
+            let old_first = read_usize(root + 0x08);
+            
+            write_ptr(node + 0x08, old_first);
+            write_ptr(node + 0x10, root);
+            write_ptr(old_first + 0x10, node);
+            write_ptr(root + 0x08, node);
+        }
+        
+        projectile
+    }
+}
+
+impl GameMemory for SimulatedGame {
+    fn match_action_ptr(&self) -> usize
+    {
+        self.match_action
+    }
+    
+    fn char_nodes_ptr(&self) -> usize
+    {
+        self.char_nodes
+    }
+    
+    fn p1_projectile_root(&self) -> usize
+    {
+        self.p1_projectile_root
+    }
+    
+    fn p2_projectile_root(&self) -> usize
+    {
+        self.p2_projectile_root
+    }
+    
+    fn get_char_id(&self, _char_ptr : usize) -> i32
+    {
+        // simulated characters all use the clone engine id slot instead
+        0
+    }
+    
+    fn get_character_name_ptr(&self, id : i32) -> usize
+    {
+        let id = if (0..NAME_COUNT as i32).contains(&id) { id as usize } else { 0 };
+        
+        self.names + id * NAME_CAPACITY
+    }
+}
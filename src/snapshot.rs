@@ -0,0 +1,198 @@
+//! saving the whole state of a CharStore as bytes, and loading it back (66_47 and 66_48).
+//!
+//! the format is little endian, and starts with a header so old snapshots can be told apart from new ones:
+//! - `MPCS` magic
+//! - u16 version, `SNAPSHOT_VERSION`
+//! - u16 sections, which of the optional parts below are there (see `Sections`)
+//! - then each section that's there, in the order of the bits:
+//!   bools as 32 bytes of bits, floats as 128 f32, ints as 128 i32, vectors as 32 pairs of f32,
+//!   persistence as 256 + 256 policy bytes and an i32 character id, the projectile filter, the scheduled
+//!   commands (see schedule.rs), the forces (see character_extensions.rs), and then the input history as a u32 count
+//!   and that many u32 inputs, oldest first (see motion.rs)
+//!
+//! version 1 had an f32 suck magnitude and an f32 suck delta between the header and the sections, and no forces.
+//!
+//! a section that isn't there means it was never used, which loads back the same as all zeroes.
+//! the projectile filter holds pointers into the game's memory, so a snapshot is only good for as long as the game
+//! has the same things loaded. that's fine for save states and rollback, which put the game's memory back too.
+//! the per-frame command flow (skips, jumps and calls) isn't saved, since it's all over by the end of the frame anyway.
+
+#![deny(unsafe_op_in_unsafe_fn)]
+
+use std::collections::HashMap;
+use std::fmt;
+use std::io::Cursor;
+use std::sync::{LazyLock, Mutex};
+
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+
+use crate::bitflag_getset;
+use crate::math::Number;
+use crate::hook_helpers::lock;
+
+pub const SNAPSHOT_MAGIC : [u8; 4] = *b"MPCS";
+
+/// goes up whenever the format changes. older versions are still read as long as we know how
+pub const SNAPSHOT_VERSION : u16 = 2;
+
+#[derive(Debug, PartialEq)]
+pub enum SnapshotError
+{
+    /// doesn't start with `SNAPSHOT_MAGIC`, so it isn't a snapshot at all
+    BadMagic,
+    /// made by a newer mag_patch than this one
+    UnsupportedVersion(u16),
+    /// ended before everything the header said would be there
+    Truncated,
+    /// something in it has a value that can't be right, like an unknown operation
+    Invalid(&'static str),
+    /// there's more after the end of the snapshot
+    TrailingBytes(usize),
+}
+
+impl std::error::Error for SnapshotError {}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SnapshotError::BadMagic => write!(f, "not a mag_patch snapshot"),
+            SnapshotError::UnsupportedVersion(version) => write!(f, "snapshot version {} is newer than this mag_patch supports ({})", version, SNAPSHOT_VERSION),
+            SnapshotError::Truncated => write!(f, "snapshot ends too early"),
+            SnapshotError::Invalid(what) => write!(f, "snapshot has an invalid {}", what),
+            SnapshotError::TrailingBytes(count) => write!(f, "snapshot has {} extra bytes at the end", count),
+        }
+    }
+}
+
+impl From<std::io::Error> for SnapshotError {
+    /// the only way reading from a slice fails is running out of bytes
+    fn from(_ : std::io::Error) -> Self
+    {
+        SnapshotError::Truncated
+    }
+}
+
+/// which of the optional sections are in a snapshot
+#[derive(Clone, Copy, Default)]
+#[repr(transparent)]
+pub struct Sections {
+    raw : u16,
+}
+
+impl Sections {
+    bitflag_getset!(0x01, has_bools, set_bools);
+    bitflag_getset!(0x02, has_floats, set_floats);
+    bitflag_getset!(0x04, has_ints, set_ints);
+    bitflag_getset!(0x08, has_vectors, set_vectors);
+    bitflag_getset!(0x10, has_persistence, set_persistence);
+    bitflag_getset!(0x20, has_projectile_filter, set_projectile_filter);
+    bitflag_getset!(0x40, has_schedule, set_schedule);
+    bitflag_getset!(0x80, has_forces, set_forces);
+    bitflag_getset!(0x100, has_input_history, set_input_history);
+    
+    const KNOWN : u16 = 0x1FF;
+}
+
+pub fn write_header(out : &mut Vec<u8>, sections : Sections)
+{
+    out.extend_from_slice(&SNAPSHOT_MAGIC);
+    out.write_u16::<LittleEndian>(SNAPSHOT_VERSION).unwrap();
+    out.write_u16::<LittleEndian>(sections.raw).unwrap();
+}
+
+/// the version and which sections are there
+pub fn read_header(cursor : &mut Cursor<&[u8]>) -> Result<(u16, Sections), SnapshotError>
+{
+    let mut magic = [0; 4];
+    
+    for byte in magic.iter_mut() {
+        *byte = cursor.read_u8().map_err(|_| SnapshotError::BadMagic)?;
+    }
+    
+    if magic != SNAPSHOT_MAGIC {
+        return Err(SnapshotError::BadMagic);
+    }
+    
+    let version = cursor.read_u16::<LittleEndian>()?;
+    
+    if version == 0 || version > SNAPSHOT_VERSION {
+        return Err(SnapshotError::UnsupportedVersion(version));
+    }
+    
+    let raw = cursor.read_u16::<LittleEndian>()?;
+    
+    if raw & !Sections::KNOWN != 0 {
+        return Err(SnapshotError::Invalid("section"));
+    }
+    
+    Ok((version, Sections { raw }))
+}
+
+/// errors if there's anything left after the snapshot
+pub fn finish(cursor : &Cursor<&[u8]>) -> Result<(), SnapshotError>
+{
+    let remaining = cursor.get_ref().len().saturating_sub(cursor.position() as usize);
+    
+    if remaining == 0 {
+        Ok(())
+    } else {
+        Err(SnapshotError::TrailingBytes(remaining))
+    }
+}
+
+pub fn write_number(out : &mut Vec<u8>, value : Number)
+{
+    match value {
+        Number::I32(value) => {
+            out.write_u8(0).unwrap();
+            out.write_i32::<LittleEndian>(value).unwrap();
+        },
+        Number::F32(value) => {
+            out.write_u8(1).unwrap();
+            out.write_f32::<LittleEndian>(value).unwrap();
+        },
+    }
+}
+
+pub fn read_number(cursor : &mut Cursor<&[u8]>) -> Result<Number, SnapshotError>
+{
+    match cursor.read_u8()? {
+        0 => Ok(Number::I32(cursor.read_i32::<LittleEndian>()?)),
+        1 => Ok(Number::F32(cursor.read_f32::<LittleEndian>()?)),
+        _ => Err(SnapshotError::Invalid("number type")),
+    }
+}
+
+/// pointers are always 8 bytes, with 0 for None
+pub fn write_ptr(out : &mut Vec<u8>, ptr : Option<usize>)
+{
+    out.write_u64::<LittleEndian>(ptr.unwrap_or(0) as u64).unwrap();
+}
+
+pub fn read_ptr(cursor : &mut Cursor<&[u8]>) -> Result<Option<usize>, SnapshotError>
+{
+    let ptr = usize::try_from(cursor.read_u64::<LittleEndian>()?)
+        .map_err(|_| SnapshotError::Invalid("pointer"))?;
+    
+    Ok(if ptr == 0 { None } else { Some(ptr) })
+}
+
+/// the character's pointer and the slot's name
+type SlotKey = (usize, Vec<u8>);
+
+/// named snapshot slots, by the character that saved them and the name. these aren't cleared when the round starts,
+/// since putting things back after a reset is the point of them
+static SLOTS : LazyLock<Mutex<HashMap<SlotKey, Vec<u8>>>> = LazyLock::new(|| {
+    Mutex::new(HashMap::new())
+});
+
+pub fn save_slot(character : usize, name : &[u8], snapshot : Vec<u8>)
+{
+    lock(&SLOTS).insert((character, name.to_vec()), snapshot);
+}
+
+/// a copy of the snapshot in the slot, if there's one
+pub fn load_slot(character : usize, name : &[u8]) -> Option<Vec<u8>>
+{
+    lock(&SLOTS).get(&(character, name.to_vec())).cloned()
+}
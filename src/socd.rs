@@ -0,0 +1,194 @@
+//! cleaning up opposite directions held at once (simultaneous opposing cardinal directions, or SOCD)
+//!
+//! a stick can't hold up and down at the same time, but a hitbox or a keyboard can, and the game doesn't expect it.
+//! up and down together lets a character crouch and jump at the same time, which is cheating and causes serious bugs.
+//! each axis gets its own mode, since tournament rule sets are usually something like up priority with left and right
+//! going neutral. the mode is picked when the dll loads, from mag_patch.ini (see config.rs).
+//!
+//! only up and down can be picked for now. left and right stay off until INPUT_RIGHT and INPUT_LEFT, and which player
+//! the input belongs to (see input_parse.rs), have been checked in game.
+
+#![deny(unsafe_op_in_unsafe_fn)]
+
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex};
+
+use crate::hook_helpers::lock;
+
+/// what the game's input parse gives back. up and down are certain
+const INPUT_UP : u32 = 0x04;
+const INPUT_DOWN : u32 = 0x08;
+// TODO - investigate more. we're assuming these are left and right the same way they're forward and back in the
+// character's inputs, which hasn't been checked. which is which doesn't matter here, since none of the modes prefer
+// one of them. until it's checked, mag_patch.ini can't turn on anything for left and right, see config.rs
+const INPUT_RIGHT : u32 = 0x01;
+const INPUT_LEFT : u32 = 0x02;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SocdMode
+{
+    /// whichever direction was pressed most recently
+    LastInputWins,
+    /// neither
+    Neutral,
+    /// whichever direction was already being held
+    FirstInputWins,
+    /// always up. there's nothing to prefer along x, so there this is the same as neutral
+    UpPriority,
+    /// both, the game gets them like it would without mag_patch
+    Off,
+}
+
+const MODES : [SocdMode; 5] = [
+    SocdMode::LastInputWins,
+    SocdMode::Neutral,
+    SocdMode::FirstInputWins,
+    SocdMode::UpPriority,
+    SocdMode::Off,
+];
+
+impl SocdMode
+{
+    pub fn name(self) -> &'static str
+    {
+        match self {
+            SocdMode::LastInputWins => "last",
+            SocdMode::Neutral => "neutral",
+            SocdMode::FirstInputWins => "first",
+            SocdMode::UpPriority => "up",
+            SocdMode::Off => "off",
+        }
+    }
+    
+    pub fn from_name(name : &str) -> Option<Self>
+    {
+        MODES.into_iter().find(|mode| name.eq_ignore_ascii_case(mode.name()))
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct SocdPolicy
+{
+    pub horizontal : SocdMode,
+    pub vertical : SocdMode,
+}
+
+impl SocdPolicy
+{
+    /// up and down has always been up. left and right are left alone like they always were, see INPUT_RIGHT
+    pub const DEFAULT : Self = Self {
+        horizontal : SocdMode::Off,
+        vertical : SocdMode::UpPriority,
+    };
+}
+
+/// a pair of opposite directions
+#[derive(Clone, Copy)]
+struct Axis
+{
+    /// the one up priority prefers
+    positive : u32,
+    negative : u32,
+}
+
+impl Axis
+{
+    const HORIZONTAL : Self = Self { positive : INPUT_RIGHT, negative : INPUT_LEFT };
+    const VERTICAL : Self = Self { positive : INPUT_UP, negative : INPUT_DOWN };
+    
+    fn bits(self) -> u32
+    {
+        self.positive | self.negative
+    }
+}
+
+/// last frame along one axis, for the modes that care about what happened first
+#[derive(Clone, Copy, Default)]
+struct AxisState
+{
+    /// what was actually held
+    held : u32,
+    /// what the game got after cleaning up
+    resolved : u32,
+}
+
+impl AxisState
+{
+    fn resolve(&mut self, mode : SocdMode, axis : Axis, inputs : u32) -> u32
+    {
+        let held = inputs & axis.bits();
+        
+        let resolved = if held != axis.bits() {
+            held
+        } else {
+            let pressed = held & !self.held;
+            
+            match mode {
+                SocdMode::Off => held,
+                SocdMode::Neutral => 0,
+                SocdMode::UpPriority if axis.positive == INPUT_UP => INPUT_UP,
+                SocdMode::UpPriority => 0,
+                // both pressed on the same frame, so there's no telling which came first
+                SocdMode::LastInputWins | SocdMode::FirstInputWins if pressed == held => 0,
+                // both were already held last frame, so keep whatever they came out as then
+                SocdMode::LastInputWins | SocdMode::FirstInputWins if pressed == 0 => self.resolved,
+                SocdMode::LastInputWins => pressed,
+                SocdMode::FirstInputWins => held & !pressed,
+            }
+        };
+        
+        self.held = held;
+        self.resolved = resolved;
+        
+        (inputs & !axis.bits()) | resolved
+    }
+}
+
+#[derive(Clone, Copy, Default)]
+struct PlayerState
+{
+    horizontal : AxisState,
+    vertical : AxisState,
+}
+
+pub struct SocdResolver
+{
+    policy : SocdPolicy,
+    players : HashMap<u32, PlayerState>,
+}
+
+impl SocdResolver
+{
+    pub fn new(policy : SocdPolicy) -> Self
+    {
+        Self {
+            policy,
+            players : HashMap::with_capacity(2),
+        }
+    }
+    
+    /// `inputs` with opposite directions cleaned up. `player` is anything that's different for each player
+    pub fn resolve(&mut self, player : u32, inputs : u32) -> u32
+    {
+        let state = self.players.entry(player).or_default();
+        
+        let inputs = state.horizontal.resolve(self.policy.horizontal, Axis::HORIZONTAL, inputs);
+        
+        state.vertical.resolve(self.policy.vertical, Axis::VERTICAL, inputs)
+    }
+}
+
+static RESOLVER : LazyLock<Mutex<SocdResolver>> = LazyLock::new(|| {
+    Mutex::new(SocdResolver::new(SocdPolicy::DEFAULT))
+});
+
+/// starts over with a new policy, forgetting what everyone was holding
+pub fn set_policy(policy : SocdPolicy)
+{
+    *lock(&RESOLVER) = SocdResolver::new(policy);
+}
+
+pub fn resolve(player : u32, inputs : u32) -> u32
+{
+    lock(&RESOLVER).resolve(player, inputs)
+}
@@ -9,15 +9,25 @@ use std::sync::{LazyLock, Mutex};
 use std::io::{Cursor, Seek, SeekFrom};
 
 use byteorder::{ReadBytesExt, WriteBytesExt, LittleEndian};
+use num_derive::FromPrimitive;
 
 
 use crate::character_extensions;
+use crate::command_flow;
 use crate::binary_operators;
 use crate::binary_operators::BinaryOpHandler;
 use crate::unary_operators;
-use crate::game_data::{Char};
+use crate::ternary_operators;
+use crate::ternary_operators::TernaryOpHandler;
+use crate::game_data::{Char, Team};
+use crate::vector::Vec2;
 use crate::reload::Reload;
-use crate::hook_helpers::read_ptr_no_check;
+use crate::snapshot;
+use crate::schedule;
+use crate::motion;
+use crate::checksum::Checksum;
+use crate::hook_helpers::{read_ptr_no_check, lock};
+use crate::command_error::CommandResult;
 use crate::math::*;
 use crate::bitflag_getset;
 use crate::math;
@@ -27,56 +37,172 @@ pub static CHAR_STORAGE : LazyLock<Mutex<HashMap<usize, CharStore>>> = LazyLock:
     Mutex::new(HashMap::with_capacity(64))
 });
 
-/// called when the round starts
-pub fn reset_all() {
-    let mut storage = CHAR_STORAGE.lock().unwrap();
+/// what kind of restart `reset_all` is being called for
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ResetKind
+{
+    /// a training mode reset, or anything else that starts the fight over without going through the intro
+    Round,
+    /// the intro of a new match
+    Match,
+}
+
+/// called when the round starts. registers that were set to last longer with 66_46 are kept, everything else is cleared
+pub fn reset_all(kind : ResetKind) {
+    let mut storage = lock(&CHAR_STORAGE);
+    
+    let kept : Vec<(usize, CharStore)> = storage.iter_mut()
+        .filter_map(|(key, store)| store.carry_over(kind).map(|store| (*key, store)))
+        .collect();
     
     storage.clear();
     
+    // the rolls carry on through training resets and new rounds, so the whole match is one sequence
+    if kind == ResetKind::Match {
+        crate::random::reset();
+    }
+    
+    lock(&SHARED_BANKS).reset();
+    
     if storage.capacity() > 512 {
         storage.shrink_to_fit();
         storage.reserve(64);
     }
+    
+    storage.extend(kept);
+}
+
+/// hash every store in an order that's the same on every machine, see checksum.rs
+pub fn checksum_all(checksum : &mut Checksum)
+{
+    let mut storage = lock(&CHAR_STORAGE);
+    
+    // stores that don't belong to a character on either team go after the ones that do, in order of what's in them
+    let mut hashes : Vec<(u8, usize, u64)> = storage.values_mut()
+        .map(|store| {
+            let (team, slot) = match store.character.team_slot() {
+                Some((Team::Player1, slot)) => (0, slot),
+                Some((Team::Player2, slot)) => (1, slot),
+                _ => (2, 0),
+            };
+            
+            (team, slot, store.checksum())
+        })
+        .collect();
+    
+    drop(storage);
+    
+    hashes.sort_unstable();
+    
+    for (team, slot, hash) in hashes {
+        checksum.write_u8(team);
+        checksum.write_u64(slot as u64);
+        checksum.write_u64(hash);
+    }
+    
+    let banks = lock(&SHARED_BANKS);
+    
+    for bank in [&banks.player1, &banks.player2, &banks.global] {
+        bank.checksum(checksum);
+    }
 }
 
 /// call to retrieve a new storage.
 /// note that if you call storage::with from inside itself, it will deadlock.
+/// a command that panicked while it was in here doesn't stop it from being used again, see `hook_helpers::lock`
 pub fn with<F, T>(key : usize, function : F) -> T
     where F : FnOnce(&mut CharStore) -> T
 {
-    let mut storage = CHAR_STORAGE.lock().unwrap();
-    
-    if !storage.contains_key(&key) {
-        storage.insert(key, CharStore::new(key));
-    }
+    let mut storage = lock(&CHAR_STORAGE);
     
-    let mut store = storage.get_mut(&key).unwrap();
+    let store = storage.entry(key).or_insert_with(|| CharStore::new(key));
     
-    let result = function(&mut store);
-    
-    result
+    function(store)
 }
 
 pub fn with_no_make<F,T>(key : usize, function : F) -> Option<T>
     where F : FnOnce(&mut CharStore) -> T
 {
-    let mut storage = CHAR_STORAGE.lock().unwrap();
-    
-    if !storage.contains_key(&key) {
-        return None;
-    }
-    
-    let mut store = storage.get_mut(&key).unwrap();
-    
-    let result = function(&mut store);
+    let mut storage = lock(&CHAR_STORAGE);
     
-    Some(result)
+    storage.get_mut(&key).map(function)
 }
 
 const REGISTER_COUNT : usize = 128;
 const BOOL_COUNT : usize = REGISTER_COUNT * 2;
 const DEFAULT_REGISTER_F32 : f32 = 0.0;
 const DEFAULT_REGISTER_I32 : i32 = 0;
+/// vector registers v00 through v1F. bigger numbers wrap around
+pub const VECTOR_COUNT : usize = 32;
+
+/// how long a register keeps its value, set with 66_46. every register starts out as ResetOnRound, unless
+/// mag_patch.ini says otherwise (see `PersistenceTable::with_defaults`)
+#[derive(FromPrimitive, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Default)]
+#[repr(u8)]
+pub enum Persistence
+{
+    /// cleared by every restart, including training mode resets. this is how registers always worked
+    #[default]
+    ResetOnRound = 0x00,
+    /// kept through training mode resets, but cleared when a new match starts
+    ResetOnMatch = 0x01,
+    /// kept until the character isn't loaded anymore, so it can carry over into the next match if the same character is still there
+    KeepUntilUnload = 0x02,
+}
+
+impl Persistence
+{
+    pub fn name(self) -> &'static str
+    {
+        match self {
+            Persistence::ResetOnRound => "round",
+            Persistence::ResetOnMatch => "match",
+            Persistence::KeepUntilUnload => "unload",
+        }
+    }
+    
+    /// look up a policy by name, ignoring case
+    pub fn from_name(name : &str) -> Option<Self>
+    {
+        [Persistence::ResetOnRound, Persistence::ResetOnMatch, Persistence::KeepUntilUnload]
+            .into_iter()
+            .find(|persistence| persistence.name().eq_ignore_ascii_case(name))
+    }
+    
+    fn survives(self, kind : ResetKind) -> bool
+    {
+        match kind {
+            ResetKind::Round => self >= Persistence::ResetOnMatch,
+            ResetKind::Match => self == Persistence::KeepUntilUnload,
+        }
+    }
+}
+
+/// the policies for every register of one character. only made once a character uses 66_46
+#[derive(Clone)]
+struct PersistenceTable
+{
+    numbers : [Persistence; REGISTER_COUNT * 2],
+    bools : [Persistence; BOOL_COUNT],
+    /// which character it was when the policies were set, so a different character loaded into the same spot doesn't
+    /// get the old one's registers
+    char_id : i32,
+}
+
+impl PersistenceTable
+{
+    /// what every register is before 66_46 touches it
+    fn with_defaults(char_id : i32) -> Self
+    {
+        let defaults = crate::config::get().persistence;
+        
+        Self {
+            numbers : [defaults.registers; REGISTER_COUNT * 2],
+            bools : [defaults.bools; BOOL_COUNT],
+            char_id,
+        }
+    }
+}
 
 pub struct CharStore
 {
@@ -85,10 +211,18 @@ pub struct CharStore
     bools : Option<Box<[bool; BOOL_COUNT]>>,
     floats : Option<Box<[f32; REGISTER_COUNT]>>,
     ints : Option<Box<[i32; REGISTER_COUNT]>>,
+    vectors : Option<Box<[Vec2; VECTOR_COUNT]>>,
+    persistence : Option<Box<PersistenceTable>>,
     
-    pub suck_opponent : character_extensions::SuckOpponent,
+    pub forces : character_extensions::Forces,
     
     pub projectile_filter : Option<crate::game_data::ProjectileFilter>,
+    
+    pub command_flow : command_flow::CommandFlow,
+    
+    pub schedule : schedule::Schedule,
+    
+    pub input_history : motion::InputHistory,
 }
 
 impl CharStore {
@@ -98,18 +232,20 @@ impl CharStore {
             bools : None,
             floats : None,
             ints : None,
-            suck_opponent : character_extensions::SuckOpponent {
-                magnitude : 0.0,
-                delta : 0.0,
-            },
+            vectors : None,
+            persistence : None,
+            forces : character_extensions::Forces::new(),
             projectile_filter : None,
+            command_flow : command_flow::CommandFlow::new(),
+            schedule : schedule::Schedule::new(),
+            input_history : motion::InputHistory::new(),
         }
     }
     
     pub fn set_f32_register(&mut self, index : u8, value : f32) {
         let index = index & F32_REGISTER_UNMASK;
         
-        if self.floats == None {
+        if self.floats.is_none() {
             self.floats = Some(Box::new([DEFAULT_REGISTER_F32; REGISTER_COUNT]));
         }
         
@@ -121,7 +257,7 @@ impl CharStore {
     pub fn set_i32_register(&mut self, index : u8, value : i32) {
         let index = index & F32_REGISTER_UNMASK;
         
-        if self.ints == None {
+        if self.ints.is_none() {
             self.ints = Some(Box::new([DEFAULT_REGISTER_I32; REGISTER_COUNT]));
         }
         
@@ -141,8 +277,8 @@ impl CharStore {
     
     pub fn set_bool(&mut self, index : u8, value : bool)
     {
-        if self.bools == None {
-            if value == false {
+        if self.bools.is_none() {
+            if !value {
                 // early out, since getting from an empty list defaults to false anyway
                 return;
             }
@@ -163,6 +299,82 @@ impl CharStore {
         }
     }
     
+    pub fn set_vector(&mut self, index : u8, value : Vec2)
+    {
+        let list = self.vectors.get_or_insert_with(|| Box::new([Vec2::ZERO; VECTOR_COUNT]));
+        
+        list[index as usize % VECTOR_COUNT] = value.clean();
+    }
+    
+    pub fn get_vector(&mut self, index : u8) -> Vec2
+    {
+        match &self.vectors {
+            Some(list) => list[index as usize % VECTOR_COUNT],
+            None => Vec2::ZERO,
+        }
+    }
+    
+    /// set the policy for registers `first` through `last`, including both. `char_id` is the character that's setting it
+    pub fn set_persistence(&mut self, first : u8, last : u8, is_bool : bool, persistence : Persistence, char_id : i32)
+    {
+        let (first, last) = if first <= last { (first, last) } else { (last, first) };
+        
+        let table = self.persistence.get_or_insert_with(|| Box::new(PersistenceTable::with_defaults(char_id)));
+        
+        table.char_id = char_id;
+        
+        let list = if is_bool { &mut table.bools } else { &mut table.numbers };
+        
+        list[first as usize..=last as usize].fill(persistence);
+    }
+    
+    /// a new store with only the registers that survive this kind of reset, or None if there's nothing to keep.
+    /// the policies themselves are kept too, so they only have to be set once
+    pub fn carry_over(&mut self, kind : ResetKind) -> Option<CharStore>
+    {
+        // without 66_46, everything still follows the defaults
+        let table = match &self.persistence {
+            Some(table) => table.clone(),
+            None => {
+                let defaults = crate::config::get().persistence;
+                
+                if defaults.registers == Persistence::default() && defaults.bools == Persistence::default() {
+                    return None;
+                }
+                
+                Box::new(PersistenceTable::with_defaults(self.character.get_char_id()))
+            },
+        };
+        
+        if kind == ResetKind::Match {
+            // the character's gone (or something else got loaded where it was), so there's nothing to carry over to
+            if self.character.identify_team() == Team::Unknown || self.character.get_char_id() != table.char_id {
+                return None;
+            }
+        }
+        
+        if !table.numbers.iter().chain(table.bools.iter()).any(|persistence| persistence.survives(kind)) {
+            return None;
+        }
+        
+        let mut kept = CharStore::new(self.character.get_ptr());
+        
+        for index in 0..=u8::MAX {
+            if table.numbers[index as usize].survives(kind) {
+                kept.set_number_register(index, self.get_number_register(index));
+            }
+            
+            if table.bools[index as usize].survives(kind) {
+                kept.set_bool(index, self.get_bool(index));
+            }
+        }
+        
+        // only what 66_46 set, so a store that only has the defaults looks the same as it did before the reset
+        kept.persistence = self.persistence.clone();
+        
+        Some(kept)
+    }
+    
     pub fn get_f32_register(&mut self, index : u8) -> f32 {
         if index & F32_REGISTER_MASK == F32_REGISTER_MASK {
             let index = index & F32_REGISTER_UNMASK;
@@ -209,19 +421,17 @@ impl CharStore {
     
     pub fn resolve_indirect_register(&mut self, index : u8, is_indirect : bool) -> u8 {
         if is_indirect {
-            let index = (self.get_number_register(index).into_int() & 0xFF) as u8;
-            
-            index
+            (self.get_number_register(index).into_int() & 0xFF) as u8
         } else {
             index
         }
     }
     
-    pub fn read_into_register(&mut self, destination : u8, cursor : &mut Cursor<&'static [u8]>, register_flags : RegisterFlags)
+    pub fn read_into_register(&mut self, destination : u8, cursor : &mut Cursor<&'static [u8]>, register_flags : RegisterFlags) -> CommandResult
     {
         if register_flags.is_destination_bool()
         {
-            let value = cursor.read_i32::<LittleEndian>().unwrap();
+            let value = cursor.read_i32::<LittleEndian>()?;
             self.set_bool(destination, value.is_true());
             self.character.set_condition_register(value);
         }
@@ -229,18 +439,20 @@ impl CharStore {
             match RegisterType::identify(destination)
             {
                 RegisterType::F32 => {
-                    let immediate = self.cursor_read_f32_with_replacement(cursor);
+                    let immediate = self.cursor_read_f32_with_replacement(cursor)?;
                     self.set_f32_register(destination, immediate);
                     self.character.set_condition_register(immediate as i32);
                 },
                 RegisterType::I32 => {
-                    let immediate = cursor.read_i32::<LittleEndian>().unwrap();
+                    let immediate = cursor.read_i32::<LittleEndian>()?;
                     self.set_i32_register(destination, immediate);
                     self.character.set_condition_register(immediate);
                 },
                 RegisterType::Bool => unreachable!(),
             }
         }
+        
+        Ok(())
     }
     
     pub fn register_unary_operation(&mut self, source : u8, destination : u8, operation : unary_operators::UnaryOp, register_flags : RegisterFlags)
@@ -674,9 +886,45 @@ impl CharStore {
             },
         };
     }
+    
+    /// a register as an operand, with bools as 0 or 1
+    pub fn get_operand(&mut self, index : u8, is_bool : bool) -> Number
+    {
+        if is_bool {
+            Number::I32(self.get_bool(index).from_bool())
+        } else {
+            self.get_number_register(index)
+        }
+    }
+    
+    /// i32 math if all three operands are integers, otherwise f32 math. then it's converted to fit the destination
+    pub fn ternary_operation(&mut self, first : Number, second : Number, third : Number, destination : u8, operation : ternary_operators::TernaryOp, register_flags : RegisterFlags)
+    {
+        let result : Number = operation.operate(first, second, third);
+        
+        let dtype = if register_flags.is_destination_bool() {
+            RegisterType::Bool
+        } else {
+            RegisterType::identify(destination)
+        };
+        
+        match dtype
+        {
+            RegisterType::F32 => self.set_f32_register(destination, result.into_float()),
+            RegisterType::I32 => self.set_i32_register(destination, result.into_int()),
+            RegisterType::Bool => self.set_bool(destination, result.is_true()),
+        };
+        
+        let condition = match dtype {
+            RegisterType::Bool => result.bool_roundtrip().into_int(),
+            RegisterType::I32 | RegisterType::F32 => result.into_int(),
+        };
+        
+        self.character.set_condition_register(condition);
+    }
 }
 
-#[derive(PartialEq, Debug)]
+#[derive(PartialEq, Debug, Clone, Copy)]
 pub enum RegisterType {
     I32,
     F32,
@@ -699,6 +947,180 @@ impl RegisterType
 }
 
 
+/// which bank 66_44 and 66_45 use, the first byte of the `LLRRFFDD` line
+#[derive(FromPrimitive, Clone, Copy, PartialEq, Eq, Debug)]
+#[repr(u8)]
+pub enum SharedBankSelect
+{
+    /// shared by everyone on the same team as the character running the command, including assists and children
+    MyTeam = 0x00,
+    /// the other team's bank, so you can read (or mess with) what they've set
+    OpposingTeam = 0x01,
+    /// one bank for the whole match, shared by both teams
+    Match = 0x02,
+}
+
+impl SharedBankSelect
+{
+    pub fn name(self) -> &'static str
+    {
+        match self {
+            SharedBankSelect::MyTeam => "team",
+            SharedBankSelect::OpposingTeam => "opponent",
+            SharedBankSelect::Match => "match",
+        }
+    }
+    
+    /// look up a bank by name, ignoring case
+    pub fn from_name(name : &str) -> Option<Self>
+    {
+        [SharedBankSelect::MyTeam, SharedBankSelect::OpposingTeam, SharedBankSelect::Match]
+            .into_iter()
+            .find(|bank| bank.name().eq_ignore_ascii_case(name))
+    }
+}
+
+/// registers that don't belong to any one character. works like the registers in a CharStore, so 00-7F are integers,
+/// 80-FF are floats, and there's 256 bools besides
+pub struct RegisterBank
+{
+    bools : Option<Box<[bool; BOOL_COUNT]>>,
+    floats : Option<Box<[f32; REGISTER_COUNT]>>,
+    ints : Option<Box<[i32; REGISTER_COUNT]>>,
+}
+
+impl RegisterBank
+{
+    const fn new() -> Self
+    {
+        Self {
+            bools : None,
+            floats : None,
+            ints : None,
+        }
+    }
+    
+    pub fn get_number(&self, index : u8) -> Number
+    {
+        let unmasked = (index & F32_REGISTER_UNMASK) as usize;
+        
+        match RegisterType::identify(index) {
+            RegisterType::F32 => Number::F32(self.floats.as_ref().map_or(DEFAULT_REGISTER_F32, |list| list[unmasked])),
+            RegisterType::I32 => Number::I32(self.ints.as_ref().map_or(DEFAULT_REGISTER_I32, |list| list[unmasked])),
+            RegisterType::Bool => unreachable!(),
+        }
+    }
+    
+    pub fn set_number(&mut self, index : u8, value : Number)
+    {
+        let unmasked = (index & F32_REGISTER_UNMASK) as usize;
+        
+        match RegisterType::identify(index) {
+            RegisterType::F32 => {
+                self.floats.get_or_insert_with(|| Box::new([DEFAULT_REGISTER_F32; REGISTER_COUNT]))[unmasked] = value.into_float();
+            },
+            RegisterType::I32 => {
+                self.ints.get_or_insert_with(|| Box::new([DEFAULT_REGISTER_I32; REGISTER_COUNT]))[unmasked] = value.into_int();
+            },
+            RegisterType::Bool => unreachable!(),
+        }
+    }
+    
+    pub fn get_bool(&self, index : u8) -> bool
+    {
+        self.bools.as_ref().is_some_and(|list| list[index as usize])
+    }
+    
+    pub fn set_bool(&mut self, index : u8, value : bool)
+    {
+        if self.bools.is_none() && !value {
+            // same early out as CharStore::set_bool
+            return;
+        }
+        
+        self.bools.get_or_insert_with(|| Box::new([false; BOOL_COUNT]))[index as usize] = value;
+    }
+    
+    fn checksum(&self, checksum : &mut Checksum)
+    {
+        for index in 0..=u8::MAX {
+            match self.get_number(index) {
+                Number::I32(value) => checksum.write_i32(value),
+                Number::F32(value) => checksum.write_f32(value),
+            }
+            
+            checksum.write_bool(self.get_bool(index));
+        }
+    }
+    
+    /// a register as a Number, with bools as 0 or 1
+    pub fn get_operand(&self, index : u8, is_bool : bool) -> Number
+    {
+        if is_bool {
+            Number::I32(self.get_bool(index).from_bool())
+        } else {
+            self.get_number(index)
+        }
+    }
+    
+    /// converts the value to fit the register, the same way the other register commands do
+    pub fn set_operand(&mut self, index : u8, is_bool : bool, value : Number)
+    {
+        if is_bool {
+            self.set_bool(index, value.is_true());
+        } else {
+            self.set_number(index, value);
+        }
+    }
+}
+
+struct SharedBanks
+{
+    player1 : RegisterBank,
+    player2 : RegisterBank,
+    global : RegisterBank,
+}
+
+impl SharedBanks
+{
+    fn reset(&mut self)
+    {
+        *self = Self::new();
+    }
+    
+    const fn new() -> Self
+    {
+        Self {
+            player1 : RegisterBank::new(),
+            player2 : RegisterBank::new(),
+            global : RegisterBank::new(),
+        }
+    }
+}
+
+/// the team and match banks. cleared along with CHAR_STORAGE when the round starts
+static SHARED_BANKS : Mutex<SharedBanks> = Mutex::new(SharedBanks::new());
+
+/// run `function` on a shared bank, as seen by a character on `team`. gives back `default` if the bank is a team bank
+/// and the team couldn't be figured out.
+/// this locks its own mutex, so it's fine to call from inside storage::with, just not the other way around
+pub fn with_shared_bank<F, T>(team : Team, bank : SharedBankSelect, default : T, function : F) -> T
+    where F : FnOnce(&mut RegisterBank) -> T
+{
+    let mut banks = lock(&SHARED_BANKS);
+    
+    let team = match bank {
+        SharedBankSelect::MyTeam => team,
+        SharedBankSelect::OpposingTeam => team.opposite(),
+        SharedBankSelect::Match => return function(&mut banks.global),
+    };
+    
+    match team {
+        Team::Player1 => function(&mut banks.player1),
+        Team::Player2 => function(&mut banks.player2),
+        Team::Unknown => default,
+    }
+}
 
 impl CharStore
 {
@@ -711,57 +1133,58 @@ impl CharStore
         if (value & Self::F32_RELOAD_MASK) != Self::F32_RELOAD_MASK {
             f32::from_bits(value)
         } else {
-            let register_index : u8 = (value & 0xFF).try_into().unwrap();
+            let register_index = (value & 0xFF) as u8;
             
             self.get_f32_register(register_index)
         }
     }
     
-    pub fn cursor_read_f32_with_replacement(&mut self,  cursor : &mut Cursor<&'static [u8]>) -> f32
+    pub fn cursor_read_f32_with_replacement(&mut self,  cursor : &mut Cursor<&'static [u8]>) -> CommandResult<f32>
     {
-        let value = cursor.read_u32::<LittleEndian>().unwrap();
+        let value = cursor.read_u32::<LittleEndian>()?;
         
         if (value & Self::F32_RELOAD_MASK) != Self::F32_RELOAD_MASK {
-            f32::from_bits(value)
+            Ok(f32::from_bits(value))
         } else {
-            let register_index : u8 = (value & 0xFF).try_into().unwrap();
+            let register_index = (value & 0xFF) as u8;
             
-            self.get_f32_register(register_index)
+            Ok(self.get_f32_register(register_index))
         }
     }
     
-    pub fn cursor_read_u32_with_replacement(&mut self,  cursor : &mut Cursor<&'static [u8]>) -> u32
+    pub fn cursor_read_u32_with_replacement(&mut self,  cursor : &mut Cursor<&'static [u8]>) -> CommandResult<u32>
     {
-        let value = cursor.read_u32::<LittleEndian>().unwrap();
+        let value = cursor.read_u32::<LittleEndian>()?;
         
         if (value & Self::F32_RELOAD_MASK) != Self::F32_RELOAD_MASK {
-            value
+            Ok(value)
         } else {
-            let register_index : u8 = (value & 0xFF).try_into().unwrap();
+            let register_index = (value & 0xFF) as u8;
             
-            self.get_i32_register(register_index) as u32
+            Ok(self.get_i32_register(register_index) as u32)
         }
     }
     
-    pub fn store_f32_for_reload(&mut self, reload : &mut Reload, cursor : &mut Cursor<&'static mut [u8]>, offset : u64)
+    pub fn store_f32_for_reload(&mut self, reload : &mut Reload, cursor : &mut Cursor<&'static mut [u8]>, offset : u64) -> CommandResult
     {
-        cursor.seek(SeekFrom::Start(offset)).unwrap();
+        cursor.seek(SeekFrom::Start(offset))?;
         
-        let saved = cursor.read_u32::<LittleEndian>().unwrap();
+        let saved = cursor.read_u32::<LittleEndian>()?;
         
         if (saved & Self::F32_RELOAD_MASK) != Self::F32_RELOAD_MASK {
             // early out because nothing to replace
-            return;
+            return Ok(());
         }
         
         let to_save = (offset, saved);
         
-        let register_index : u8 = (saved & 0xFF).try_into().unwrap();
+        let register_index = (saved & 0xFF) as u8;
         
         let replacement_value = self.get_f32_register(register_index).to_bits();
         
-        cursor.seek(SeekFrom::Start(offset)).unwrap();
-        cursor.write_u32::<LittleEndian>(replacement_value).unwrap();
+        // the read above already made sure these 4 bytes are there
+        cursor.seek(SeekFrom::Start(offset))?;
+        cursor.write_u32::<LittleEndian>(replacement_value)?;
         
         match &mut reload.original_values {
             None => {
@@ -775,6 +1198,230 @@ impl CharStore
                 original_values.push(to_save);
             },
         };
+        
+        Ok(())
+    }
+}
+
+
+impl CharStore
+{
+    /// a hash of everything in this store that's the same on every machine, so no pointers. registers that were never
+    /// set hash the same as ones set to 0
+    pub fn checksum(&mut self) -> u64
+    {
+        let mut checksum = Checksum::new();
+        
+        for index in 0..=u8::MAX {
+            match self.get_number_register(index) {
+                Number::I32(value) => checksum.write_i32(value),
+                Number::F32(value) => checksum.write_f32(value),
+            }
+            
+            checksum.write_bool(self.get_bool(index));
+            checksum.write_u8(self.persistence.as_ref().map_or(0, |table| table.numbers[index as usize] as u8));
+            checksum.write_u8(self.persistence.as_ref().map_or(0, |table| table.bools[index as usize] as u8));
+        }
+        
+        for index in 0..VECTOR_COUNT as u8 {
+            let vector = self.get_vector(index);
+            
+            checksum.write_f32(vector.x);
+            checksum.write_f32(vector.y);
+        }
+        
+        let filter = self.projectile_filter.as_ref();
+        checksum.write_bool(filter.is_some());
+        checksum.write_bool(filter.is_some_and(|filter| filter.projectile.is_some()));
+        
+        self.forces.checksum(&mut checksum);
+        self.schedule.checksum(&mut checksum);
+        self.input_history.checksum(&mut checksum);
+        
+        checksum.finish()
+    }
+    
+    /// everything in this store as bytes, see snapshot.rs for the format
+    pub fn save_snapshot(&self) -> Vec<u8>
+    {
+        let sections = snapshot::Sections::default()
+            .set_bools(self.bools.is_some())
+            .set_floats(self.floats.is_some())
+            .set_ints(self.ints.is_some())
+            .set_vectors(self.vectors.is_some())
+            .set_persistence(self.persistence.is_some())
+            .set_projectile_filter(self.projectile_filter.is_some())
+            .set_schedule(!self.schedule.is_empty())
+            .set_forces(!self.forces.is_empty())
+            .set_input_history(!self.input_history.is_empty());
+        
+        let mut out = Vec::with_capacity(64);
+        
+        snapshot::write_header(&mut out, sections);
+        
+        if let Some(bools) = &self.bools {
+            for chunk in bools.chunks(8) {
+                let byte = chunk.iter().enumerate().fold(0u8, |byte, (bit, value)| byte | ((*value as u8) << bit));
+                
+                out.write_u8(byte).unwrap();
+            }
+        }
+        
+        if let Some(floats) = &self.floats {
+            floats.iter().for_each(|value| out.write_f32::<LittleEndian>(*value).unwrap());
+        }
+        
+        if let Some(ints) = &self.ints {
+            ints.iter().for_each(|value| out.write_i32::<LittleEndian>(*value).unwrap());
+        }
+        
+        if let Some(vectors) = &self.vectors {
+            for vector in vectors.iter() {
+                out.write_f32::<LittleEndian>(vector.x).unwrap();
+                out.write_f32::<LittleEndian>(vector.y).unwrap();
+            }
+        }
+        
+        if let Some(table) = &self.persistence {
+            table.numbers.iter().chain(table.bools.iter()).for_each(|persistence| out.write_u8(*persistence as u8).unwrap());
+            out.write_i32::<LittleEndian>(table.char_id).unwrap();
+        }
+        
+        if let Some(filter) = &self.projectile_filter {
+            filter.write_snapshot(&mut out);
+        }
+        
+        if !self.schedule.is_empty() {
+            self.schedule.write_snapshot(&mut out);
+        }
+        
+        if !self.forces.is_empty() {
+            self.forces.write_snapshot(&mut out);
+        }
+        
+        if !self.input_history.is_empty() {
+            self.input_history.write_snapshot(&mut out);
+        }
+        
+        out
+    }
+    
+    /// replace everything in this store with a snapshot from `save_snapshot`. if it fails, nothing is changed
+    pub fn load_snapshot(&mut self, bytes : &[u8]) -> Result<(), snapshot::SnapshotError>
+    {
+        let mut cursor = Cursor::new(bytes);
+        let (version, sections) = snapshot::read_header(&mut cursor)?;
+        
+        // version 1 always had 66_50's suck right after the header, before forces could do more than that
+        let version_1_suck = if version == 1 {
+            Some(character_extensions::Forces::from_version_1_suck(
+                cursor.read_f32::<LittleEndian>()?,
+                cursor.read_f32::<LittleEndian>()?))
+        } else {
+            None
+        };
+        
+        let bools = if sections.has_bools() {
+            let mut bools = Box::new([false; BOOL_COUNT]);
+            
+            for chunk in bools.chunks_mut(8) {
+                let byte = cursor.read_u8()?;
+                
+                for (bit, value) in chunk.iter_mut().enumerate() {
+                    *value = (byte >> bit) & 1 == 1;
+                }
+            }
+            
+            Some(bools)
+        } else {
+            None
+        };
+        
+        let floats = if sections.has_floats() {
+            let mut floats = Box::new([DEFAULT_REGISTER_F32; REGISTER_COUNT]);
+            cursor.read_f32_into::<LittleEndian>(&mut floats[..])?;
+            
+            Some(floats)
+        } else {
+            None
+        };
+        
+        let ints = if sections.has_ints() {
+            let mut ints = Box::new([DEFAULT_REGISTER_I32; REGISTER_COUNT]);
+            cursor.read_i32_into::<LittleEndian>(&mut ints[..])?;
+            
+            Some(ints)
+        } else {
+            None
+        };
+        
+        let vectors = if sections.has_vectors() {
+            let mut vectors = Box::new([Vec2::ZERO; VECTOR_COUNT]);
+            
+            for vector in vectors.iter_mut() {
+                *vector = Vec2::new(cursor.read_f32::<LittleEndian>()?, cursor.read_f32::<LittleEndian>()?);
+            }
+            
+            Some(vectors)
+        } else {
+            None
+        };
+        
+        let persistence = if sections.has_persistence() {
+            let mut table = Box::new(PersistenceTable {
+                numbers : [Persistence::default(); REGISTER_COUNT * 2],
+                bools : [Persistence::default(); BOOL_COUNT],
+                char_id : 0,
+            });
+            
+            for persistence in table.numbers.iter_mut().chain(table.bools.iter_mut()) {
+                *persistence = num::FromPrimitive::from_u8(cursor.read_u8()?)
+                    .ok_or(snapshot::SnapshotError::Invalid("persistence"))?;
+            }
+            table.char_id = cursor.read_i32::<LittleEndian>()?;
+            
+            Some(table)
+        } else {
+            None
+        };
+        
+        let projectile_filter = if sections.has_projectile_filter() {
+            Some(crate::game_data::ProjectileFilter::read_snapshot(&mut cursor)?)
+        } else {
+            None
+        };
+        
+        let schedule = if sections.has_schedule() {
+            schedule::Schedule::read_snapshot(&mut cursor)?
+        } else {
+            schedule::Schedule::new()
+        };
+        
+        let forces = if sections.has_forces() {
+            character_extensions::Forces::read_snapshot(&mut cursor)?
+        } else {
+            version_1_suck.unwrap_or_else(character_extensions::Forces::new)
+        };
+        
+        let input_history = if sections.has_input_history() {
+            motion::InputHistory::read_snapshot(&mut cursor)?
+        } else {
+            motion::InputHistory::new()
+        };
+        
+        snapshot::finish(&cursor)?;
+        
+        self.bools = bools;
+        self.floats = floats;
+        self.ints = ints;
+        self.vectors = vectors;
+        self.persistence = persistence;
+        self.forces = forces;
+        self.projectile_filter = projectile_filter;
+        self.schedule = schedule;
+        self.input_history = input_history;
+        
+        Ok(())
     }
 }
 
@@ -792,13 +1439,28 @@ impl RegisterFlags {
     bitflag_getset!(0x10, is_lhs_indirect, set_lhs_indirect);
     bitflag_getset!(0x20, is_rhs_indirect, set_rhs_indirect);
     bitflag_getset!(0x40, is_destination_indirect, set_destination_indirect);
+    // only used by ternary operations
+    bitflag_getset!(0x08, is_third_bool, set_third_bool);
+    bitflag_getset!(0x80, is_third_indirect, set_third_indirect);
     
-    pub fn read(cursor : &mut Cursor<&'static [u8]>) -> Self
+    pub fn read(cursor : &mut Cursor<&'static [u8]>) -> CommandResult<Self>
+    {
+        Ok(Self {
+            raw : cursor.read_u8()?
+        })
+    }
+    
+    pub fn new(raw : u8) -> Self
     {
         Self {
-            raw : cursor.read_u8().unwrap()
+            raw
         }
     }
+    
+    pub fn raw(&self) -> u8
+    {
+        self.raw
+    }
 }
 
 pub fn with_stored_projectile<F, T>(addr : usize, default : T, function : F) -> T
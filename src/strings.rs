@@ -49,6 +49,16 @@ impl GStr {
         }
     }
     
+    pub fn get_ptr(&self) -> usize
+    {
+        self.ptr
+    }
+    
+    pub fn capacity(&self) -> usize
+    {
+        self.capacity
+    }
+    
     pub fn compare<F>(&self, other: &Self, comparator : F) -> bool
         where F : Fn(GChar, GChar) -> bool
     {
@@ -58,7 +68,7 @@ impl GStr {
             let mut is_match = true;
             let mut ended_before_capacity = false;
             
-            for (lhs, rhs) in self.into_iter().zip(other.into_iter()) {
+            for (lhs, rhs) in self.into_iter().zip(other) {
                 if !comparator(lhs, rhs) {
                     is_match = false;
                     ended_before_capacity = true;
@@ -85,7 +95,7 @@ impl GStr {
                 
                 let last_char = unsafe { read_ptr::<u8>(ptr_to_last) };
                 
-                last_char.map_or(true, |c| c == 0x00)
+                last_char.is_none_or(|c| c == 0x00)
             } else {
                 is_match
             }
@@ -102,18 +112,14 @@ impl GStr {
     }
     
     pub fn eq_ignore_ascii_case_and_path_separators(&self, other : &Self) -> bool {
-        self.compare(&other, |lhs, rhs| {
+        self.compare(other, |lhs, rhs| {
             if is_path_separator(lhs) && is_path_separator(rhs) {
                 true
             } else {
                 let lhs = lhs as u8;
                 let rhs = rhs as u8;
             
-                if lhs.eq_ignore_ascii_case(&rhs) {
-                    true
-                } else {
-                false
-                }
+                lhs.eq_ignore_ascii_case(&rhs)
             }
         })
     }
@@ -146,7 +152,7 @@ impl GStr {
                     if is_path_separator(c) {
                         slashes_needed -= 1;
                         
-                        if slashes_needed <= 0 {
+                        if slashes_needed == 0 {
                             break;
                         }
                     }
@@ -182,7 +188,7 @@ impl PartialEq for GStr {
         } else {
             let mut is_match = true;
             
-            for (lhs, rhs) in self.into_iter().zip(other.into_iter()) {
+            for (lhs, rhs) in self.into_iter().zip(other) {
                 let lhs = lhs as u8;
                 let rhs = rhs as u8;
                 
@@ -0,0 +1,221 @@
+//! Operators with 3 parameters for use by anmchr commands and so on.
+
+#![deny(unsafe_op_in_unsafe_fn)]
+// need to detect those types of errors so we lint for it
+#![deny(clippy::arithmetic_side_effects)]
+use crate::math::*;
+
+macro_rules! ternary_operators {
+
+    {
+        $( ( $(#[$($attrss1:tt)*])* $id:literal, $(#[$($attrss2:tt)*])* $name:ident,  $float_func:expr, $int_func:expr $(,)* ) ),+
+        $(,)*
+    } => {
+        use num_derive::FromPrimitive;
+        use crate::math::Number;
+        #[derive(Debug, Copy, Clone, FromPrimitive, PartialEq, Eq)]
+        #[repr(u32)]
+        pub enum TernaryOp
+        {
+            $(
+                $(#[$($attrss1)*])*
+                $(#[$($attrss2)*])*
+                $name = $id,
+            )+
+        }
+        
+        impl TernaryOp {
+            /// the name of the operation, as written in the list above
+            pub fn name(self) -> &'static str
+            {
+                match self {
+                    $(
+                    TernaryOp::$name => stringify!($name),
+                    )+
+                }
+            }
+            
+            /// look up an operation by name, ignoring case
+            pub fn from_name(name : &str) -> Option<Self>
+            {
+                $(
+                if name.eq_ignore_ascii_case(stringify!($name)) {
+                    return Some(TernaryOp::$name);
+                }
+                )+
+                
+                None
+            }
+        }
+        
+        fn handle_ternary_operation_f32(first : f32, second : f32, third : f32, op : TernaryOp) -> f32
+        {
+            let func = match op {
+                $(
+                TernaryOp::$name => $float_func,
+                )+
+            };
+            
+            let float = func(first, second, third);
+            
+            clean_float(float)
+        }
+        
+        fn handle_ternary_operation_i32(first : i32, second : i32, third : i32, op : TernaryOp) -> i32
+        {
+            let func = match op {
+                $(
+                TernaryOp::$name => $int_func,
+                )+
+            };
+            
+            func(first, second, third)
+        }
+        
+        pub trait TernaryOpHandler<T>
+        {
+            fn operate(self, first : T, second : T, third : T) -> T;
+        }
+        
+        impl TernaryOpHandler<i32> for TernaryOp {
+            fn operate(self, first : i32, second : i32, third : i32) -> i32
+            {
+                handle_ternary_operation_i32(first, second, third, self)
+            }
+        }
+        
+        impl TernaryOpHandler<f32> for TernaryOp {
+            fn operate(self, first : f32, second : f32, third : f32) -> f32
+            {
+                handle_ternary_operation_f32(first, second, third, self)
+            }
+        }
+        
+        impl TernaryOpHandler<Number> for TernaryOp {
+            /// i32 math only if all three are integers, otherwise f32 math
+            fn operate(self, first : Number, second : Number, third : Number) -> Number
+            {
+                match (first, second, third) {
+                    (Number::I32(first), Number::I32(second), Number::I32(third)) => {
+                        Number::I32(self.operate(first, second, third))
+                    },
+                    _ => Number::F32(self.operate(first.into_float(), second.into_float(), third.into_float())),
+                }
+            }
+        }
+    }
+}
+
+/// the integer versions of the in-between operations use percent, since integers can't hold fractions
+const PERCENT : i64 = 100;
+
+fn saturate(value : i64) -> i32
+{
+    value.clamp(i32::MIN as i64, i32::MAX as i64) as i32
+}
+
+/// how far `value` is from `from` to `to`, in percent. 0 if `from` and `to` are the same
+fn inverse_lerp_percent(from : i32, to : i32, value : i32) -> i64
+{
+    let range = (to as i64).saturating_sub(from as i64);
+    
+    (value as i64).saturating_sub(from as i64).saturating_mul(PERCENT).checked_div(range).unwrap_or(0)
+}
+
+// booleans just use the i32 version, as 0 or 1
+ternary_operators! {
+    (
+        /// first, but kept between second and third. if second is bigger than third, they're swapped
+        /// so clamp of 150, 0, 100 gives 100, and clamp of -5, 0, 100 gives 0
+        0x00, Clamp,
+        |value : f32, low : f32, high : f32| {
+            let (low, high) = if low <= high { (low, high) } else { (high, low) };
+            
+            value.max(low).min(high)
+        },
+        |value : i32, low : i32, high : i32| {
+            let (low, high) = if low <= high { (low, high) } else { (high, low) };
+            
+            value.max(low).min(high)
+        }
+    ),
+    (
+        /// linear interpolation. goes from first to second by the amount in third. so third = 0.0 gives first, 1.0 gives second, and 0.5 is halfway between.
+        /// amounts outside of 0.0 to 1.0 keep going past first or second.
+        /// for integers, the amount is in percent instead, so 50 is halfway. the result saturates at the integer limits
+        0x01, Lerp,
+        |from : f32, to : f32, amount : f32| {
+            from + (to - from) * amount
+        },
+        |from : i32, to : i32, percent : i32| {
+            let offset = (to as i64).saturating_sub(from as i64).saturating_mul(percent as i64).wrapping_div(PERCENT);
+            
+            saturate((from as i64).saturating_add(offset))
+        }
+    ),
+    (
+        /// if first is true, gives second. if first is false, gives third
+        0x02, Select,
+        |condition : f32, if_true : f32, if_false : f32| {
+            if condition.is_true() { if_true } else { if_false }
+        },
+        |condition : i32, if_true : i32, if_false : i32| {
+            if condition.is_true() { if_true } else { if_false }
+        }
+    ),
+    (
+        /// first times second, plus third. the float version only rounds once, at the end
+        /// the integer version wraps around on overflow, like Mul and Add
+        0x03, MulAdd,
+        |first : f32, second : f32, third : f32| {
+            first.mul_add(second, third)
+        },
+        |first : i32, second : i32, third : i32| {
+            first.wrapping_mul(second).wrapping_add(third)
+        }
+    ),
+    (
+        /// the opposite of Lerp. how far third is from first to second, so first gives 0.0, second gives 1.0, and halfway between gives 0.5.
+        /// if first and second are the same, gives 0.
+        /// for integers, the result is in percent instead, so halfway gives 50. the result saturates at the integer limits
+        0x04, InverseLerp,
+        |from : f32, to : f32, value : f32| {
+            if to != from {
+                (value - from) / (to - from)
+            } else {
+                0.0
+            }
+        },
+        |from : i32, to : i32, value : i32| {
+            saturate(inverse_lerp_percent(from, to, value))
+        }
+    ),
+    (
+        /// like InverseLerp, but kept between 0.0 and 1.0, and eased so it starts and ends slowly. see https://en.wikipedia.org/wiki/Smoothstep
+        /// if first and second are the same, gives 0 if third is less than them and 1 otherwise.
+        /// for integers, the result is in percent instead, from 0 to 100
+        0x05, SmoothStep,
+        |from : f32, to : f32, value : f32| {
+            if to == from {
+                (value >= from).from_bool()
+            } else {
+                let t = ((value - from) / (to - from)).clamp(0.0, 1.0);
+                
+                t * t * (3.0 - 2.0 * t)
+            }
+        },
+        |from : i32, to : i32, value : i32| {
+            if to == from {
+                if value >= from { PERCENT as i32 } else { 0 }
+            } else {
+                let t = inverse_lerp_percent(from, to, value).clamp(0, PERCENT);
+                
+                // t is at most 100, so none of this can overflow
+                #[allow(clippy::arithmetic_side_effects)]
+                let result = t * t * (3 * PERCENT - 2 * t) / (PERCENT * PERCENT);
+                
+                result as i32
+            }
+        }
+    ),
+}
@@ -4,59 +4,12 @@
 use crate::hook_helpers::*;
 use crate::storage;
 use crate::math::near_eq;
+use crate::anmchr_asm::to_hex_string;
 
 /// interpret a string that looks like "12abce53" to a series of hex bytes
 fn to_bytes(input : &str) -> Vec<u8>
 {
-    let mut output : Vec<u8> = Vec::with_capacity(input.len() / 2);
-    
-    let mut chars = input.chars();
-    
-    loop {
-        let hi_nibble = match chars.next()
-        {
-            Some(c) => c,
-            _ => break,
-        };
-        
-        if hi_nibble.is_whitespace() {
-            continue;
-        }
-        
-        let lo_nibble = match chars.next()
-        {
-            Some(c) => c,
-            _ => break,
-        };
-        
-        if lo_nibble.is_whitespace() {
-            continue;
-        }
-        
-        let byte : [char; 2] = [hi_nibble, lo_nibble];
-        
-        let byte : String = byte.iter().collect();
-        
-        let byte = u8::from_str_radix(byte.as_str(), 16).unwrap();
-        
-        output.push(byte);
-    }
-    
-    output
-}
-
-fn to_hex_string(input : Vec<u8>) -> String
-{
-    let mut output = String::new();
-    
-    for (index, value) in input.iter().enumerate() {
-        if index != 0 && index % 4 == 0 {
-            output.push_str("\n\t");
-        }
-        output.push_str(format!("{:02X}",value).as_str());
-    }
-    
-    output
+    crate::anmchr_asm::to_bytes(input).unwrap()
 }
 
 fn test_reload_one(to_test_str : &str, expected_str : &str)
@@ -75,13 +28,13 @@ fn test_reload_one(to_test_str : &str, expected_str : &str)
     
     let expected = to_bytes(expected_str);
     
-    for (index, (a,b)) in to_test.clone().into_iter().zip(expected.into_iter()).enumerate() {
-        assert_eq!(a, b, "in test of \n\t{}\n\tvs\n\t{}\n\tvs\n\t{},\n\nto_test {:#X} != {:#X} expected at index {} bytes (div by 4 = {})", to_test_str, expected_str, to_hex_string(to_test), a, b, index, index/4);
+    for (index, (a,b)) in to_test.clone().into_iter().zip(expected).enumerate() {
+        assert_eq!(a, b, "in test of \n\t{}\n\tvs\n\t{}\n\tvs\n\t{},\n\nto_test {:#X} != {:#X} expected at index {} bytes (div by 4 = {})", to_test_str, expected_str, to_hex_string(&to_test).replace('\n', "\n\t"), a, b, index, index/4);
     }
     
     reloads.restore();
     
-    for (index, (a,b)) in to_test.clone().into_iter().zip(original.into_iter()).enumerate() {
+    for (index, (a,b)) in to_test.clone().into_iter().zip(original).enumerate() {
         assert_eq!(a, b, "in test of \n\t{}\n\tvs\n\t{},\n\nto_test {:#X} != {:#X} original at index {} bytes (div by 4 = {})", to_test_str, expected_str, a, b, index, index/4);
     }
 }
@@ -96,10 +49,88 @@ fn test_execute_anmchr_command(ptr : usize, to_test_str : &str)
 }
 
 
-pub extern "win64" fn replaced_fake_execute_anmchr_command(executor_ptr : usize, _anmchr_command_ptr : usize) {
-    if executor_ptr == 0
+/// every command the game's part got run for, so tests can check none of the game's own commands went missing
+static GAME_COMMANDS_RUN : std::sync::Mutex<Vec<usize>> = std::sync::Mutex::new(Vec::new());
+
+pub extern "win64" fn replaced_fake_execute_anmchr_command(_executor_ptr : usize, anmchr_command_ptr : usize) {
+    lock(&GAME_COMMANDS_RUN).push(anmchr_command_ptr);
+}
+
+/// how many times the game's part ran for commands that aren't 66 commands in `bytes`
+fn game_commands_run(bytes : &[u8]) -> usize
+{
+    let range = bytes.as_ptr_range();
+    
+    lock(&GAME_COMMANDS_RUN).iter()
+        .filter(|&&ptr| range.contains(&(ptr as *const u8)) && unsafe { *(ptr as *const u8) } != 0x66)
+        .count()
+}
+
+/// tests run in parallel, but the hook can only be made once
+fn hook_fake_execute_anmchr_command()
+{
+    static HOOKED : std::sync::Once = std::sync::Once::new();
+    
+    HOOKED.call_once(|| {
+        crate::ExecuteAnmChrCommandFn::make_hook(replaced_fake_execute_anmchr_command as *const () as usize, crate::execute_anmchr_command).unwrap();
+    });
+}
+
+/// mag_patch keeps its state in globals (storage, the shared banks, random, the error register, the command groups),
+/// but tests run in parallel. so every test that runs commands holds this the whole time, and starts with all of that
+/// cleared, so nothing from another test can leak in
+static GLOBAL_STATE : std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+fn lock_global_state() -> std::sync::MutexGuard<'static, ()>
+{
+    let guard = lock(&GLOBAL_STATE);
+    
+    lock(&storage::CHAR_STORAGE).clear();
+    // with nothing in storage, there's nothing to carry over, so this only clears the shared banks and random
+    storage::reset_all(storage::ResetKind::Match);
+    crate::command_error::set_error_register(None);
+    crate::anmchr_commands::set_command_groups(&[]);
+    
+    guard
+}
+
+/// a SimulatedGame installed on this thread, with the game's part of running commands faked. it's uninstalled when
+/// the test is done with it
+struct TestGame
+{
+    game : crate::simulated_game::SimulatedGame,
+    _global_state : std::sync::MutexGuard<'static, ()>,
+}
+
+impl std::ops::Deref for TestGame
+{
+    type Target = crate::simulated_game::SimulatedGame;
+    
+    fn deref(&self) -> &Self::Target
+    {
+        &self.game
+    }
+}
+
+impl Drop for TestGame
+{
+    fn drop(&mut self)
     {
-        println!("function body to get the optimizer to not delete this during release builds");
+        crate::simulated_game::SimulatedGame::uninstall();
+    }
+}
+
+fn simulated_game() -> TestGame
+{
+    let global_state = lock_global_state();
+    hook_fake_execute_anmchr_command();
+    
+    let game = crate::simulated_game::SimulatedGame::new();
+    game.install();
+    
+    TestGame {
+        game,
+        _global_state : global_state,
     }
 }
 
@@ -135,8 +166,8 @@ fn get_register_f32(ptr : usize, register : usize) -> f32
 
 #[test]
 fn test_commands() {
-    crate::ExecuteAnmChrCommandFn::make_hook(replaced_fake_execute_anmchr_command as *const () as usize, crate::execute_anmchr_command).unwrap();
-    
+    let _global_state = lock_global_state();
+    hook_fake_execute_anmchr_command();
     
     let mut char_struct = [0; TEST_CHARACTER_STRUCT_SIZE];
     let ptr = char_struct.as_mut_ptr() as usize;
@@ -256,7 +287,7 @@ fn test_commands() {
         F0000000"
         );
     
-    assert_eq!(get_register_i32(ptr, 0x56), 80 as i32);
+    assert_eq!(get_register_i32(ptr, 0x56), 80_i32);
     
     // InvincibilityFlags = 17
     test_execute_anmchr_command(
@@ -277,7 +308,7 @@ fn test_commands() {
         F2000000"
         );
     
-    assert_eq!(get_register_i32(ptr, 0x57), 17 as i32);
+    assert_eq!(get_register_i32(ptr, 0x57), 17_i32);
     
      // InvincibilityFlags = InvincibilityFlags << 5 = 544
     test_execute_anmchr_command(
@@ -299,7 +330,7 @@ fn test_commands() {
         F2000000"
         );
     
-    assert_eq!(get_register_i32(ptr, 0x58), 544 as i32);
+    assert_eq!(get_register_i32(ptr, 0x58), 544_i32);
     
     
      // CharacterComboCounter = logical not(CharacterComboCounter) = 1
@@ -321,7 +352,7 @@ fn test_commands() {
         32000000"
         );
     
-    assert_eq!(get_register_i32(ptr, 0x59), 1 as i32);
+    assert_eq!(get_register_i32(ptr, 0x59), 1_i32);
     
      // XPosition = 1.0
     test_execute_anmchr_command(
@@ -414,7 +445,7 @@ fn test_commands() {
         01000000"
         );
     
-    assert_eq!(get_register_bool(ptr, 0xff), true);
+    assert!(get_register_bool(ptr, 0xff));
     
     // boolean[0x1] = boolean[0x1] + 1 = true
     test_execute_anmchr_command(
@@ -426,7 +457,7 @@ fn test_commands() {
         01000000"
         );
     
-    assert_eq!(get_register_bool(ptr, 0x01), true);
+    assert!(get_register_bool(ptr, 0x01));
     // boolean[0x1] = boolean[0x1] + 1 = true
     test_execute_anmchr_command(
         ptr,
@@ -437,7 +468,7 @@ fn test_commands() {
         01000000"
         );
     
-    assert_eq!(get_register_bool(ptr, 0x01), true);
+    assert!(get_register_bool(ptr, 0x01));
     
     // boolean[0x1] = boolean[0x1] ^ true = false
     test_execute_anmchr_command(
@@ -449,7 +480,7 @@ fn test_commands() {
         01000000"
         );
     
-    assert_eq!(get_register_bool(ptr, 0x01), false);
+    assert!(!get_register_bool(ptr, 0x01));
     
     // boolean[0x1] = boolean[0x1] ^ true = true
     test_execute_anmchr_command(
@@ -461,7 +492,7 @@ fn test_commands() {
         00040701"
         );
     
-    assert_eq!(get_register_bool(ptr, 0x01), true);
+    assert!(get_register_bool(ptr, 0x01));
     
     
     // boolean[0x3] = boolean[0x1] | boolean[0x2] = true
@@ -474,8 +505,8 @@ fn test_commands() {
         01000000"
         );
     
-    assert_eq!(get_register_bool(ptr, 0x02), false);
-    assert_eq!(get_register_bool(ptr, 0x03), true);
+    assert!(!get_register_bool(ptr, 0x02));
+    assert!(get_register_bool(ptr, 0x03));
     
     // boolean[0x4] = bitwise !boolean[0x3] = false
     test_execute_anmchr_command(
@@ -486,7 +517,7 @@ fn test_commands() {
         03000504"
         );
     
-    assert_eq!(get_register_bool(ptr, 0x04), false);
+    assert!(!get_register_bool(ptr, 0x04));
     
     // boolean[0x5] = logical !boolean[0x4] = true
     test_execute_anmchr_command(
@@ -497,7 +528,7 @@ fn test_commands() {
         04000505"
         );
     
-    assert_eq!(get_register_bool(ptr, 0x05), true);
+    assert!(get_register_bool(ptr, 0x05));
     
     // boolean[0x6] = bitwise !0x55 = false
     test_execute_anmchr_command(
@@ -509,7 +540,7 @@ fn test_commands() {
         55000000"
         );
     
-    assert_eq!(get_register_bool(ptr, 0x06), false);
+    assert!(!get_register_bool(ptr, 0x06));
     
     // boolean[0x6] = bitwise !0x00 = true
     test_execute_anmchr_command(
@@ -521,7 +552,7 @@ fn test_commands() {
         00000000"
         );
     
-    assert_eq!(get_register_bool(ptr, 0x06), true);
+    assert!(get_register_bool(ptr, 0x06));
     
     
     // boolean[0x71] = condition register = true
@@ -533,7 +564,7 @@ fn test_commands() {
         C0000000"
         );
     
-    assert_eq!(get_register_bool(ptr, 0x71), true);
+    assert!(get_register_bool(ptr, 0x71));
     
     // boolean[0x71] = condition register = true
     test_execute_anmchr_command(
@@ -544,7 +575,7 @@ fn test_commands() {
         C0000000"
         );
     
-    assert_eq!(get_register_bool(ptr, 0x71), true);
+    assert!(get_register_bool(ptr, 0x71));
     
     // AssistType = boolean[0x06] = true = 1
     test_execute_anmchr_command(
@@ -564,7 +595,7 @@ fn test_commands() {
         B2000000"
         );
     
-    assert_eq!(get_register_bool(ptr, 0x72), true);
+    assert!(get_register_bool(ptr, 0x72));
     
     // AssistCooldown = boolean[0x72] (true) + AssistCooldown (0.0) = 1.0
     test_execute_anmchr_command(
@@ -718,7 +749,7 @@ fn test_commands() {
         );
     
     assert_eq!(get_register_i32(ptr, 0x0a), 0x7777);
-    assert_eq!(get_register_bool(ptr, 0x0a), true);
+    assert!(get_register_bool(ptr, 0x0a));
     
     // register[0x0b] = condition register = 0x7777
     test_execute_anmchr_command(
@@ -748,7 +779,7 @@ fn test_commands() {
         );
     
     assert_eq!(get_register_i32(ptr, 0x0a), 0x7770);
-    assert_eq!(get_register_bool(ptr, 0x0a), false);
+    assert!(!get_register_bool(ptr, 0x0a));
     
     // register[0x0b] = condition register = 0x7770
     test_execute_anmchr_command(
@@ -777,12 +808,14 @@ fn test_commands() {
         );
     
     assert_eq!(get_register_i32(ptr, 0x0c), 0x2222);
-    assert_eq!(get_register_bool(ptr, 0x0c), true);
+    assert!(get_register_bool(ptr, 0x0c));
 }
 
 
 #[test]
 fn test_reload_all() {
+    let _global_state = lock_global_state();
+    
     test_reload_one(
         "0000000021000000040000000000000003000000030000000600000003000000010000002D000000FFFFFFFF03000000",
         "0000000021000000040000000000000003000000030000000600000003000000010000002D0000000000000003000000",
@@ -1606,125 +1639,1723 @@ fn test_string_comparisons() {
         GStr::from_ptr(s.as_ptr() as usize, s.len())
     };
     
-    assert_eq!(
+    assert!(
         create_gstr("Test string 1\0\0\0\0\0")
             .eq_ignore_ascii_case(
                 &create_gstr("Test string 1\0\0\0\0\0")
-            ),
-        true
+            )
         );
     
-    assert_eq!(
+    assert!(
         create_gstr("Test string 2\0")
             .eq_ignore_ascii_case(
                 &create_gstr("Test string 2\0\0\0\0\0")
-            ),
-        true
+            )
         );
     
-    assert_eq!(
+    assert!(
         create_gstr("Test string 3")
             .eq_ignore_ascii_case(
                 &create_gstr("Test stRING 3")
-            ),
-        true
+            )
         );
     
-    assert_eq!(
+    assert!(
         create_gstr("teSTing string 4\0")
             .eq_ignore_ascii_case(
                 &create_gstr("Testing stRING 4")
-            ),
-        true
+            )
         );
     
-    assert_eq!(
+    assert!(
         create_gstr("testiNg string 5")
             .eq_ignore_ascii_case(
                 &create_gstr("testing string 5\0")
-            ),
-        true
+            )
         );
     
     
-    assert_eq!(
-        create_gstr("should not be equal")
+    assert!(
+        !create_gstr("should not be equal")
             .eq_ignore_ascii_case(
                 &create_gstr("to each other")
-            ),
-        false
+            )
         );
     
-    assert_eq!(
-        create_gstr("also should not be equal")
+    assert!(
+        !create_gstr("also should not be equal")
             .eq_ignore_ascii_case(
                 &create_gstr("to each other\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0")
-            ),
-        false
+            )
         );
     
     
-    assert_eq!(
+    assert!(
         create_gstr("a/b")
             .eq_ignore_ascii_case_and_path_separators(
                 &create_gstr(r"a\b")
-            ),
-        true
+            )
         );
     
-    assert_eq!(
+    assert!(
         create_gstr(r"b\c")
             .eq_ignore_ascii_case_and_path_separators(
                 &create_gstr(r"b/c")
-            ),
-        true
+            )
         );
     
-    assert_eq!(
+    assert!(
         create_gstr(r"FFFFFF\c")
             .eq_ignore_ascii_case_and_path_separators(
                 &create_gstr(r"Ffffff/c")
-            ),
-        true
+            )
         );
     
-    assert_eq!(
+    assert!(
         create_gstr(r"potato/yam\tuber")
             .path_suffix_compare(
                 &create_gstr(r"potato\YAM\tuber")
-            ),
-        true
+            )
         );
     
-    assert_eq!(
+    assert!(
         create_gstr(r"carrot/parsnip")
             .path_suffix_compare(
                 &create_gstr("parsley/carrot/PARSNIP\0\0\0\0")
-            ),
-        true
+            )
         );
     
-    assert_eq!(
-        create_gstr(r"carrot/parsnip/")
+    assert!(
+        !create_gstr(r"carrot/parsnip/")
             .path_suffix_compare(
                 &create_gstr(r"parsley/carrot/PARSNIP")
-            ),
-        false
+            )
         );
     
-    assert_eq!(
+    assert!(
         create_gstr("garlic\0\0\0\0")
             .path_suffix_compare(
                 &create_gstr(r"onions\shallots\garlic")
-            ),
-        true
+            )
         );
     
-    assert_eq!(
+    assert!(
         create_gstr("chivEs")
             .path_suffix_compare(
                 &create_gstr("onions/shallots/GARLIC/chives\0\0\0\0\0\0\0")
-            ),
-        true
+            )
         );
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_anmchr_asm() {
+    use crate::anmchr_asm::{assemble, disassemble};
+    
+    // examples from docs/registers-explanation.md
+    let examples = [
+        ("teleport.x 150.0", "66000000 00000000 00001643"),
+        ("load.imm rFF, 1.7", "66000000 10000000 000000FF 9A99D93F"),
+        ("load.imm bFF, true", "66000000 10000000 000004FF 01000000"),
+        ("mul.imm r33, rFF, 4", "66000000 11000000 02000000 FF000033 04000000"),
+        ("load.var r23, Meter, me", "66000000 15000000 00000023 40000000"),
+        ("cond equalityapproximate, assigntorighthandside, r0C, b0C, 0, 0x2222, 1",
+            "66000000 1C000000 C0000000 D0000000 0C00010C 00000000 22220000 01000000"),
+        ("proj.filter greaterthanequal, YPosition, 100.0, me backwards",
+            "66000000 32000000 C4000000 00000000 01000000 21000000 0000C842"),
+        ("proj.get opponent", "66000000 30000000 00800000 00000000"),
+        ("suck.x -19.0, 1.0", "66000000 50000000 000098C1 0000803F"),
+        ("force 1, opponent, -19.0, 0.0, linear, 1.0, 0.0", "66000000 51000000 01800100 000098C1 00000000 0000803F 00000000"),
+        ("force 7, opponent.assist1, 10.0, r81, exp, 0.5, 300.0 both radial",
+            "66000000 51000000 07820701 00002041 81FFFFFF 0000003F 00009643"),
+        ("force 0, me, 2.0, 0.0, linear, 0.0, 0.0 proj", "66000000 51000000 00000200 00000040 00000000 00000000 00000000"),
+        ("force.clear 3", "66000000 52000000 03000000"),
+        ("force.clear all", "66000000 52000000 FF000000"),
+        ("motion b01, me, qcf", "66000000 60000000 00000401 00000000"),
+        ("motion r02, point, charge.bf, 8, 45", "66000000 60000000 04010002 082D0000"),
+        ("motion [r03], opponent, tap.b, 12", "66000000 60000000 07804003 0C000000"),
+        ("skip.if !b05, 2", "66000000 20000000 05010100 02000000"),
+        ("jump.if cond, -3", "66000000 21000000 00020000 FDFFFFFF"),
+        ("jump.if [r07], 1", "66000000 21000000 07001000 01000000"),
+        ("skip 1", "66000000 20000000 00040000 01000000"),
+        ("schedule 30, 0, 5", "66000000 23000000 1E000000 00000000 05000000"),
+        ("schedule r05, 10, -1", "66000000 23000000 05FFFFFF 0A000000 FFFFFFFF"),
+        ("schedule.cancel 5", "66000000 24000000 05000000"),
+        ("terop.reg clamp, r07, r05, r06, r07", "66000000 1D000000 00000000 05060007 07000000"),
+        ("clamp.imm r05, r05, 0, 0x64", "66000000 1E000000 00000000 05000005 00000000 64000000"),
+        ("lerp.imm r85, r81, 0.0, r02", "66000000 1E000000 01000000 81000085 00000000 02FFFFFF"),
+        ("select.reg b01, b02, r03, [r04]", "66000000 1D000000 02000000 02038501 04000000"),
+        ("vec.load v01, opponent", "66000000 40000000 00800001 00000000"),
+        ("vec.store [r05], me proj", "66000000 41000000 00001005 01000000"),
+        ("vec.dir v02, opponent", "66000000 43000000 00800002"),
+        ("vec.add v01, v01, v02", "66000000 42000000 00000000 01020001 00000000"),
+        ("vec.scale v02, v02, 150.0", "66000000 42000000 02000000 02000002 00001643"),
+        ("vec.rotate v02, v02, r81", "66000000 42000000 03000000 02000002 81FFFFFF"),
+        ("vec.normalize v03, v02", "66000000 42000000 04000000 02000003 00000000"),
+        ("vec.length r81, v02", "66000000 42000000 10000000 02000081 00000000"),
+        ("vec.dot r81, v02, v03", "66000000 42000000 11000000 02030081 00000000"),
+        ("vec.make v04, r81, b02", "66000000 42000000 20000000 81020204 00000000"),
+        ("load.shared r01, team, r10", "66000000 44000000 00100001"),
+        ("load.shared b02, match, [r05]", "66000000 44000000 02051402"),
+        ("store.shared r81, opponent, r90", "66000000 45000000 01900081"),
+        ("store.shared [r05], team, b03", "66000000 45000000 00031405"),
+        ("persist match, r10, r1F", "66000000 46000000 101F0001"),
+        ("persist unload, b03", "66000000 46000000 03030102"),
+    ];
+    
+    for (text, hex) in examples {
+        let expected = to_bytes(hex);
+        
+        assert_eq!(assemble(text).unwrap(), expected, "assembling {}", text);
+        assert_eq!(crate::anmchr_asm::command_len(&expected), Some(expected.len()), "length of {}", text);
+        
+        // disassembly doesn't have to match the text exactly, but it has to make the same bytes
+        let disassembled = disassemble(&expected);
+        assert!(!disassembled.starts_with(".hex"), "{} disassembled to {}", text, disassembled);
+        assert_eq!(assemble(&disassembled).unwrap(), expected, "round trip of {}", disassembled);
+    }
+    
+    assert_eq!(disassemble(&to_bytes("66000000 10000000 000000FF 9A99D93F")), "load.imm rFF, 1.7\n");
+    assert_eq!(
+        assemble("binop.reg r18, r03, r01, r02").unwrap(),
+        to_bytes("66000000 12000000 18FFFFFF 01020003"));
+    
+    // game commands and unknown commands survive as raw bytes
+    let mixed = to_bytes("01000000 B1000000 01000000 00000000 06000000 0000803F 66000000 31000000 66000000 99000000 12345678");
+    assert_eq!(assemble(&disassemble(&mixed)).unwrap(), mixed);
+    
+    // labels turn into offsets counted from the command after the jump
+    assert_eq!(
+        assemble("start:\nproj.next\njump.if b01, start\njump end\nproj.next\nend:\nproj.next").unwrap(),
+        assemble("proj.next\njump.if b01, -2\njump 1\nproj.next\nproj.next").unwrap());
+    assert!(assemble("jump nowhere").is_err());
+    assert!(assemble("a:\nproj.next\na:").is_err());
+    assert!(assemble("skip -1").is_err());
+    
+    let error = assemble("load.imm r01, 1\nload.imm r01, 1.5").unwrap_err();
+    assert_eq!(error.line, Some(2));
+    assert!(assemble("add.imm r01, r01").is_err());
+    assert!(assemble("load.var r01, NotAVariable, me").is_err());
+}
+
+
+#[test]
+fn test_anmchr_validate() {
+    use crate::anmchr_asm::assemble;
+    use crate::anmchr_validate::{validate, Severity};
+    
+    let count = |bytes : &[u8], severity : Severity| {
+        validate(bytes).iter().filter(|d| d.severity == severity).count()
+    };
+    
+    let clean = assemble("
+        load.imm rFF, 1.7
+        mul.imm r33, r01, 4
+        load.var r83, Meter, opponent.assist1
+        store.var.imm Health, me, 50.0
+        binop.reg r18, r01, r02, r03
+        proj.filter greaterthanequal, YPosition, 100.0, me backwards
+        .hex 01000000 B1000000 01000000 00000000 06000000 0000803F
+        proj.next
+    ").unwrap();
+    assert_eq!(validate(&clean).len(), 0, "{:?}", validate(&clean));
+    
+    // unknown command stops the check
+    assert_eq!(count(&to_bytes("66000000 99000000 66000000 31000000"), Severity::Error), 1);
+    // cut off arguments
+    assert_eq!(count(&to_bytes("66000000 15000000 00000023"), Severity::Error), 1);
+    // unknown operations, with replacement still allowed
+    assert_eq!(count(&to_bytes("66000000 12000000 99999999 01020003"), Severity::Error), 1);
+    assert_eq!(count(&to_bytes("66000000 13000000 18FFFFFF 01000003"), Severity::Error), 0);
+    assert_eq!(count(&to_bytes("66000000 14000000 EEEE0000 000000FF 00000000"), Severity::Error), 1);
+    // unknown variable, read only variable, unknown relation
+    assert_eq!(count(&to_bytes("66000000 15000000 00000023 EEEE0000"), Severity::Error), 1);
+    assert_eq!(count(&assemble("store.var r01, FacingReadOnly, me").unwrap(), Severity::Error), 1);
+    assert_eq!(count(&assemble("add.var.imm InputsReadOnly, me, 1").unwrap(), Severity::Error), 1);
+    assert_eq!(count(&assemble("load.var r01, InputsReadOnly, me").unwrap(), Severity::Error), 0);
+    assert_eq!(count(&to_bytes("66000000 15000000 00300023 40000000"), Severity::Error), 1);
+    assert_eq!(count(&to_bytes("66000000 30000000 00FF0000 00000000"), Severity::Error), 1);
+    
+    // int and float banks mixing
+    assert_eq!(count(&assemble("add.reg r80, r01, r02").unwrap(), Severity::Warning), 1);
+    assert_eq!(count(&assemble("add.reg r03, r01, r82").unwrap(), Severity::Warning), 1);
+    assert_eq!(count(&assemble("load.var r01, Health, me").unwrap(), Severity::Warning), 1);
+    assert_eq!(count(&assemble("load.var b01, Health, me").unwrap(), Severity::Warning), 0);
+    assert_eq!(count(&assemble("add.reg [r80], r01, r02").unwrap(), Severity::Warning), 0);
+    
+    // skips and jumps
+    assert_eq!(count(&assemble("proj.next\njump.if b01, -2").unwrap(), Severity::Error), 0);
+    assert_eq!(count(&assemble("proj.next\njump.if b01, -3").unwrap(), Severity::Error), 1);
+    assert_eq!(count(&to_bytes("66000000 20000000 00090000 01000000"), Severity::Error), 1);
+    assert_eq!(count(&to_bytes("66000000 20000000 00040000 FFFFFFFF"), Severity::Error), 1);
+    assert_eq!(count(&to_bytes("66000000 22000000 40000000"), Severity::Error), 1);
+    
+    // ternary operations
+    assert_eq!(count(&to_bytes("66000000 1D000000 99000000 05060007 07000000"), Severity::Error), 1);
+    assert_eq!(count(&to_bytes("66000000 1E000000 18FFFFFF 05000005 00000000 64000000"), Severity::Error), 0);
+    assert_eq!(count(&assemble("lerp.reg r81, r82, r83, r84").unwrap(), Severity::Warning), 0);
+    assert_eq!(count(&assemble("lerp.reg r81, r82, r83, r04").unwrap(), Severity::Warning), 1);
+    assert_eq!(count(&assemble("select.reg r81, b01, r82, r83").unwrap(), Severity::Warning), 0);
+    
+    // vectors
+    assert_eq!(count(&to_bytes("66000000 40000000 00000001 02000000"), Severity::Error), 1);
+    assert_eq!(count(&to_bytes("66000000 42000000 05000000 01020003 00000000"), Severity::Error), 1);
+    assert_eq!(count(&assemble("vec.dir v01, opponent\nvec.length r81, v01").unwrap(), Severity::Error), 0);
+}
+
+
+/// run one command as `character`. the bytes are kept around forever like the game's anmchr data, since filename filters point into them
+fn execute_simulated(character : &crate::game_data::Char, source : &str)
+{
+    let bytes = Box::leak(crate::anmchr_asm::assemble(source).unwrap().into_boxed_slice());
+    
+    crate::execute_anmchr_command(character.get_ptr() + 0x1348, bytes.as_mut_ptr() as usize);
+}
+
+#[test]
+fn test_simulated_game() {
+    use crate::game_data::Team;
+    
+    let game = simulated_game();
+    
+    let p1 = game.add_character(&Team::Player1, 0, 1);
+    let p1_assist1 = game.add_character(&Team::Player1, 1, 2);
+    let p2 = game.add_character(&Team::Player2, 0, 3);
+    let p2_assist1 = game.add_character(&Team::Player2, 1, 4);
+    let p2_assist2 = game.add_character(&Team::Player2, 2, 5);
+    let p1_child = game.add_child(&Team::Player1, &p1);
+    
+    let executor = |c : &crate::game_data::Char| c.get_ptr() + 0x1348;
+    
+    assert_eq!(p1.identify_team(), Team::Player1);
+    assert_eq!(p1_child.identify_team(), Team::Player1);
+    assert_eq!(p2_assist2.identify_team(), Team::Player2);
+    assert_eq!(p1.get_opponent_point_char(), Some(p2.clone()));
+    
+    // 150 units in front of the opponent
+    p1.set_x_pos(-200.0);
+    p2.set_x_pos(300.0);
+    execute_simulated(&p1, "teleport.x 150.0");
+    assert!(near_eq(p1.get_x_pos(), 150.0));
+    
+    game.set_facing_left(&p1, true);
+    execute_simulated(&p1, "teleport.x 150.0");
+    assert!(near_eq(p1.get_x_pos(), 450.0));
+    game.set_facing_left(&p1, false);
+    
+    // character variables through relations
+    game.player(&Team::Player2).set_meter(25000.0);
+    execute_simulated(&p1, "load.var r80, Meter, opponent");
+    assert!(near_eq(get_register_f32(executor(&p1), 0x80), 25000.0));
+    
+    p2_assist1.set_health_raw(1234.0);
+    execute_simulated(&p1, "load.var r81, Health, opponent.assist1");
+    assert!(near_eq(get_register_f32(executor(&p1), 0x81), 1234.0));
+    
+    execute_simulated(&p1, "load.var r01, CharOrderReadOnly, assist1");
+    assert_eq!(get_register_i32(executor(&p1), 0x01), 1);
+    
+    execute_simulated(&p1_child, "load.var r82, XPosition, parent");
+    assert!(near_eq(get_register_f32(executor(&p1_child), 0x82), 450.0));
+    
+    game.set_character_name(3, "Djinn");
+    execute_simulated(&p1, r#"char.name r02, opponent, "djinn""#);
+    assert_eq!(get_register_i32(executor(&p1), 0x02), 1);
+    execute_simulated(&p1, r#"char.name r02, opponent.assist1, "djinn""#);
+    assert_eq!(get_register_i32(executor(&p1), 0x02), 0);
+    
+    // projectiles, newest first
+    for (y_pos, filename) in [(10.0, r"Djinn\shot\Beam"), (150.0, r"Djinn\shot\Beam"), (200.0, r"Djinn\shot\Orb")] {
+        game.add_projectile(&Team::Player1, &p1, filename, 0x1234).set_y_pos(y_pos);
+    }
+    game.add_projectile(&Team::Player1, &p1_assist1, r"Djinn\shot\Orb", 0x1234).set_y_pos(999.0);
+    
+    execute_simulated(&p1, "proj.filter greaterthanequal, YPosition, 100.0, me");
+    assert_eq!(p1.get_condition_register(), 1);
+    execute_simulated(&p1, "load.proj r83, YPosition, me");
+    assert!(near_eq(get_register_f32(executor(&p1), 0x83), 200.0));
+    
+    execute_simulated(&p1, "proj.next");
+    execute_simulated(&p1, "load.proj r83, YPosition, me");
+    assert!(near_eq(get_register_f32(executor(&p1), 0x83), 150.0));
+    
+    execute_simulated(&p1, "proj.next");
+    assert_eq!(p1.get_condition_register(), 0);
+    
+    execute_simulated(&p1, "proj.filter lessthan, YPosition, 100.0, me backwards");
+    execute_simulated(&p1, "load.proj r84, YPosition, me");
+    assert!(near_eq(get_register_f32(executor(&p1), 0x84), 10.0));
+    
+    execute_simulated(&p1, r#"proj.filter.name "shot\beam", me"#);
+    assert_eq!(p1.get_condition_register(), 1);
+    execute_simulated(&p1, "load.proj r85, YPosition, me");
+    assert!(near_eq(get_register_f32(executor(&p1), 0x85), 150.0));
+    
+    execute_simulated(&p1_assist1, r#"proj.filter.name "shot\orb", me"#);
+    execute_simulated(&p1_assist1, "load.proj r85, YPosition, me");
+    assert!(near_eq(get_register_f32(executor(&p1_assist1), 0x85), 999.0));
+    
+    // player 2 has none of its own, but can look at player 1's
+    execute_simulated(&p2, "proj.get me");
+    assert_eq!(p2.get_condition_register(), 0);
+    execute_simulated(&p2, "proj.get opponent");
+    assert_eq!(p2.get_condition_register(), 1);
+    
+    // suck the opponent in, slowing down by 1 each frame
+    p1.set_x_pos(0.0);
+    p2.set_x_pos(300.0);
+    execute_simulated(&p1, "suck.x 20.0, 1.0");
+    crate::character_tick::generic_character_tick(p1.clone());
+    assert!(near_eq(p2.get_x_pos(), 319.0));
+    crate::character_tick::generic_character_tick(p1.clone());
+    assert!(near_eq(p2.get_x_pos(), 337.0));
+}
+
+
+/// a new frame for `character` followed by the whole stream of commands, one at a time like the game does
+fn execute_simulated_frame(character : &crate::game_data::Char, source : &str) -> &'static [u8]
+{
+    let bytes = Box::leak(crate::anmchr_asm::assemble(source).unwrap().into_boxed_slice());
+    
+    crate::character_tick::generic_character_tick(character.clone());
+    
+    let mut position = 0;
+    
+    while position < bytes.len() {
+        crate::execute_anmchr_command(character.get_ptr() + 0x1348, bytes.as_ptr() as usize + position);
+        
+        position += crate::anmchr_asm::command_len(&bytes[position..]).unwrap();
+    }
+    
+    bytes
+}
+
+#[test]
+fn test_command_flow() {
+    use crate::game_data::Team;
+    
+    let game = simulated_game();
+    
+    let p1 = game.add_character(&Team::Player1, 0, 1);
+    let executor = p1.get_ptr() + 0x1348;
+    
+    // skipping on a bool register, both ways
+    execute_simulated_frame(&p1, "
+        load.imm r01, 0
+        load.imm b02, true
+        skip.if b02, 1
+        add.imm r01, r01, 1
+        skip.if !b02, 1
+        add.imm r01, r01, 10
+    ");
+    assert_eq!(get_register_i32(executor, 0x01), 10);
+    
+    // the condition register is left alone by the skip itself
+    execute_simulated_frame(&p1, "
+        load.imm r03, 0
+        load.imm r04, 7
+        skip.if cond, 2
+        load.imm r03, 1
+        load.imm r03, 2
+    ");
+    assert_eq!(get_register_i32(executor, 0x03), 0);
+    assert_eq!(p1.get_condition_register(), 7);
+    
+    // a loop, counting up to 5
+    execute_simulated_frame(&p1, "
+        load.imm r05, 0
+        again:
+        add.imm r05, r05, 1
+        lessthan.imm r06, r05, 5
+        jump.if r06, again
+        add.imm r05, r05, 100
+    ");
+    assert_eq!(get_register_i32(executor, 0x05), 105);
+    
+    // forward jumps, and jumps inside of a loop
+    execute_simulated_frame(&p1, "
+        load.imm r07, 0
+        load.imm r08, 0
+        top:
+        add.imm r07, r07, 1
+        jump.if b09, odd
+        add.imm r08, r08, 10
+        jump flip
+        odd:
+        add.imm r08, r08, 1
+        flip:
+        logicalnot.reg b09, b09
+        lessthan.imm r0A, r07, 4
+        jump.if r0A, top
+    ");
+    assert_eq!(get_register_i32(executor, 0x07), 4);
+    assert_eq!(get_register_i32(executor, 0x08), 22);
+    
+    // a loop that never ends gives up eventually
+    execute_simulated_frame(&p1, "
+        load.imm r0B, 0
+        forever:
+        add.imm r0B, r0B, 1
+        jump forever
+    ");
+    assert!(get_register_i32(executor, 0x0B) > 1);
+    
+    // the game's own commands aren't counted or skipped, and only run once even inside of a loop
+    let bytes = execute_simulated_frame(&p1, "
+        load.imm r0D, 0
+        skip 1
+        .hex 01000000 B1000000 01000000 00000000 06000000 0000803F
+        add.imm r0D, r0D, 1
+        add.imm r0D, r0D, 10
+        again:
+        add.imm r0D, r0D, 100
+        .hex 01000000 B1000000 01000000 00000000 06000000 0000803F
+        lessthan.imm r0E, r0D, 300
+        jump.if r0E, again
+    ");
+    assert_eq!(get_register_i32(executor, 0x0D), 310);
+    assert_eq!(game_commands_run(bytes), 2);
+    
+    // skips carry on into the next frame while the character stays in the same anmchr entry
+    let stream = Box::leak(crate::anmchr_asm::assemble("
+        load.imm r0C, 0
+        skip 2
+        add.imm r0C, r0C, 1
+        add.imm r0C, r0C, 10
+        add.imm r0C, r0C, 100
+    ").unwrap().into_boxed_slice());
+    let mut offsets = vec![0];
+    
+    while *offsets.last().unwrap() < stream.len() {
+        let offset = *offsets.last().unwrap();
+        
+        offsets.push(offset + crate::anmchr_asm::command_len(&stream[offset..]).unwrap());
+    }
+    
+    let command_at = |index : usize| stream.as_ptr() as usize + offsets[index];
+    
+    crate::character_tick::generic_character_tick(p1.clone());
+    crate::execute_anmchr_command(executor, command_at(0));
+    crate::execute_anmchr_command(executor, command_at(1));
+    crate::execute_anmchr_command(executor, command_at(2));
+    crate::character_tick::generic_character_tick(p1.clone());
+    crate::execute_anmchr_command(executor, command_at(3));
+    crate::execute_anmchr_command(executor, command_at(4));
+    assert_eq!(get_register_i32(executor, 0x0C), 100);
+    
+    // but not into a different move
+    crate::character_tick::generic_character_tick(p1.clone());
+    crate::execute_anmchr_command(executor, command_at(0));
+    crate::execute_anmchr_command(executor, command_at(1));
+    p1.set_anmchr_id(p1.get_anmchr_id() + 1);
+    crate::execute_anmchr_command(executor, command_at(2));
+    assert_eq!(get_register_i32(executor, 0x0C), 1);
+    
+    // or the same one starting over
+    crate::character_tick::generic_character_tick(p1.clone());
+    crate::execute_anmchr_command(executor, command_at(1));
+    crate::execute_anmchr_command(executor, command_at(0));
+    crate::execute_anmchr_command(executor, command_at(2));
+    assert_eq!(get_register_i32(executor, 0x0C), 1);
+}
+
+#[test]
+fn test_random() {
+    use crate::game_data::Team;
+    use crate::unary_operators::{UnaryOp, UnaryOpHandler};
+    
+    let game = simulated_game();
+    
+    let rolls = || -> Vec<i32> {
+        crate::random::reset();
+        
+        (0..16).map(|_| UnaryOp::Random.operate(1000)).collect()
+    };
+    
+    // starting over gives the same rolls, which aren't all the same
+    let first = rolls();
+    assert_eq!(first, rolls());
+    assert!(first.iter().any(|&roll| roll != first[0]));
+    
+    let mut seen = [false; 6];
+    
+    for _ in 0..1000 {
+        let roll = crate::random::below_i32(6);
+        seen[roll as usize] = true;
+        
+        assert!((-5..=0).contains(&crate::random::below_i32(-6)));
+        
+        let roll = crate::random::below_f32(2.5);
+        assert!((0.0..=2.5).contains(&roll));
+    }
+    
+    assert!(seen.iter().all(|&is_seen| is_seen));
+    assert_eq!(crate::random::below_i32(0), 0);
+    
+    // 0 can't be used as a seed
+    crate::random::set_state(0);
+    assert_eq!(crate::random::get_state(), crate::random::DEFAULT_SEED as i32);
+    
+    // saving and restoring the state through a variable repeats a roll
+    let p1 = game.add_character(&Team::Player1, 0, 1);
+    let executor = p1.get_ptr() + 0x1348;
+    
+    execute_simulated_frame(&p1, "
+        load.var r01, RandomState, me
+        random.imm r02, 1000000
+        store.var r01, RandomState, me
+        random.imm r03, 1000000
+        random.imm r84, 1.0
+    ");
+    assert_eq!(get_register_i32(executor, 0x02), get_register_i32(executor, 0x03));
+    assert!((0.0..1.0).contains(&get_register_f32(executor, 0x84)));
+}
+
+#[test]
+fn test_ternary_operators() {
+    use crate::game_data::Team;
+    use crate::ternary_operators::{TernaryOp, TernaryOpHandler};
+    use crate::math::Number;
+    
+    assert_eq!(TernaryOp::Clamp.operate(150, 0, 100), 100);
+    assert_eq!(TernaryOp::Clamp.operate(-5, 100, 0), 0);
+    assert_eq!(TernaryOp::Clamp.operate(f32::NAN, 0.0, 1.0), 0.0);
+    assert_eq!(TernaryOp::Lerp.operate(10.0, 20.0, 0.25), 12.5);
+    assert_eq!(TernaryOp::Lerp.operate(10, 20, 50), 15);
+    assert_eq!(TernaryOp::Lerp.operate(0, i32::MAX, 300), i32::MAX);
+    assert_eq!(TernaryOp::Select.operate(0, 1, 2), 2);
+    assert_eq!(TernaryOp::Select.operate(0.5, 1.0, 2.0), 1.0);
+    assert_eq!(TernaryOp::MulAdd.operate(3.0, 4.0, 0.5), 12.5);
+    assert_eq!(TernaryOp::MulAdd.operate(i32::MAX, 2, 2), 0);
+    assert_eq!(TernaryOp::InverseLerp.operate(10.0, 20.0, 12.5), 0.25);
+    assert_eq!(TernaryOp::InverseLerp.operate(10, 20, 15), 50);
+    assert_eq!(TernaryOp::InverseLerp.operate(5.0, 5.0, 12.5), 0.0);
+    assert_eq!(TernaryOp::InverseLerp.operate(i32::MIN, i32::MAX, i32::MAX), 100);
+    assert_eq!(TernaryOp::SmoothStep.operate(0.0, 1.0, 0.5), 0.5);
+    assert_eq!(TernaryOp::SmoothStep.operate(0.0, 1.0, 2.0), 1.0);
+    assert_eq!(TernaryOp::SmoothStep.operate(0, 10, 5), 50);
+    assert_eq!(TernaryOp::SmoothStep.operate(0, 10, -5), 0);
+    assert_eq!(TernaryOp::SmoothStep.operate(3, 3, 3), 100);
+    
+    // only all integers gets integer math
+    assert_eq!(TernaryOp::Lerp.operate(Number::I32(10), Number::I32(20), Number::I32(50)), Number::I32(15));
+    assert_eq!(TernaryOp::Lerp.operate(Number::I32(10), Number::I32(20), Number::F32(0.5)), Number::F32(15.0));
+    
+    let game = simulated_game();
+    
+    let p1 = game.add_character(&Team::Player1, 0, 1);
+    let executor = p1.get_ptr() + 0x1348;
+    
+    execute_simulated_frame(&p1, "
+        load.imm r05, 150
+        clamp.imm r05, r05, 0, 100
+        load.imm r81, 2.0
+        load.imm r82, 0.5
+        lerp.imm r83, r82, 10.0, r81
+        load.imm b01, true
+        load.imm r06, 7
+        load.imm r07, 9
+        select.reg r08, b01, r06, r07
+        load.imm r09, 7
+        select.reg r0A, r0B, r06, [r09]
+        mul.imm r0C, r0C, 0
+        muladd.reg r0D, r06, r07, r05
+    ");
+    assert_eq!(get_register_i32(executor, 0x05), 100);
+    assert_eq!(get_register_f32(executor, 0x83), 19.5);
+    assert_eq!(get_register_i32(executor, 0x08), 7);
+    assert_eq!(get_register_i32(executor, 0x0A), 9);
+    assert_eq!(get_register_i32(executor, 0x0D), 163);
+    assert_eq!(p1.get_condition_register(), 163);
+    
+    // a float anywhere means float math, then it's cut down to fit the destination
+    execute_simulated(&p1, "lerp.reg r0E, r06, r07, r82");
+    assert_eq!(get_register_i32(executor, 0x0E), 8);
+}
+
+#[test]
+fn test_math_operators() {
+    use crate::binary_operators::{BinaryOp, BinaryOpHandler};
+    use crate::unary_operators::{UnaryOp, UnaryOpHandler};
+    use std::f32::consts::*;
+    
+    let binary_f32 = |op : BinaryOp, lhs : f32, rhs : f32| -> f32 { op.operate(lhs, rhs) };
+    let binary_i32 = |op : BinaryOp, lhs : i32, rhs : i32| -> i32 { op.operate(lhs, rhs) };
+    let is_close = |answer : f32, expected : f32| (answer - expected).abs() <= 0.0001 * expected.abs().max(1.0);
+    
+    // the approximations against the builtins
+    for i in -200..=200 {
+        let x = i as f32 * 0.37;
+        
+        for y in [-3.0, -0.5, 0.0, 0.01, 2.0, 50.0] {
+            let answer : f32 = BinaryOp::Atan2.operate(y, x);
+            assert!(is_close(answer, f32::atan2(y, x)) || (x == 0.0 && y == 0.0), "atan2 {} {} = {}", y, x, answer);
+        }
+        
+        let answer : f32 = UnaryOp::Exp.operate(x * 0.2);
+        assert!(is_close(answer, (x * 0.2).exp()), "exp {} = {}", x, answer);
+        
+        if x > 0.0 {
+            let answer : f32 = UnaryOp::Ln.operate(x);
+            assert!(is_close(answer, x.ln()), "ln {} = {}", x, answer);
+            let answer : f32 = UnaryOp::Log2.operate(x);
+            assert!(is_close(answer, x.log2()), "log2 {} = {}", x, answer);
+            let answer : f32 = BinaryOp::Pow.operate(x, 1.5);
+            assert!(is_close(answer, x.powf(1.5)), "pow {} = {}", x, answer);
+        }
+        
+        let answer : f32 = UnaryOp::NormalizeAngle.operate(x);
+        assert!((-PI..PI).contains(&answer) && is_close(answer.sin(), x.sin()), "normalize {} = {}", x, answer);
+    }
+    
+    // domains
+    assert_eq!(UnaryOp::Ln.operate(0.0), 0.0);
+    assert_eq!(UnaryOp::Log2.operate(-4.0), 0.0);
+    assert_eq!(UnaryOp::Log2.operate(f32::MIN_POSITIVE / 4.0), -128.0);
+    assert_eq!(UnaryOp::Exp.operate(1000.0), f32::MAX);
+    assert_eq!(UnaryOp::Exp.operate(-1000.0), 0.0);
+    assert_eq!(UnaryOp::Tan.operate(FRAC_PI_4), 1.0);
+    assert_eq!(binary_f32(BinaryOp::Atan2, 0.0, 0.0), 0.0);
+    assert_eq!(binary_f32(BinaryOp::Pow, 3.0, 2.0), 9.0);
+    assert_eq!(binary_f32(BinaryOp::Pow, 2.0, -2.0), 0.25);
+    assert_eq!(binary_f32(BinaryOp::Pow, -2.0, 3.0), -8.0);
+    assert!(is_close(binary_f32(BinaryOp::Pow, -9.0, 0.5), -3.0));
+    assert_eq!(binary_f32(BinaryOp::Pow, 0.0, -1.0), 0.0);
+    assert_eq!(binary_f32(BinaryOp::Pow, 10.0, 100.0), f32::MAX);
+    assert_eq!(binary_f32(BinaryOp::Hypot, 3.0, 4.0), 5.0);
+    assert_eq!(binary_f32(BinaryOp::Hypot, f32::MAX, f32::MAX), f32::MAX);
+    
+    // integers use the same angle units as Sin and Cos
+    assert_eq!(crate::math::DEGREES_PER_TURN, 120);
+    assert_eq!(binary_i32(BinaryOp::Atan2, 1, 0), 30);
+    assert_eq!(binary_i32(BinaryOp::Atan2, -5, -5), -45);
+    assert_eq!(UnaryOp::Tan.operate(15), 16384);
+    assert_eq!(UnaryOp::NormalizeAngle.operate(60), -60);
+    assert_eq!(UnaryOp::NormalizeAngle.operate(-61), 59);
+    assert_eq!(UnaryOp::NormalizeAngle.operate(i32::MIN), UnaryOp::NormalizeAngle.operate(i32::MIN.rem_euclid(120)));
+    
+    // integer saturation
+    assert_eq!(binary_i32(BinaryOp::Pow, 2, 10), 1024);
+    assert_eq!(binary_i32(BinaryOp::Pow, 10, 10), i32::MAX);
+    assert_eq!(binary_i32(BinaryOp::Pow, -10, 11), i32::MIN);
+    assert_eq!(binary_i32(BinaryOp::Pow, 2, -1), 0);
+    assert_eq!(binary_i32(BinaryOp::Pow, -1, -3), -1);
+    assert_eq!(binary_i32(BinaryOp::Hypot, 3, -4), 5);
+    assert_eq!(binary_i32(BinaryOp::Hypot, i32::MIN, i32::MIN), i32::MAX);
+    assert_eq!(UnaryOp::Exp.operate(2), 7);
+    assert_eq!(UnaryOp::Exp.operate(-2), 0);
+    assert!((1_318_800_000..=1_318_830_000).contains(&UnaryOp::Exp.operate(21)));
+    assert_eq!(UnaryOp::Exp.operate(22), i32::MAX);
+    assert_eq!(UnaryOp::Ln.operate(100), 4);
+    assert_eq!(UnaryOp::Ln.operate(-100), 0);
+    assert_eq!(UnaryOp::Log2.operate(15), 3);
+    assert_eq!(UnaryOp::Log2.operate(0), 0);
+}
+
+#[test]
+fn test_vectors() {
+    use crate::game_data::Team;
+    use crate::vector::Vec2;
+    
+    let game = simulated_game();
+    
+    let p1 = game.add_character(&Team::Player1, 0, 1);
+    let p2 = game.add_character(&Team::Player2, 0, 2);
+    let executor = p1.get_ptr() + 0x1348;
+    let vector = |index : u8| crate::storage::with(p1.get_ptr(), |store| store.get_vector(index));
+    
+    p1.set_x_pos(100.0);
+    p1.set_y_pos(0.0);
+    p2.set_x_pos(400.0);
+    p2.set_y_pos(400.0);
+    
+    // move 150 toward the opponent along the line between us
+    execute_simulated_frame(&p1, "
+        vec.load v01, me
+        vec.dir v02, opponent
+        vec.scale v02, v02, 150.0
+        vec.add v01, v01, v02
+        vec.store v01, me
+    ");
+    assert!(near_eq(p1.get_x_pos(), 190.0));
+    assert!(near_eq(p1.get_y_pos(), 120.0));
+    assert_eq!(p1.get_condition_register(), 1);
+    
+    execute_simulated_frame(&p1, "
+        vec.length r81, v02
+        vec.x r82, v02
+        vec.y r03, v02
+        vec.load v03, opponent
+        vec.dot r84, v02, v03
+        vec.make v05, r82, r03
+        load.imm r85, 1.5707964
+        vec.rotate v06, v02, r85
+        load.imm r07, 2
+        vec.normalize [r07], v02
+    ");
+    assert!(near_eq(get_register_f32(executor, 0x81), 150.0));
+    assert!(near_eq(get_register_f32(executor, 0x82), 90.0));
+    assert_eq!(get_register_i32(executor, 0x03), 120);
+    assert!(near_eq(get_register_f32(executor, 0x84), 90.0 * 400.0 + 120.0 * 400.0));
+    assert_eq!(vector(0x05), Vec2::new(90.0, 120.0));
+    assert!((vector(0x06).x + 120.0).abs() < 0.5 && (vector(0x06).y - 90.0).abs() < 0.5, "{:?}", vector(0x06));
+    assert!(near_eq(vector(0x02).x, 0.6) && near_eq(vector(0x02).y, 0.8));
+    
+    // standing in the same spot has no direction
+    p2.set_x_pos(190.0);
+    p2.set_y_pos(120.0);
+    execute_simulated(&p1, "vec.dir v08, opponent");
+    assert_eq!(vector(0x08), Vec2::ZERO);
+    assert_eq!(p1.get_condition_register(), 0);
+    
+    // projectile positions, through the projectile list
+    let projectile = game.add_projectile(&Team::Player1, &p1, r"Djinn\shot\Orb", 0x1234);
+    projectile.set_x_pos(10.0);
+    projectile.set_y_pos(20.0);
+    
+    execute_simulated(&p1, "vec.store v01, me proj");
+    assert_eq!(p1.get_condition_register(), 0);
+    
+    execute_simulated(&p1, "proj.get me");
+    execute_simulated(&p1, "vec.load v09, me proj");
+    assert_eq!(vector(0x09), Vec2::new(10.0, 20.0));
+    execute_simulated(&p1, "vec.store v01, me proj");
+    assert_eq!(p1.get_condition_register(), 1);
+    assert!(near_eq(projectile.get_x_pos(), 190.0) && near_eq(projectile.get_y_pos(), 120.0));
+}
+
+#[test]
+fn test_shared_registers() {
+    use crate::game_data::Team;
+    
+    let game = simulated_game();
+    
+    let p1 = game.add_character(&Team::Player1, 0, 1);
+    let p1_assist1 = game.add_character(&Team::Player1, 1, 2);
+    let p2 = game.add_character(&Team::Player2, 0, 3);
+    let executor = |character : &crate::game_data::Char| character.get_ptr() + 0x1348;
+    
+    // the team bank is shared with the assist, but not with the other team
+    execute_simulated_frame(&p1, "load.imm r01, 5\nstore.shared r01, team, r40");
+    assert_eq!(p1.get_condition_register(), 5);
+    execute_simulated(&p1_assist1, "load.shared r02, team, r40");
+    assert_eq!(get_register_i32(executor(&p1_assist1), 0x02), 5);
+    assert_eq!(p1_assist1.get_condition_register(), 5);
+    execute_simulated(&p2, "load.shared r02, team, r40");
+    assert_eq!(get_register_i32(executor(&p2), 0x02), 0);
+    execute_simulated(&p2, "load.shared r03, opponent, r40");
+    assert_eq!(get_register_i32(executor(&p2), 0x03), 5);
+    
+    // the match bank is shared by everyone, and converts between int and float like the other register commands
+    execute_simulated_frame(&p2, "load.imm r81, 2.5\nstore.shared r81, match, rC0");
+    execute_simulated_frame(&p1, "load.shared r84, match, rC0\nload.shared r04, match, rC0");
+    assert!(near_eq(get_register_f32(executor(&p1), 0x84), 2.5));
+    assert_eq!(get_register_i32(executor(&p1), 0x04), 2);
+    
+    // bools and indirect registers on both sides
+    execute_simulated_frame(&p1, "
+        load.imm b01, true
+        load.imm r05, 0x41
+        store.shared b01, team, [r05]
+        load.imm r06, 0x07
+        load.shared [r06], team, r41
+        load.imm r85, 1.0
+        store.shared r85, team, b09
+        load.shared b0A, team, b09
+    ");
+    crate::storage::with_shared_bank(Team::Player1, crate::storage::SharedBankSelect::MyTeam, (), |bank| {
+        assert_eq!(bank.get_number(0x41), crate::math::Number::I32(1));
+        assert!(bank.get_bool(0x09));
+    });
+    assert_eq!(get_register_i32(executor(&p1), 0x07), 1);
+    assert!(storage::with(p1.get_ptr(), |store| store.get_bool(0x0A)));
+}
+
+#[test]
+fn test_register_persistence() {
+    use crate::game_data::Team;
+    use crate::storage::{Persistence, ResetKind};
+    
+    let game = simulated_game();
+    
+    let p1 = game.add_character(&Team::Player1, 0, 1);
+    
+    execute_simulated_frame(&p1, "
+        load.imm r01, 1
+        load.imm r02, 2
+        load.imm r83, 3.0
+        load.imm b04, true
+        load.imm b05, true
+        persist match, r02
+        persist unload, r80, r8F
+        persist unload, b05, b05
+    ");
+    
+    let kept = |kind| storage::with(p1.get_ptr(), |store| store.carry_over(kind));
+    
+    // a training mode reset keeps everything that isn't reset on round
+    let mut round = kept(ResetKind::Round).unwrap();
+    assert_eq!(round.get_i32_register(0x01), 0);
+    assert_eq!(round.get_i32_register(0x02), 2);
+    assert!(near_eq(round.get_f32_register(0x83), 3.0));
+    assert!(!round.get_bool(0x04));
+    assert!(round.get_bool(0x05));
+    
+    // a new match only keeps what's kept until unload, and the policies carry over too so it keeps working
+    let mut matched = round.carry_over(ResetKind::Match).unwrap();
+    assert_eq!(matched.get_i32_register(0x02), 0);
+    assert!(near_eq(matched.get_f32_register(0x83), 3.0));
+    assert!(matched.get_bool(0x05));
+    assert!(matched.carry_over(ResetKind::Match).is_some());
+    
+    // but not if a different character got loaded in the same spot
+    matched.set_persistence(0x05, 0x05, true, Persistence::KeepUntilUnload, 0x55);
+    assert!(matched.carry_over(ResetKind::Match).is_none());
+    
+    // or if the character isn't loaded at all
+    let unloaded = storage::with(0x7777_0000, |store| {
+        store.set_i32_register(0x01, 5);
+        store.set_persistence(0x01, 0x01, false, Persistence::KeepUntilUnload, 1);
+        
+        (store.carry_over(ResetKind::Round).is_some(), store.carry_over(ResetKind::Match).is_some())
+    });
+    assert_eq!(unloaded, (true, false));
+    
+    // nothing to keep means no store at all
+    let p2 = game.add_character(&Team::Player2, 0, 2);
+    execute_simulated(&p2, "load.imm r01, 1");
+    assert!(storage::with(p2.get_ptr(), |store| store.carry_over(ResetKind::Round)).is_none());
+}
+
+#[test]
+fn test_snapshots() {
+    use crate::game_data::Team;
+    use crate::snapshot::{SnapshotError, SNAPSHOT_VERSION};
+    use crate::anmchr_asm::{assemble, disassemble};
+    
+    let game = simulated_game();
+    
+    let p1 = game.add_character(&Team::Player1, 0, 1);
+    let p2 = game.add_character(&Team::Player2, 0, 2);
+    game.add_projectile(&Team::Player1, &p1, r"Djinn\shot\Orb", 0x1234);
+    let executor = p1.get_ptr() + 0x1348;
+    
+    for text in [r#"snapshot.save "training 1""#, r#"snapshot.load "a""#] {
+        let bytes = assemble(text).unwrap();
+        assert_eq!(bytes.len(), 8 + 64);
+        assert_eq!(assemble(&disassemble(&bytes)).unwrap(), bytes, "round trip of {}", text);
+    }
+    
+    // a store that's never been used is just the header
+    let empty = storage::with(0x6666_0000, |store| store.save_snapshot());
+    assert_eq!(empty.len(), 8);
+    assert_eq!(&empty[0..4], b"MPCS");
+    
+    execute_simulated_frame(&p1, "
+        load.imm r01, -7
+        load.imm r82, 2.5
+        load.imm b03, true
+        load.imm bFF, true
+        vec.load v04, opponent
+        persist match, r01
+        suck.x 20.0, 1.0
+        proj.get me
+    ");
+    
+    // the same bytes come back out after loading them into another store
+    let saved = storage::with(p1.get_ptr(), |store| store.save_snapshot());
+    let reloaded = storage::with(0x6666_0100, |store| {
+        store.load_snapshot(&saved).unwrap();
+        
+        assert_eq!(store.get_i32_register(0x01), -7);
+        assert!(near_eq(store.get_f32_register(0x82), 2.5));
+        assert!(store.get_bool(0x03) && store.get_bool(0xFF) && !store.get_bool(0x04));
+        assert_eq!(store.get_vector(0x04), crate::vector::Vec2::new(p2.get_x_pos(), p2.get_y_pos()));
+        assert!(store.projectile_filter.as_ref().is_some_and(|filter| filter.projectile.is_some()));
+        
+        store.save_snapshot()
+    });
+    assert_eq!(reloaded, saved);
+    
+    // broken snapshots don't change anything
+    let load = |bytes : &[u8]| storage::with(0x6666_0200, |store| store.load_snapshot(bytes));
+    assert_eq!(load(&saved[..saved.len() - 1]), Err(SnapshotError::Truncated));
+    assert_eq!(load(&[saved.as_slice(), &[0]].concat()), Err(SnapshotError::TrailingBytes(1)));
+    assert_eq!(load(b"MPC"), Err(SnapshotError::BadMagic));
+    let mut newer = saved.clone();
+    newer[4..6].copy_from_slice(&(SNAPSHOT_VERSION + 1).to_le_bytes());
+    assert_eq!(load(&newer), Err(SnapshotError::UnsupportedVersion(SNAPSHOT_VERSION + 1)));
+    assert_eq!(storage::with(0x6666_0200, |store| store.get_i32_register(0x01)), 0);
+    
+    // named slots
+    execute_simulated_frame(&p1, r#"
+        snapshot.save "setup"
+        load.imm r01, 100
+        load.imm b03, false
+    "#);
+    assert_eq!(get_register_i32(executor, 0x01), 100);
+    execute_simulated(&p1, r#"snapshot.load "setup""#);
+    assert_eq!(p1.get_condition_register(), 1);
+    assert_eq!(get_register_i32(executor, 0x01), -7);
+    assert!(storage::with(p1.get_ptr(), |store| store.get_bool(0x03)));
+    
+    // slots belong to the character that saved them
+    execute_simulated(&p2, r#"snapshot.load "setup""#);
+    assert_eq!(p2.get_condition_register(), 0);
+    execute_simulated(&p1, r#"snapshot.load "nothing here""#);
+    assert_eq!(p1.get_condition_register(), 0);
+    assert_eq!(get_register_i32(executor, 0x01), -7);
+}
+
+#[test]
+fn test_checksum() {
+    use crate::game_data::Team;
+    use crate::checksum::Checksum;
+    
+    // FNV-1a test vectors, so the hash never quietly changes
+    assert_eq!(Checksum::new().finish(), 0xcbf29ce484222325);
+    let mut checksum = Checksum::new();
+    checksum.write(b"a");
+    assert_eq!(checksum.finish(), 0xaf63dc4c8601ec8c);
+    
+    let game = simulated_game();
+    
+    let p1 = game.add_character(&Team::Player1, 0, 1);
+    let p1_assist1 = game.add_character(&Team::Player1, 1, 2);
+    let p2 = game.add_character(&Team::Player2, 0, 3);
+    let p1_child = game.add_child(&Team::Player1, &p1);
+    
+    assert_eq!(p1.team_slot(), Some((Team::Player1, 0)));
+    assert_eq!(p1_assist1.team_slot(), Some((Team::Player1, 1)));
+    assert_eq!(p1_child.team_slot(), Some((Team::Player1, 2)));
+    assert_eq!(p2.team_slot(), Some((Team::Player2, 0)));
+    assert_eq!(crate::game_data::Char::new(0x5555_0000).team_slot(), None);
+    
+    let hash = |character : &crate::game_data::Char| storage::with(character.get_ptr(), |store| store.checksum());
+    
+    // the same things in two different stores hash the same, no matter where they are
+    execute_simulated_frame(&p1, "load.imm r01, 3\nload.imm r81, 0.5\nload.imm b02, true");
+    execute_simulated_frame(&p2, "load.imm r81, 0.5\nload.imm b02, true\nload.imm r01, 3");
+    assert_eq!(hash(&p1), hash(&p2));
+    
+    // and anything different hashes differently
+    execute_simulated(&p2, "load.imm r01, 4");
+    assert_ne!(hash(&p1), hash(&p2));
+    execute_simulated(&p2, "load.imm r01, 3");
+    p1.set_x_pos(100.0);
+    execute_simulated(&p2, "vec.load v00, opponent");
+    assert_ne!(hash(&p1), hash(&p2));
+    
+    // setting a register to 0 is the same as never setting it
+    execute_simulated(&p1_assist1, "load.imm r05, 0");
+    assert_eq!(hash(&p1_assist1), storage::with(0x5555_0100, |store| store.checksum()));
+    
+    // the whole thing can be called from outside, it just has to not crash while other tests are changing things
+    crate::mag_patch_state_checksum();
+}
+
+#[test]
+fn test_scheduled_commands() {
+    use crate::game_data::Team;
+    use crate::anmchr_asm::assemble;
+    use crate::anmchr_validate::{validate, Severity};
+    
+    let game = simulated_game();
+    
+    let p1 = game.add_character(&Team::Player1, 0, 1);
+    let executor = p1.get_ptr() + 0x1348;
+    
+    // the next command is taken out and runs two ticks later
+    execute_simulated_frame(&p1, "
+        load.imm r01, 0
+        schedule 2
+        add.imm r01, r01, 5
+    ");
+    assert_eq!(p1.get_condition_register(), 1);
+    assert_eq!(get_register_i32(executor, 0x01), 0);
+    execute_simulated_frame(&p1, "");
+    assert_eq!(get_register_i32(executor, 0x01), 0);
+    execute_simulated_frame(&p1, "");
+    assert_eq!(get_register_i32(executor, 0x01), 5);
+    execute_simulated_frame(&p1, "");
+    assert_eq!(get_register_i32(executor, 0x01), 5);
+    
+    // repeating every 2 frames, in whatever animation we're in by then, until it's cancelled
+    execute_simulated_frame(&p1, "
+        load.imm r02, 0
+        schedule 1, 2, 7
+        add.imm r02, r02, 1
+        schedule 3, 0, 7
+        add.imm r02, r02, 100
+    ");
+    for _ in 0..5 {
+        execute_simulated_frame(&p1, "load.imm r03, 1");
+    }
+    assert_eq!(get_register_i32(executor, 0x02), 103);
+    execute_simulated(&p1, "schedule.cancel 7");
+    assert_eq!(p1.get_condition_register(), 1);
+    execute_simulated_frame(&p1, "");
+    execute_simulated_frame(&p1, "");
+    assert_eq!(get_register_i32(executor, 0x02), 103);
+    execute_simulated(&p1, "schedule.cancel 7");
+    assert_eq!(p1.get_condition_register(), 0);
+    
+    // delays can come from a register, and are read when scheduling. the command's own registers are read when it runs
+    execute_simulated_frame(&p1, "
+        load.imm r04, 2
+        load.imm r81, 1.0
+        schedule r04, 0, 1
+        add.imm r82, r81, r81
+        load.imm r04, 10
+        load.imm r81, 4.0
+    ");
+    execute_simulated_frame(&p1, "");
+    execute_simulated_frame(&p1, "");
+    assert!(near_eq(get_register_f32(executor, 0x82), 8.0));
+    
+    // anything else runs right away instead
+    execute_simulated_frame(&p1, "
+        load.imm r06, 0
+        schedule 1, 0, 2
+        proj.next
+        add.imm r06, r06, 1
+    ");
+    assert_eq!(get_register_i32(executor, 0x06), 1);
+    execute_simulated(&p1, "schedule.cancel 2");
+    assert_eq!(p1.get_condition_register(), 0);
+    
+    // what's scheduled goes into snapshots
+    execute_simulated_frame(&p1, "schedule 10, 0, 3\nadd.imm r07, r07, 1");
+    let saved = storage::with(p1.get_ptr(), |store| store.save_snapshot());
+    storage::with(0x7777_0000, |store| {
+        store.load_snapshot(&saved).unwrap();
+        assert_eq!(store.save_snapshot(), saved);
+        
+        assert_eq!(store.schedule.cancel(3), 1);
+        assert!(store.save_snapshot().len() < saved.len());
+    });
+    execute_simulated(&p1, "schedule.cancel 3");
+    
+    let count = |source : &str| validate(&assemble(source).unwrap()).iter().filter(|d| d.severity == Severity::Error).count();
+    assert_eq!(count("schedule 1\nadd.imm r01, r01, 1"), 0);
+    assert_eq!(count("schedule r01, r02\nload.var r01, Meter, me"), 0);
+    assert_eq!(count("schedule 1\nproj.next"), 1);
+    assert_eq!(count("schedule -1000\nadd.imm r01, r01, 1"), 1);
+    assert_eq!(count("schedule 1"), 1);
+}
+
+#[test]
+fn test_forces() {
+    use crate::game_data::Team;
+    use crate::anmchr_asm::assemble;
+    use crate::anmchr_validate::{validate, Severity};
+    
+    let game = simulated_game();
+    
+    let p1 = game.add_character(&Team::Player1, 0, 1);
+    let p2 = game.add_character(&Team::Player2, 0, 2);
+    let p2_assist1 = game.add_character(&Team::Player2, 1, 3);
+    let orb = game.add_projectile(&Team::Player2, &p2, r"Djinn\shot\Orb", 0x1234);
+    
+    let place = |x : f32, y : f32| {
+        p1.set_x_pos(0.0);
+        p1.set_y_pos(0.0);
+        p2.set_x_pos(x);
+        p2.set_y_pos(y);
+    };
+    let tick = || crate::character_tick::generic_character_tick(p1.clone());
+    
+    // two forces at once, one pushing forward and up and one pulling back
+    place(300.0, 0.0);
+    execute_simulated(&p1, "force 0, opponent, 10.0, 5.0, linear, 0.0, 0.0");
+    assert_eq!(p1.get_condition_register(), 1);
+    execute_simulated(&p1, "force 1, opponent, -4.0, 0.0, linear, 0.0, 0.0");
+    tick();
+    assert!(near_eq(p2.get_x_pos(), 306.0));
+    assert!(near_eq(p2.get_y_pos(), 5.0));
+    
+    // forward is whichever way we're facing when the force is set
+    execute_simulated(&p1, "force.clear 0");
+    game.set_facing_left(&p1, true);
+    execute_simulated(&p1, "force 0, opponent, 10.0, 0.0, linear, 0.0, 0.0");
+    game.set_facing_left(&p1, false);
+    tick();
+    assert!(near_eq(p2.get_x_pos(), 292.0));
+    execute_simulated(&p1, "force.clear all");
+    tick();
+    assert!(near_eq(p2.get_x_pos(), 292.0));
+    
+    // halving every frame, and linear slowing down until it stops
+    place(300.0, 0.0);
+    execute_simulated(&p1, "force 2, opponent, 16.0, 0.0, exp, 0.5, 0.0");
+    execute_simulated(&p1, "force 3, opponent, 3.0, 0.0, linear, 1.0, 0.0");
+    for _ in 0..4 {
+        tick();
+    }
+    assert!(near_eq(p2.get_x_pos(), 300.0 + 8.0 + 4.0 + 2.0 + 1.0 + 2.0 + 1.0));
+    
+    // radial forces pull toward us, and fade out with distance
+    place(300.0, 400.0);
+    execute_simulated(&p1, "force.clear all");
+    execute_simulated(&p1, "force 4, opponent, -30.0, 0.0, linear, 0.0, 1000.0 radial");
+    tick();
+    assert!(near_eq(p2.get_x_pos(), 300.0 - 30.0 * 0.5 * 0.6));
+    assert!(near_eq(p2.get_y_pos(), 400.0 - 30.0 * 0.5 * 0.8));
+    place(3000.0, 0.0);
+    tick();
+    assert!(near_eq(p2.get_x_pos(), 3000.0));
+    
+    // other characters' assists and projectiles
+    execute_simulated(&p1, "force.clear 4");
+    place(300.0, 0.0);
+    p2_assist1.set_x_pos(500.0);
+    orb.set_x_pos(100.0);
+    execute_simulated(&p1, "force 5, opponent.assist1, 7.0, 0.0, linear, 0.0, 0.0");
+    execute_simulated(&p1, "force 6, opponent, -20.0, 0.0, linear, 0.0, 0.0 proj");
+    tick();
+    assert!(near_eq(p2_assist1.get_x_pos(), 507.0));
+    assert!(near_eq(orb.get_x_pos(), 80.0));
+    assert!(near_eq(p2.get_x_pos(), 300.0));
+    execute_simulated(&p1, "force.clear all");
+    
+    // 66_50 has its own slot, so it doesn't replace any of the others
+    place(300.0, 0.0);
+    execute_simulated(&p1, "force 0, opponent, 1.0, 0.0, linear, 0.0, 0.0");
+    execute_simulated(&p1, "suck.x 20.0, 1.0");
+    tick();
+    assert!(near_eq(p2.get_x_pos(), 320.0));
+    execute_simulated(&p1, "force.clear all");
+    
+    // forces go in snapshots, and version 1 snapshots still load their suck
+    execute_simulated(&p1, "force 2, opponent, 16.0, 0.0, exp, 0.5, 0.0");
+    let saved = storage::with(p1.get_ptr(), |store| store.save_snapshot());
+    storage::with(0x7777_0100, |store| {
+        store.load_snapshot(&saved).unwrap();
+        assert_eq!(store.save_snapshot(), saved);
+        
+        let version_1 = to_bytes("4D504353 0100 0000 0000A041 0000803F");
+        store.load_snapshot(&version_1).unwrap();
+        assert!(!store.forces.is_empty());
+    });
+    execute_simulated(&p1, "force.clear all");
+    
+    let errors = |bytes : &[u8]| validate(bytes).iter().filter(|d| d.severity == Severity::Error).count();
+    assert_eq!(errors(&assemble("force 0, opponent, 1.0, 0.0, linear, 0.0, 0.0\nforce.clear all").unwrap()), 0);
+    assert_eq!(errors(&to_bytes("66000000 51000000 09800002 00000040 00000000 00000000 00000000")), 3);
+    assert_eq!(errors(&to_bytes("66000000 52000000 08000000")), 1);
+    assert!(assemble("force 8, opponent, 1.0, 0.0, linear, 0.0, 0.0").is_err());
+    assert!(assemble("force 0, opponent, 1.0, 0.0, linear, 0.0, 0.0 sideways").is_err());
+}
+
+#[test]
+fn test_velocity_vars() {
+    use crate::game_data::Team;
+    use crate::anmchr_asm::assemble;
+    use crate::anmchr_validate::{validate, Severity};
+    
+    let game = simulated_game();
+    
+    let p1 = game.add_character(&Team::Player1, 0, 1);
+    let p2 = game.add_character(&Team::Player2, 0, 2);
+    let orb = game.add_projectile(&Team::Player1, &p1, r"Djinn\shot\Orb", 0x1234);
+    
+    let executor = |c : &crate::game_data::Char| c.get_ptr() + 0x1348;
+    
+    p1._set_x_velocity_dont_use(3.0);
+    p1._set_y_acceleration_dont_use(-0.5);
+    execute_simulated(&p1, "load.var r80, XVelocityReadOnly, me");
+    execute_simulated(&p1, "load.var r81, YAccelerationReadOnly, me");
+    assert!(near_eq(get_register_f32(executor(&p1), 0x80), 3.0));
+    assert!(near_eq(get_register_f32(executor(&p1), 0x81), -0.5));
+    
+    // forward is stage x facing right, and the other way facing left
+    execute_simulated(&p1, "load.var r80, ForwardXVelocityReadOnly, me");
+    assert!(near_eq(get_register_f32(executor(&p1), 0x80), 3.0));
+    game.set_facing_left(&p1, true);
+    execute_simulated(&p1, "load.var r80, ForwardXVelocityReadOnly, me");
+    assert!(near_eq(get_register_f32(executor(&p1), 0x80), -3.0));
+    
+    // nobody has checked what writing them does in game, so they don't change
+    execute_simulated(&p1, "store.var.imm XVelocityReadOnly, me, 12.0");
+    assert!(near_eq(p1.get_x_velocity(), 3.0));
+    let errors = |source : &str| validate(&assemble(source).unwrap()).iter().filter(|d| d.severity == Severity::Error).count();
+    assert_eq!(errors("store.var.imm ForwardXVelocityReadOnly, me, 12.0"), 1);
+    
+    // projectiles go forward the way their owner is facing
+    orb._set_x_velocity_dont_use(-6.0);
+    execute_simulated(&p1, "proj.get me");
+    execute_simulated(&p1, "load.proj r82, ForwardXVelocityReadOnly, me");
+    assert!(near_eq(get_register_f32(executor(&p1), 0x82), 6.0));
+    game.set_facing_left(&p1, false);
+    execute_simulated(&p1, "load.proj r82, ForwardXVelocityReadOnly, me");
+    assert!(near_eq(get_register_f32(executor(&p1), 0x82), -6.0));
+    
+    // on the opponent too
+    p2._set_y_velocity_dont_use(8.0);
+    execute_simulated(&p1, "load.var r83, YVelocityReadOnly, opponent");
+    assert!(near_eq(get_register_f32(executor(&p1), 0x83), 8.0));
+}
+
+#[test]
+fn test_combo_and_xfactor_vars() {
+    use crate::game_data::Team;
+    use crate::anmchr_asm::assemble;
+    use crate::anmchr_validate::{validate, Severity};
+    
+    let game = simulated_game();
+    
+    let p1 = game.add_character(&Team::Player1, 0, 1);
+    let p2 = game.add_character(&Team::Player2, 0, 2);
+    
+    let executor = |c : &crate::game_data::Char| c.get_ptr() + 0x1348;
+    
+    let player = game.player(&Team::Player1);
+    player._set_xfactor_max_timer_dont_use(600.0);
+    player._set_xfactor_level_dont_use(2);
+    player._set_xfactor_timer_dont_use(450.0);
+    execute_simulated(&p1, "load.var r80, XFactorTimerReadOnly, me");
+    assert!(near_eq(get_register_f32(executor(&p1), 0x80), 450.0));
+    execute_simulated(&p1, "load.var r01, XFactorActiveReadOnly, me");
+    assert_eq!(get_register_i32(executor(&p1), 0x01), 1);
+    execute_simulated(&p1, "load.var r02, XFactorLevelReadOnly, me");
+    assert_eq!(get_register_i32(executor(&p1), 0x02), 2);
+    execute_simulated(&p1, "load.var r81, XFactorMaxTimeReadOnly, me");
+    assert!(near_eq(get_register_f32(executor(&p1), 0x81), 600.0));
+    
+    // the other team's is separate
+    execute_simulated(&p1, "load.var r01, XFactorActiveReadOnly, opponent");
+    assert_eq!(get_register_i32(executor(&p1), 0x01), 0);
+    
+    // scaling is on the character getting hit
+    p2._set_damage_scaling_dont_use(0.8);
+    p2._set_hitstun_scaling_dont_use(0.6);
+    execute_simulated(&p1, "load.var r80, DamageScalingReadOnly, opponent");
+    assert!(near_eq(get_register_f32(executor(&p1), 0x80), 0.8));
+    execute_simulated(&p1, "load.var r80, HitstunScalingReadOnly, opponent");
+    assert!(near_eq(get_register_f32(executor(&p1), 0x80), 0.6));
+    
+    p2._set_times_hit_dont_use(7);
+    execute_simulated(&p1, "load.var r03, TimesHitReadOnly, opponent");
+    assert_eq!(get_register_i32(executor(&p1), 0x03), 7);
+    
+    // both bounces share a byte
+    p2._set_wall_bounce_used_dont_use(true);
+    execute_simulated(&p1, "load.var r04, WallBounceUsedReadOnly, opponent");
+    assert_eq!(get_register_i32(executor(&p1), 0x04), 1);
+    execute_simulated(&p1, "load.var r04, GroundBounceUsedReadOnly, opponent");
+    assert_eq!(get_register_i32(executor(&p1), 0x04), 0);
+    
+    // nobody has checked what writing any of these does in game, so they can't be stored to
+    execute_simulated(&p1, "store.var.imm XFactorTimerReadOnly, me, 900.0");
+    assert!(near_eq(player.get_xfactor_timer(), 450.0));
+    execute_simulated(&p1, "store.var.imm DamageScalingReadOnly, opponent, 1.0");
+    assert!(near_eq(p2.get_damage_scaling(), 0.8));
+    execute_simulated(&p1, "store.var.imm WallBounceUsedReadOnly, opponent, 0");
+    assert!(p2.get_wall_bounce_used());
+    
+    let count = |source : &str| validate(&assemble(source).unwrap()).iter().filter(|d| d.severity == Severity::Error).count();
+    assert_eq!(count("store.var r01, TimesHitReadOnly, opponent"), 1);
+    assert_eq!(count("store.var r01, XFactorLevelReadOnly, me"), 1);
+    assert_eq!(count("store.var.imm HitstunScalingReadOnly, opponent, 0.5"), 1);
+    assert_eq!(count("load.var r01, GroundBounceUsedReadOnly, opponent"), 0);
+}
+
+#[test]
+fn test_motion_inputs() {
+    use crate::game_data::Team;
+    use crate::anmchr_asm::assemble;
+    use crate::anmchr_validate::{validate, Severity};
+    
+    let game = simulated_game();
+    
+    let p1 = game.add_character(&Team::Player1, 0, 1);
+    let p2 = game.add_character(&Team::Player2, 0, 2);
+    
+    // 1 is forward, 2 is back, 4 is up and 8 is down
+    let hold = |inputs : &[i32]| {
+        for inputs in inputs {
+            p1.set_inputs_raw(*inputs);
+            crate::character_tick::generic_character_tick(p1.clone());
+        }
+    };
+    let neutral = || hold(&[0; 30]);
+    let check = |motion : &str| {
+        execute_simulated(&p1, &format!("motion r02, me, {}", motion));
+        p1.get_condition_register() == 1
+    };
+    
+    // 236, with some slop in between
+    hold(&[8, 8, 2, 9, 9, 1, 1]);
+    assert!(check("qcf"));
+    assert!(!check("qcb"));
+    assert!(!check("dp"));
+    execute_simulated(&p1, "motion b01, me, qcf");
+    assert!(storage::with(p1.get_ptr(), |store| store.get_bool(0x01)));
+    
+    // the other side can see it too
+    execute_simulated(&p2, "motion r02, opponent, qcf");
+    assert_eq!(p2.get_condition_register(), 1);
+    
+    // too long ago for the default window, but not for a longer one
+    hold(&[0; 20]);
+    assert!(!check("qcf"));
+    assert!(check("qcf, 30"));
+    
+    neutral();
+    hold(&[1, 0, 8, 9]);
+    assert!(check("dp"));
+    assert!(!check("qcf"));
+    
+    neutral();
+    hold(&[1, 9, 8, 10, 2, 6, 4]);
+    assert!(check("360"));
+    neutral();
+    hold(&[1, 8, 2]);
+    assert!(!check("360"));
+    
+    // holding forward and back together is neither
+    neutral();
+    hold(&[8, 9, 3]);
+    assert!(!check("qcf"));
+    
+    neutral();
+    hold(&[2; 45]);
+    hold(&[1]);
+    assert!(check("charge.bf"));
+    assert!(!check("charge.bf, 0, 50"));
+    assert!(!check("charge.du"));
+    
+    neutral();
+    hold(&[1, 1, 0, 0, 1]);
+    assert!(check("tap.f"));
+    assert!(!check("tap.b"));
+    neutral();
+    hold(&[1, 1, 1, 1]);
+    assert!(!check("tap.f"));
+    
+    // snapshots keep the history, so rolling back keeps motions that were in progress
+    hold(&[8, 9, 1]);
+    let snapshot = storage::with(p1.get_ptr(), |store| store.save_snapshot());
+    neutral();
+    assert!(!check("qcf"));
+    storage::with(p1.get_ptr(), |store| store.load_snapshot(&snapshot)).unwrap();
+    assert!(check("qcf"));
+    
+    let warnings = |bytes : &[u8]| validate(bytes).iter().filter(|d| d.severity == Severity::Warning).count();
+    let errors = |bytes : &[u8]| validate(bytes).iter().filter(|d| d.severity == Severity::Error).count();
+    assert_eq!(warnings(&assemble("motion b01, me, charge.bf, 60, 60").unwrap()), 1);
+    assert_eq!(warnings(&assemble("motion b01, me, qcf, 60").unwrap()), 0);
+    assert_eq!(errors(&to_bytes("66000000 60000000 09000401 00000000")), 1);
+    assert!(assemble("motion b01, me, 214").is_err());
+    assert!(assemble("motion b01, me, qcf, 200").is_err());
+}
+
+#[test]
+fn test_socd() {
+    use crate::socd::{SocdResolver, SocdPolicy, SocdMode};
+    
+    const RIGHT : u32 = 0x01;
+    const LEFT : u32 = 0x02;
+    const UP : u32 = 0x04;
+    const DOWN : u32 = 0x08;
+    // anything else the game puts in there gets left alone
+    const BUTTON : u32 = 0x100;
+    
+    let run = |horizontal : SocdMode, vertical : SocdMode, frames : &[u32]| -> Vec<u32> {
+        let mut resolver = SocdResolver::new(SocdPolicy { horizontal, vertical });
+        
+        frames.iter().map(|inputs| resolver.resolve(0, *inputs)).collect()
+    };
+    
+    use SocdMode::*;
+    
+    // the default is what input_parse always did
+    assert_eq!(SocdPolicy::DEFAULT, SocdPolicy { horizontal : Off, vertical : UpPriority });
+    assert_eq!(run(Off, UpPriority, &[UP | DOWN | BUTTON, LEFT | RIGHT | DOWN]), [UP | BUTTON, LEFT | RIGHT | DOWN]);
+    assert_eq!(run(Off, Off, &[UP | DOWN, UP | DOWN | LEFT]), [UP | DOWN, UP | DOWN | LEFT]);
+    assert_eq!(run(Neutral, UpPriority, &[UP | DOWN | BUTTON, LEFT | RIGHT | DOWN]), [UP | BUTTON, DOWN]);
+    
+    // left and right can't be set from mag_patch.ini yet, but they're still checked here for when they can
+    assert_eq!(run(LastInputWins, LastInputWins, &[DOWN, DOWN | UP, DOWN | UP, DOWN, LEFT, LEFT | RIGHT]), [DOWN, UP, UP, DOWN, LEFT, RIGHT]);
+    assert_eq!(run(FirstInputWins, FirstInputWins, &[DOWN, DOWN | UP, DOWN | UP, UP, LEFT, LEFT | RIGHT]), [DOWN, DOWN, DOWN, UP, LEFT, LEFT]);
+    
+    // pressed on the same frame, so neither wins, even after
+    assert_eq!(run(LastInputWins, LastInputWins, &[UP | DOWN, UP | DOWN]), [0, 0]);
+    assert_eq!(run(FirstInputWins, FirstInputWins, &[LEFT | RIGHT, LEFT | RIGHT | UP]), [0, UP]);
+    
+    // up priority has nothing to prefer between left and right
+    assert_eq!(run(UpPriority, UpPriority, &[LEFT | RIGHT | UP | DOWN]), [UP]);
+    
+    // each player keeps track separately
+    let mut resolver = SocdResolver::new(SocdPolicy { horizontal : SocdMode::LastInputWins, vertical : SocdMode::Neutral });
+    assert_eq!(resolver.resolve(1, LEFT), LEFT);
+    assert_eq!(resolver.resolve(2, RIGHT), RIGHT);
+    assert_eq!(resolver.resolve(1, LEFT | RIGHT), RIGHT);
+    assert_eq!(resolver.resolve(2, LEFT | RIGHT), LEFT);
+    assert_eq!(resolver.resolve(1, UP | DOWN), 0);
+    
+    assert_eq!(SocdMode::from_name("NEUTRAL"), Some(Neutral));
+    assert_eq!(SocdMode::from_name("sideways"), None);
+}
+
+#[test]
+fn test_config() {
+    use crate::config::{self, Config, LogLevel};
+    use crate::socd::{SocdMode, SocdPolicy};
+    use crate::storage::Persistence;
+    
+    // nothing there is everything on its default
+    assert_eq!(config::parse(""), (Config::default(), Vec::new()));
+    assert_eq!(config::parse("; just a comment\n\n[hooks]\n"), (Config::default(), Vec::new()));
+    
+    let text = "
+        [hooks]
+        character_ticks = true
+        input_parse = FALSE   ; no SOCD cleanup at all
+        
+        [SOCD]
+        vertical = neutral
+        
+        [log]
+        level = info
+        
+        [persistence]
+        registers = match
+        bools = unload
+        
+        [commands]
+        groups = 0x66, 0x70, 0x66, 200
+        error_register = 0x7F
+    ";
+    
+    let (parsed, errors) = config::parse(text);
+    assert!(errors.is_empty());
+    assert!(parsed.hooks.character_ticks && parsed.hooks.anmchr_commands && !parsed.hooks.input_parse);
+    assert_eq!(parsed.socd.horizontal, SocdMode::Off);
+    assert_eq!(parsed.socd.vertical, SocdMode::Neutral);
+    assert_eq!(parsed.log_level, LogLevel::Info);
+    assert_eq!(parsed.persistence.registers, Persistence::ResetOnMatch);
+    assert_eq!(parsed.persistence.bools, Persistence::KeepUntilUnload);
+    assert_eq!(parsed.command_groups, [0x66, 0x70, 200]);
+    assert_eq!(parsed.error_register, Some(0x7F));
+    assert_eq!(Config::default().error_register, None);
+    
+    // only integer registers can hold an error code
+    assert_eq!(config::parse("[commands]\nerror_register = OFF").0.error_register, None);
+    assert!(config::parse("[commands]\nerror_register = 0x80").1[0].msg.contains("0x7F"));
+    
+    // 66 can't be left out, or every 66 command would stop working
+    assert_eq!(config::parse("[commands]\ngroups = 0x70").0.command_groups, [0x66, 0x70]);
+    
+    // every problem gets reported, each with its line
+    let text = "
+        level = debug
+        [hooks]
+        input_parse = maybe
+        input_parse = true
+        [sound]
+        volume = 11
+        [log]
+        level = loud
+        verbose = true
+        [commands]
+        groups = 0x05
+        [persistence
+        bools =
+        registers
+    ";
+    
+    let errors = config::parse(text).1;
+    let lines : Vec<usize> = errors.iter().map(|error| error.line).collect();
+    assert_eq!(lines, [2, 4, 5, 6, 9, 10, 12, 13, 14, 15]);
+    assert!(errors[0].msg.contains("inside a section"));
+    assert!(errors[1].msg.contains("true or false"));
+    assert!(errors[2].msg.contains("more than once"));
+    assert!(errors[3].msg.contains("[sound]"));
+    assert!(errors[4].msg.contains("loud"));
+    assert!(errors[5].msg.contains("verbose"));
+    assert!(errors[6].msg.contains("belongs to the game"));
+    assert!(errors[8].msg.contains("missing its value"));
+    assert_eq!(errors[1].to_string(), "mag_patch.ini line 4: `input_parse` should be true or false, got `maybe`");
+    
+    // the lines with problems are skipped, and everything else still counts
+    let text = "
+        [hooks]
+        input_parse = false
+        character_tick = false
+        [log]
+        level = loud
+        [persistence]
+        registers = match
+    ";
+    
+    let (parsed, errors) = config::parse(text);
+    assert_eq!(errors.len(), 2);
+    assert!(!parsed.hooks.input_parse);
+    assert!(parsed.hooks.character_ticks);
+    assert_eq!(parsed.log_level, Config::default().log_level);
+    assert_eq!(parsed.persistence.registers, Persistence::ResetOnMatch);
+    
+    // a key that's set twice keeps the first one
+    let (parsed, errors) = config::parse("[socd]\nvertical = neutral\nvertical = off");
+    assert_eq!(errors.len(), 1);
+    assert_eq!(parsed.socd.vertical, SocdMode::Neutral);
+    
+    // left and right can't be turned on until their bits are checked, but the rest of the section still counts
+    let (parsed, errors) = config::parse("[socd]\nhorizontal = last\nvertical = neutral");
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].line, 2);
+    assert_eq!(parsed.socd, SocdPolicy { horizontal : SocdMode::Off, vertical : SocdMode::Neutral });
+}
+
+#[test]
+fn test_logging() {
+    use crate::logging::{RotatingFile, RateLimiter, format_line, KEPT_LOGS};
+    use crate::config::LogLevel;
+    
+    assert_eq!(format_line(1234, "Fighting", "p2.0 id 0x2A", LogLevel::Warn, "hello"),
+        "[frame 1234 | Fighting | p2.0 id 0x2A] warn: hello");
+    
+    let mut limiter = RateLimiter::new(2);
+    assert!(limiter.allow() && limiter.allow());
+    assert!(!limiter.allow() && !limiter.allow());
+    assert_eq!(limiter.new_frame(), 2);
+    assert!(limiter.allow());
+    assert_eq!(limiter.new_frame(), 0);
+    
+    let directory = std::env::temp_dir().join(format!("mag_patch_log_test_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&directory);
+    std::fs::create_dir_all(&directory).unwrap();
+    
+    // each line is 10 bytes with its newline, so two fit before it rotates
+    let mut file = RotatingFile::open(&directory, 20).unwrap();
+    
+    for line in 0..12 {
+        file.write_line(&format!("line {:04}", line)).unwrap();
+    }
+    
+    let read = |index| std::fs::read_to_string(RotatingFile::path(&directory, index)).unwrap();
+    assert_eq!(read(0), "line 0010\nline 0011\n");
+    assert_eq!(read(1), "line 0008\nline 0009\n");
+    assert_eq!(read(KEPT_LOGS), "line 0004\nline 0005\n");
+    assert!(!RotatingFile::path(&directory, KEPT_LOGS + 1).exists());
+    
+    // reopening keeps adding to the same file, and knows how big it already is
+    drop(file);
+    let mut file = RotatingFile::open(&directory, 20).unwrap();
+    file.write_line("line 0012").unwrap();
+    assert_eq!(read(0), "line 0012\n");
+    assert_eq!(read(1), "line 0010\nline 0011\n");
+    
+    std::fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn test_command_errors() {
+    use crate::game_data::Team;
+    use crate::command_error::{self, CommandError};
+    
+    let game = simulated_game();
+    
+    let p1 = game.add_character(&Team::Player1, 0, 1);
+    let error_code = || storage::with(p1.get_ptr(), |store| store.get_i32_register(0x7F));
+    
+    // no register gets touched unless mag_patch.ini says which one
+    let bytes = Box::leak(to_bytes("66000000 7F000000").into_boxed_slice());
+    execute_simulated(&p1, "load.imm r7F, 9");
+    crate::execute_anmchr_command(p1.get_ptr() + 0x1348, bytes.as_mut_ptr() as usize);
+    assert_eq!(error_code(), 9);
+    
+    command_error::set_error_register(Some(0x7F));
+    
+    let failures = command_error::failure_count();
+    
+    // not a command, so it's skipped and the game's own command still runs
+    crate::execute_anmchr_command(p1.get_ptr() + 0x1348, bytes.as_mut_ptr() as usize);
+    assert_eq!(error_code(), 2);
+    assert!(command_error::failure_count() > failures);
+    
+    // commands after it still work, and leave the error code alone
+    execute_simulated(&p1, "load.imm r01, 5");
+    assert_eq!(storage::with(p1.get_ptr(), |store| store.get_i32_register(0x01)), 5);
+    assert_eq!(error_code(), 2);
+    execute_simulated(&p1, "load.imm r7F, 0");
+    assert_eq!(error_code(), 0);
+    
+    // a group set aside in mag_patch.ini, which doesn't have any commands yet. it's only mag_patch's once it's set aside
+    let reserved = Box::leak(to_bytes("67000000 00000000").into_boxed_slice());
+    crate::execute_anmchr_command(p1.get_ptr() + 0x1348, reserved.as_mut_ptr() as usize);
+    assert_eq!(error_code(), 0);
+    
+    crate::anmchr_commands::set_command_groups(&[0x66, 0x67]);
+    let failures = command_error::failure_count();
+    crate::execute_anmchr_command(p1.get_ptr() + 0x1348, reserved.as_mut_ptr() as usize);
+    assert_eq!(error_code(), 2);
+    assert!(command_error::failure_count() > failures);
+    execute_simulated(&p1, "load.imm r7F, 0");
+    
+    // a game command whose table of argument types runs off the end of what reload looks at
+    let mut game_command = to_bytes("00000000 00000000 80000000 00000000");
+    game_command.extend(std::iter::repeat_n([0x03, 0x00, 0x00, 0x00], 0x80).flatten());
+    let failures = command_error::failure_count();
+    crate::execute_anmchr_command(p1.get_ptr() + 0x1348, game_command.as_mut_ptr() as usize);
+    assert_eq!(error_code(), 3);
+    assert!(command_error::failure_count() > failures);
+    
+    // a panic is caught, and doesn't leave storage locked up
+    let result = command_error::catch(|| storage::with(p1.get_ptr(), |_| panic!("on purpose")));
+    assert_eq!(result, Err(CommandError::Failed(String::from("on purpose"))));
+    execute_simulated(&p1, "load.imm r01, 6");
+    assert_eq!(storage::with(p1.get_ptr(), |store| store.get_i32_register(0x01)), 6);
+}
+
+#[test]
+fn test_game_build() {
+    use crate::game_build::{Fingerprint, GameBuild, STEAM, BUILDS, identify, fallback};
+    
+    // just enough of a 64 bit exe's headers: the MZ header pointing at the PE header at 0x80, then the file header
+    // and the start of the optional header
+    let mut headers = vec![0u8; 0x400];
+    headers[0..2].copy_from_slice(b"MZ");
+    headers[0x3C..0x40].copy_from_slice(&0x80u32.to_le_bytes());
+    headers[0x80..0x84].copy_from_slice(b"PE\0\0");
+    headers[0x84..0x86].copy_from_slice(&0x8664u16.to_le_bytes());
+    headers[0x88..0x8C].copy_from_slice(&0x5A0B_1234u32.to_le_bytes());
+    headers[0x98 + 56..0x98 + 60].copy_from_slice(&0x0200_0000u32.to_le_bytes());
+    headers[0x98 + 60..0x98 + 64].copy_from_slice(&0x400u32.to_le_bytes());
+    
+    let fingerprint = Fingerprint::from_headers(&headers).unwrap();
+    assert_eq!(fingerprint.timestamp, 0x5A0B_1234);
+    assert_eq!(fingerprint.image_size, 0x0200_0000);
+    
+    // anything in the headers changes the hash, even if the timestamp and size don't
+    let mut patched = headers.clone();
+    patched[0x300] = 1;
+    let patched = Fingerprint::from_headers(&patched).unwrap();
+    assert_eq!(patched.timestamp, fingerprint.timestamp);
+    assert_ne!(patched.headers_hash, fingerprint.headers_hash);
+    
+    // not an exe, or a 32 bit one
+    assert_eq!(Fingerprint::from_headers(&headers[..0x40]), None);
+    let mut not_64_bit = headers.clone();
+    not_64_bit[0x84..0x86].copy_from_slice(&0x014Cu16.to_le_bytes());
+    assert_eq!(Fingerprint::from_headers(&not_64_bit), None);
+    
+    // an exe nobody has written down isn't anything, even if everything would fit in it
+    assert!(identify(&fingerprint, BUILDS).is_none());
+    
+    // a build only matches that exact exe, and one without a fingerprint never does
+    let builds : &'static [GameBuild] = Box::leak(Box::new([
+        GameBuild { name : "unknown", fingerprint : None, offsets : STEAM.offsets },
+        GameBuild { name : "known", fingerprint : Some(fingerprint), offsets : STEAM.offsets },
+    ]));
+    assert_eq!(identify(&fingerprint, builds).map(|build| build.name), Some("known"));
+    assert!(identify(&patched, builds).is_none());
+    let same_size = Fingerprint { timestamp : 0, ..fingerprint };
+    assert!(identify(&same_size, builds).is_none());
+    
+    // the fallback stands in for exes it fits inside of, but only until its own fingerprint is written down
+    assert_eq!(fallback(&fingerprint, &STEAM).map(|build| build.name), Some("steam"));
+    let too_small = Fingerprint { image_size : 0x1000, ..fingerprint };
+    assert!(fallback(&too_small, &STEAM).is_none());
+    assert!(fallback(&patched, &builds[1]).is_none());
+}
@@ -25,6 +25,30 @@ macro_rules! unary_operators {
             )+
         }
         
+        impl UnaryOp {
+            /// the name of the operation, as written in the list above
+            pub fn name(self) -> &'static str
+            {
+                match self {
+                    $(
+                    UnaryOp::$name => stringify!($name),
+                    )+
+                }
+            }
+            
+            /// look up an operation by name, ignoring case
+            pub fn from_name(name : &str) -> Option<Self>
+            {
+                $(
+                if name.eq_ignore_ascii_case(stringify!($name)) {
+                    return Some(UnaryOp::$name);
+                }
+                )+
+                
+                None
+            }
+        }
+        
         pub trait UnaryOpHandler<T> {
             fn operate(self, value : T) -> T;
         }
@@ -125,14 +149,14 @@ unary_operators! {
         0x10, SqrtWithNegative,
         |value : f32| {
             if value < 0.0 {
-                value.abs().sqrt() * -1.0
+                -value.abs().sqrt()
             } else {
                 value.sqrt()
             }
         },
         |value : i32| {
             if value < 0 {
-                value.abs().isqrt() * -1
+                value.abs().isqrt().saturating_neg()
             } else {
                 value.isqrt()
             }
@@ -180,6 +204,91 @@ unary_operators! {
         },
         |value : bool| { value },
     ),
+    (
+        /// approximate tangent of the number, sine divided by cosine. approximation is used for the same reason as Sin and Cos.
+        /// right on the spots where tangent goes to infinity (a quarter turn, three quarters of a turn, and so on) it gives 0 instead. close to them, it gets very big.
+        /// for integers, it takes the angle in the same units as Sin and Cos do and gives the answer times 16384, saturating at the integer limits
+        0x14, Tan,
+        |value : f32| {
+            approx_tan(value)
+        },
+        |value : i32| {
+            let value = (value as f32) * DEGREES_TO_RADIANS;
+            
+            // as saturates
+            (approx_tan(value) * TRIG_INT_SCALE) as i32
+        },
+        |value : bool| { value },
+    ),
+    (
+        /// approximate e to the power of the number, so 1.0 gives 2.718... and 0 gives 1.
+        /// for floats, answers too big for a float (the number is about 88.7 or more) give the biggest float instead.
+        /// for integers, the fraction is chopped off, so negative numbers give 0. 22 and up saturate to the biggest integer
+        0x15, Exp,
+        |value : f32| {
+            saturate_float(approx_exp(value))
+        },
+        |value : i32| {
+            // e^21 is the last one that fits
+            if value >= 22 {
+                i32::MAX
+            } else {
+                approx_exp(value as f32) as i32
+            }
+        },
+        |_value : bool| { true },
+    ),
+    (
+        /// approximate natural logarithm, the opposite of Exp. so 2.718... gives 1.0 and 1 gives 0.
+        /// 0 and negative numbers have no logarithm, so they give 0.
+        /// for integers, the fraction is chopped off
+        0x16, Ln,
+        |value : f32| {
+            approx_ln(value)
+        },
+        |value : i32| {
+            approx_ln(value as f32) as i32
+        },
+        |_value : bool| { false },
+    ),
+    (
+        /// approximate logarithm base 2, so 8.0 gives 3.0 and 1 gives 0.
+        /// 0 and negative numbers have no logarithm, so they give 0.
+        /// for integers, this is exact, and the fraction is chopped off. so 8 through 15 all give 3
+        0x17, Log2,
+        |value : f32| {
+            approx_log2(value)
+        },
+        |value : i32| {
+            if value > 0 {
+                value.ilog2() as i32
+            } else {
+                0
+            }
+        },
+        |_value : bool| { false },
+    ),
+    (
+        /// wraps an angle around so it's within half a turn either way. for floats that's from -pi up to (not including) pi, so 4.0 gives 4.0 - 2pi = -2.28...
+        /// for integers, it's in the same units as Sin and Cos, from -60 up to (not including) 60. this can't overflow
+        0x18, NormalizeAngle,
+        |value : f32| {
+            normalize_angle(value)
+        },
+        |value : i32| {
+            // rem_euclid is never negative, and the divisor isn't 0, so none of this can overflow
+            #[allow(clippy::arithmetic_side_effects)]
+            let wrapped = value.rem_euclid(DEGREES_PER_TURN);
+            
+            #[allow(clippy::arithmetic_side_effects)]
+            if wrapped >= DEGREES_PER_TURN / 2 {
+                wrapped - DEGREES_PER_TURN
+            } else {
+                wrapped
+            }
+        },
+        |value : bool| { value },
+    ),
     
     
     (
@@ -202,7 +311,7 @@ unary_operators! {
     (
         /// the number multiplied by -1
         0x22, Negate,
-        |value : f32| { value * -1.0 },
+        |value : f32| { -value },
         |value : i32| { value.saturating_neg() },
         |value : bool| { !value },
     ),
@@ -224,6 +333,16 @@ unary_operators! {
         |value : bool| { value },
     ),
     
+    (
+        /// a random number from 0 up to the number, not including the number itself. so 6 gives 0, 1, 2, 3, 4 or 5, and 1.0 gives anything from 0.0 to just under 1.0.
+        /// negative numbers go the other way, so -6 gives -5 through 0. 0 always gives 0. a boolean gives a coin flip if it's true, and false if it's false.
+        /// the same commands will roll the same numbers every time, including in replays and netplay. see RandomState in the variables list
+        0x40, Random,
+        |value : f32| { crate::random::below_f32(value) },
+        |value : i32| { crate::random::below_i32(value) },
+        |value : bool| { value && crate::random::below_i32(2) == 1 },
+    ),
+    
     (
         /// flips all the bits 0 to 1 and 1 to 0. see also https://en.wikipedia.org/wiki/Bitwise_operation#NOT
         /// note that doing this on floating point numbers won't be very useful as mag_patch resets any NaN/infinity floats to 0.0
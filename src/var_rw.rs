@@ -18,6 +18,8 @@ macro_rules! var_rw {
     } => {
         #[derive(FromPrimitive)]
         #[repr(u32)]
+        // the names come straight from the variable lists below, like MatchState's MatchStateReadOnly
+        #[allow(clippy::enum_variant_names)]
         pub enum $type_name
         {
             $(
@@ -30,6 +32,40 @@ macro_rules! var_rw {
         #[expect(clippy::unnecessary_cast)]
         impl $type_name {
             
+            /// the name of the variable, as written in the list below
+            pub fn name(var : u32) -> Option<&'static str>
+            {
+                let var = num::FromPrimitive::from_u32(var);
+                
+                if let Some(var) = var {
+                    match var {
+                        $(
+                        $type_name::$name => Some(stringify!($name)),
+                        )+
+                    }
+                } else {
+                    None
+                }
+            }
+            
+            /// look up a variable id by name, ignoring case
+            pub fn from_name(name : &str) -> Option<u32>
+            {
+                $(
+                if name.eq_ignore_ascii_case(stringify!($name)) {
+                    return Some($type_name::$name as u32);
+                }
+                )+
+                
+                None
+            }
+            
+            /// variables named ...ReadOnly ignore writes
+            pub fn is_read_only(var : u32) -> bool
+            {
+                $type_name::name(var).is_some_and(|name| name.ends_with("ReadOnly"))
+            }
+            
             fn load(owner_ptr : usize, var : u32) -> Number
             {
                 let var = num::FromPrimitive::from_u32(var);
@@ -148,6 +184,12 @@ macro_rules! var_rw {
     }
 }
 
+/// like Char::facing_x, for whoever owns the projectile. left as stage x if the owner isn't a character we know
+fn owner_facing_x(projectile : &Projectile, x : f32) -> f32
+{
+    Char::if_valid(projectile.get_current_owner().get_ptr(), x, |c| c.facing_x(x))
+}
+
 var_rw! {
     { ProjectileState };
     
@@ -197,6 +239,84 @@ var_rw! {
             })
         },
     ),
+    (
+        /// how far it moves along x next frame, in stage space. positive is toward the right side of the stage
+        0x22, XVelocityReadOnly,
+        F32,
+        |ptr| {
+            Number::F32(Projectile::if_valid(ptr, 0.0, |p| {
+                p.get_x_velocity()
+            }))
+        },
+        |_, _| {
+            // don't set
+        },
+    ),
+    (
+        /// how far it moves along y next frame, upward is positive
+        0x23, YVelocityReadOnly,
+        F32,
+        |ptr| {
+            Number::F32(Projectile::if_valid(ptr, 0.0, |p| {
+                p.get_y_velocity()
+            }))
+        },
+        |_, _| {
+            // don't set
+        },
+    ),
+    (
+        /// added to XVelocityReadOnly every frame
+        0x24, XAccelerationReadOnly,
+        F32,
+        |ptr| {
+            Number::F32(Projectile::if_valid(ptr, 0.0, |p| {
+                p.get_x_acceleration()
+            }))
+        },
+        |_, _| {
+            // don't set
+        },
+    ),
+    (
+        /// added to YVelocityReadOnly every frame. this is gravity, so it's negative while falling
+        0x25, YAccelerationReadOnly,
+        F32,
+        |ptr| {
+            Number::F32(Projectile::if_valid(ptr, 0.0, |p| {
+                p.get_y_acceleration()
+            }))
+        },
+        |_, _| {
+            // don't set
+        },
+    ),
+    (
+        /// XVelocityReadOnly, but positive is the way the character that owns it is facing
+        0x26, ForwardXVelocityReadOnly,
+        F32,
+        |ptr| {
+            Number::F32(Projectile::if_valid(ptr, 0.0, |p| {
+                owner_facing_x(&p, p.get_x_velocity())
+            }))
+        },
+        |_, _| {
+            // don't set
+        },
+    ),
+    (
+        /// XAccelerationReadOnly, but positive is the way the character that owns it is facing
+        0x27, ForwardXAccelerationReadOnly,
+        F32,
+        |ptr| {
+            Number::F32(Projectile::if_valid(ptr, 0.0, |p| {
+                owner_facing_x(&p, p.get_x_acceleration())
+            }))
+        },
+        |_, _| {
+            // don't set
+        },
+    ),
     
     (
         /// Projectile Class ID
@@ -207,9 +327,7 @@ var_rw! {
                 p.get_shot_resource().get_shot_file().get_type_hash()
             }))
         },
-        |_ptr, _new_value| {
-            ()
-        },
+        |_ptr, _new_value| {},
     ),
 }
 
@@ -236,6 +354,14 @@ var_rw! {
         |_| {  Number::I32(match_state::get_match_state() as i32) },
         |_, _| { },
     ),
+    (
+        /// The state of the random number generator used by the Random operation. It's the same for everyone, and starts over at the start of each match.
+        /// Storing a value here reseeds it, and storing a value you loaded before makes it repeat the same rolls.
+        0x03, RandomState,
+        I32,
+        |_| {  Number::I32(crate::random::get_state()) },
+        |_, new_value| { crate::random::set_state(new_value as i32); }
+    ),
     (
         0x10, Health,
         F32,
@@ -320,6 +446,84 @@ var_rw! {
             })
         },
     ),
+    (
+        /// how far it moves along x next frame, in stage space. positive is toward the right side of the stage
+        0x22, XVelocityReadOnly,
+        F32,
+        |ptr| {
+            Number::F32(Char::if_valid(ptr, 0.0, |c| {
+                c.get_x_velocity()
+            }))
+        },
+        |_, _| {
+            // don't set
+        },
+    ),
+    (
+        /// how far it moves along y next frame, upward is positive
+        0x23, YVelocityReadOnly,
+        F32,
+        |ptr| {
+            Number::F32(Char::if_valid(ptr, 0.0, |c| {
+                c.get_y_velocity()
+            }))
+        },
+        |_, _| {
+            // don't set
+        },
+    ),
+    (
+        /// added to XVelocityReadOnly every frame
+        0x24, XAccelerationReadOnly,
+        F32,
+        |ptr| {
+            Number::F32(Char::if_valid(ptr, 0.0, |c| {
+                c.get_x_acceleration()
+            }))
+        },
+        |_, _| {
+            // don't set
+        },
+    ),
+    (
+        /// added to YVelocityReadOnly every frame. this is gravity, so it's negative while falling
+        0x25, YAccelerationReadOnly,
+        F32,
+        |ptr| {
+            Number::F32(Char::if_valid(ptr, 0.0, |c| {
+                c.get_y_acceleration()
+            }))
+        },
+        |_, _| {
+            // don't set
+        },
+    ),
+    (
+        /// XVelocityReadOnly, but positive is the way the character is facing. same idea as 66_00
+        0x26, ForwardXVelocityReadOnly,
+        F32,
+        |ptr| {
+            Number::F32(Char::if_valid(ptr, 0.0, |c| {
+                c.facing_x(c.get_x_velocity())
+            }))
+        },
+        |_, _| {
+            // don't set
+        },
+    ),
+    (
+        /// XAccelerationReadOnly, but positive is the way the character is facing
+        0x27, ForwardXAccelerationReadOnly,
+        F32,
+        |ptr| {
+            Number::F32(Char::if_valid(ptr, 0.0, |c| {
+                c.facing_x(c.get_x_acceleration())
+            }))
+        },
+        |_, _| {
+            // don't set
+        },
+    ),
     (
         /// counts up from 0 every time you do a special in the air. is used to limit specials to 3 normally
         0x30, SpecialAirActionCounter,
@@ -380,6 +584,71 @@ var_rw! {
             })
         },
     ),
+    (
+        /// note: this is on the character getting hit. 1.0 is full damage, and it goes down over a combo
+        0x34, DamageScalingReadOnly,
+        F32,
+        |ptr| {
+            Number::F32(Char::if_valid(ptr, 0.0, |c| {
+                c.get_damage_scaling()
+            }))
+        },
+        |_, _| {
+            // don't set
+        },
+    ),
+    (
+        /// note: this is on the character getting hit. 1.0 is full hitstun, and it goes down over a combo
+        0x35, HitstunScalingReadOnly,
+        F32,
+        |ptr| {
+            Number::F32(Char::if_valid(ptr, 0.0, |c| {
+                c.get_hitstun_scaling()
+            }))
+        },
+        |_, _| {
+            // don't set
+        },
+    ),
+    (
+        /// note: this is on the character getting hit. how many hits they've taken in the current combo
+        0x36, TimesHitReadOnly,
+        I32,
+        |ptr| {
+            Number::I32(Char::if_valid(ptr, 0, |c| {
+                c.get_times_hit()
+            }))
+        },
+        |_, _| {
+            // don't set
+        },
+    ),
+    (
+        /// note: this is on the character getting hit. 1 if a ground bounce has been used in this combo
+        0x37, GroundBounceUsedReadOnly,
+        I32,
+        |ptr| {
+            Number::I32(Char::if_valid(ptr, 0, |c| {
+                c.get_ground_bounce_used().from_bool()
+            }))
+        },
+        |_, _| {
+            // don't set
+        },
+    ),
+    (
+        /// note: this is on the character getting hit. 1 if a wall bounce has been used in this combo
+        0x38, WallBounceUsedReadOnly,
+        I32,
+        |ptr| {
+            Number::I32(Char::if_valid(ptr, 0, |c| {
+                c.get_wall_bounce_used().from_bool()
+            }))
+        },
+        |_, _| {
+            // don't set
+        },
+    ),
     (
         /// super meter. 50000.0 is the max. 10000.0 is one bar
         0x40, Meter,
@@ -462,6 +731,74 @@ var_rw! {
             // don't set
         },
     ),
+    (
+        /// frames of x-factor left for the team, 0 if it isn't active
+        0x45, XFactorTimerReadOnly,
+        F32,
+        |ptr| {
+            Number::F32(Char::if_valid(ptr, 0.0, |c| {
+                if let Some(player) = c.player() {
+                    player.get_xfactor_timer()
+                } else {
+                    0.0
+                }
+            }))
+        },
+        |_, _| {
+            // don't set
+        },
+    ),
+    (
+        /// how long the team's x-factor lasted when it was activated
+        0x46, XFactorMaxTimeReadOnly,
+        F32,
+        |ptr| {
+            Number::F32(Char::if_valid(ptr, 0.0, |c| {
+                if let Some(player) = c.player() {
+                    player.get_xfactor_max_timer()
+                } else {
+                    0.0
+                }
+            }))
+        },
+        |_, _| {
+            // don't set
+        },
+    ),
+    (
+        /// 1 through 3 once the team has used x-factor, 0 before
+        0x47, XFactorLevelReadOnly,
+        I32,
+        |ptr| {
+            Number::I32(Char::if_valid(ptr, 0, |c| {
+                if let Some(player) = c.player() {
+                    player.get_xfactor_level()
+                } else {
+                    0
+                }
+            }))
+        },
+        |_, _| {
+            // don't set
+        },
+    ),
+    (
+        /// 1 while the team's x-factor is running, 0 otherwise
+        0x48, XFactorActiveReadOnly,
+        I32,
+        |ptr| {
+            Number::I32(Char::if_valid(ptr, 0, |c| {
+                if let Some(player) = c.player() {
+                    player.is_xfactor_active().from_bool()
+                } else {
+                    0
+                }
+            }))
+        },
+        |_, _| {
+            // don't set
+        },
+    ),
     
     (
         /// 1 if facing left, 0 if facing right